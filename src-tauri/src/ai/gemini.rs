@@ -217,6 +217,9 @@ impl AIService for GeminiService {
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(AIServiceError::RateLimited(error_text));
+            }
             return Err(AIServiceError::ApiError(format!(
                 "API returned status {}: {}",
                 status, error_text
@@ -0,0 +1,56 @@
+use super::traits::{AIResponse, AIService, AIServiceError, ChatMessage, ToolCall};
+use std::sync::Mutex;
+
+/// Offline stand-in for a real provider: always answers with the same canned response
+pub struct MockAIService {
+    response: AIResponse,
+    received: Mutex<Vec<Vec<ChatMessage>>>, // Messages from each call, for assertions
+}
+
+impl MockAIService {
+    pub fn new(content: &str) -> Self {
+        Self {
+            response: AIResponse {
+                content: content.to_string(),
+                usage: None,
+                tools: None,
+            },
+            received: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Also return these tool calls with every response
+    #[cfg(test)]
+    pub fn with_tools(mut self, tools: Vec<ToolCall>) -> Self {
+        self.response.tools = Some(tools);
+        self
+    }
+
+    /// Messages passed to each chat call so far
+    #[cfg(test)]
+    pub fn received_messages(&self) -> Vec<Vec<ChatMessage>> {
+        self.received.lock().map(|received| received.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait::async_trait]
+impl AIService for MockAIService {
+    async fn chat(&self, messages: Vec<ChatMessage>) -> Result<AIResponse, AIServiceError> {
+        if let Ok(mut received) = self.received.lock() {
+            received.push(messages);
+        }
+        Ok(self.response.clone())
+    }
+
+    async fn chat_with_context(
+        &self,
+        messages: Vec<ChatMessage>,
+        _context: &str,
+    ) -> Result<AIResponse, AIServiceError> {
+        self.chat(messages).await
+    }
+
+    fn get_model_name(&self) -> &str {
+        "mock"
+    }
+}
@@ -1,8 +1,21 @@
 mod gemini;
+mod mock;
 mod traits;
 mod tools;
 
 pub use gemini::GeminiService;
+pub use mock::MockAIService;
 pub use traits::{AIService, AIServiceError, ChatMessage, AIResponse, ToolCall};
 pub use tools::get_available_tools;
 
+/// The AI provider used by the assistant commands.
+/// Set AI_PROVIDER=mock to get canned replies without a Gemini key.
+pub fn create_ai_service() -> Result<Box<dyn AIService>, AIServiceError> {
+    match std::env::var("AI_PROVIDER").ok().as_deref() {
+        Some("mock") => Ok(Box::new(MockAIService::new(
+            "The assistant is running in mock mode (AI_PROVIDER=mock), so no AI provider was contacted.",
+        ))),
+        _ => Ok(Box::new(GeminiService::new()?)),
+    }
+}
+
@@ -4,15 +4,31 @@ use serde::{Deserialize, Serialize};
 pub enum AIServiceError {
     NetworkError(String),
     ApiError(String),
+    RateLimited(String),
     ConfigurationError(String),
     InvalidResponse(String),
 }
 
+impl AIServiceError {
+    /// Short message that is safe to show end users; the Display form keeps the
+    /// detail (URLs, response bodies) for logs
+    pub fn into_user_message(self) -> String {
+        match self {
+            AIServiceError::NetworkError(_) => "Couldn't reach the assistant".to_string(),
+            AIServiceError::ApiError(_) => "The assistant ran into a problem, please try again".to_string(),
+            AIServiceError::RateLimited(_) => "The assistant is busy, try again in a moment".to_string(),
+            AIServiceError::ConfigurationError(_) => "AI isn't configured".to_string(),
+            AIServiceError::InvalidResponse(_) => "The assistant sent a reply that couldn't be read, please try again".to_string(),
+        }
+    }
+}
+
 impl std::fmt::Display for AIServiceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AIServiceError::NetworkError(msg) => write!(f, "Network error: {}", msg),
             AIServiceError::ApiError(msg) => write!(f, "API error: {}", msg),
+            AIServiceError::RateLimited(msg) => write!(f, "Rate limited: {}", msg),
             AIServiceError::ConfigurationError(msg) => write!(f, "Configuration error: {}", msg),
             AIServiceError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
         }
@@ -58,3 +74,27 @@ pub trait AIService: Send + Sync {
     fn get_model_name(&self) -> &str;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_messages_hide_the_detail() {
+        let detail = "https://example.com/v1?key=secret returned 500".to_string();
+        let cases = [
+            (AIServiceError::NetworkError(detail.clone()), "Couldn't reach the assistant"),
+            (AIServiceError::ApiError(detail.clone()), "The assistant ran into a problem, please try again"),
+            (AIServiceError::RateLimited(detail.clone()), "The assistant is busy, try again in a moment"),
+            (AIServiceError::ConfigurationError(detail.clone()), "AI isn't configured"),
+            (
+                AIServiceError::InvalidResponse(detail.clone()),
+                "The assistant sent a reply that couldn't be read, please try again",
+            ),
+        ];
+
+        for (error, expected) in cases {
+            assert!(error.to_string().contains(&detail));
+            assert_eq!(error.into_user_message(), expected);
+        }
+    }
+}
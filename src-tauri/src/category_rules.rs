@@ -0,0 +1,170 @@
+use crate::settings_file::SettingsFile;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+static RULES_FILE: SettingsFile = SettingsFile::new("category_rules.json", "category rules");
+
+/// A user rule mapping app or process names to a category. Plain patterns match
+/// anywhere in the name; both kinds ignore case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub pattern: String,
+    pub category: String,
+    #[serde(default)]
+    pub is_regex: bool,
+}
+
+// A rule with its pattern ready to match
+struct CompiledRule {
+    category: String,
+    matcher: Matcher,
+}
+
+enum Matcher {
+    Contains(String), // Lowercased pattern
+    Regex(Regex),
+}
+
+impl CompiledRule {
+    fn matches(&self, name_lower: &str) -> bool {
+        match &self.matcher {
+            Matcher::Contains(pattern) => name_lower.contains(pattern.as_str()),
+            Matcher::Regex(regex) => regex.is_match(name_lower),
+        }
+    }
+}
+
+// Compiled rules in evaluation order, rebuilt whenever the file changes
+static COMPILED: Lazy<Arc<Mutex<Vec<CompiledRule>>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(Vec::new()))
+});
+
+fn compile(rule: &CategoryRule) -> Result<CompiledRule, String> {
+    let matcher = if rule.is_regex {
+        let regex = RegexBuilder::new(&rule.pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("Invalid regex pattern '{}': {}", rule.pattern, e))?;
+        Matcher::Regex(regex)
+    } else {
+        Matcher::Contains(rule.pattern.to_lowercase())
+    };
+    Ok(CompiledRule { category: rule.category.clone(), matcher })
+}
+
+fn compile_all(rules: &[CategoryRule]) -> Vec<CompiledRule> {
+    rules
+        .iter()
+        .filter_map(|rule| match compile(rule) {
+            Ok(compiled) => Some(compiled),
+            Err(e) => {
+                log::warn!("Skipping category rule: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn first_match<'a>(rules: &'a [CompiledRule], name: &str) -> Option<&'a str> {
+    let name_lower = name.to_lowercase();
+    rules
+        .iter()
+        .find(|rule| rule.matches(&name_lower))
+        .map(|rule| rule.category.as_str())
+}
+
+fn reload(rules: &[CategoryRule]) {
+    if let Ok(mut compiled) = COMPILED.lock() {
+        *compiled = compile_all(rules);
+    }
+}
+
+/// Set the directory the rules file lives in (the app data dir) and load it
+pub fn set_rules_dir(dir: PathBuf) {
+    RULES_FILE.set_dir(dir);
+    reload(&RULES_FILE.read::<Vec<CategoryRule>>());
+}
+
+/// The user's rules in the order they are evaluated
+pub fn category_rules() -> Vec<CategoryRule> {
+    RULES_FILE.read()
+}
+
+/// Category from the first user rule that matches the name, if any
+pub fn category_for(name: &str) -> Option<String> {
+    let compiled = COMPILED.lock().ok()?;
+    first_match(&compiled, name).map(str::to_string)
+}
+
+/// Append a rule, rejecting an empty pattern or category and regexes that don't compile
+pub fn add_rule(pattern: &str, category: &str, is_regex: bool) -> Result<(), String> {
+    let rule = CategoryRule {
+        pattern: pattern.trim().to_string(),
+        category: category.trim().to_string(),
+        is_regex,
+    };
+    if rule.pattern.is_empty() {
+        return Err("Pattern is required".to_string());
+    }
+    if rule.category.is_empty() {
+        return Err("Category is required".to_string());
+    }
+    compile(&rule)?;
+
+    RULES_FILE.update(
+        |rules: &mut Vec<CategoryRule>| {
+            rules.push(rule);
+            Ok(())
+        },
+        |rules| reload(rules),
+    )
+}
+
+/// Remove the rule at `index` in evaluation order
+pub fn remove_rule(index: usize) -> Result<(), String> {
+    RULES_FILE.update(
+        |rules: &mut Vec<CategoryRule>| {
+            if index >= rules.len() {
+                return Err(format!("No category rule at position {}", index));
+            }
+            rules.remove(index);
+            Ok(())
+        },
+        |rules| reload(rules),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, category: &str, is_regex: bool) -> CategoryRule {
+        CategoryRule { pattern: pattern.to_string(), category: category.to_string(), is_regex }
+    }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let rules = compile_all(&[
+            rule("jetbrains", "Development", false),
+            rule(r"^idea\d*", "IDE", true),
+            rule("idea", "Notes", false),
+        ]);
+
+        assert_eq!(first_match(&rules, "JetBrains Toolbox"), Some("Development"));
+        assert_eq!(first_match(&rules, "idea64.exe"), Some("IDE"));
+        assert_eq!(first_match(&rules, "My Ideas"), Some("Notes"));
+        assert_eq!(first_match(&rules, "Spotify"), None);
+    }
+
+    #[test]
+    fn invalid_regexes_are_rejected() {
+        let err = add_rule("(unclosed", "Development", true).unwrap_err();
+
+        assert!(err.contains("Invalid regex pattern"));
+        // The same text is fine as a plain pattern
+        assert!(compile(&rule("(unclosed", "Development", false)).is_ok());
+    }
+}
@@ -1,9 +1,10 @@
-use crate::database::{Database, Application, TimeEntry, Task, User};
+use crate::database::{encode_filter_value, Database, Application, TimeEntry, TimeEntryWithApp, Task, User};
 use crate::default_user::get_default_user_id;
-use super::{get_time_entries_by_user, get_applications_by_user, get_my_tasks, fetch_users_by_workspace};
+use super::{get_time_entries_by_user_with_apps, get_applications_by_user, get_my_tasks, get_tasks_by_assignee, fetch_users_by_workspace, fetch_user_by_email, fetch_time_entries_in_range, fetch_app_time_entries_in_range};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use chrono::{DateTime, Utc, Duration};
+use once_cell::sync::Lazy;
 
 // Data structures for AI assistant insights
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,12 @@ pub struct ProductivityInsights {
   pub current_activity: Option<CurrentActivityInfo>,
   pub task_stats: TaskStats,
   pub productivity_trend: ProductivityTrend,
+  #[serde(default)]
+  pub total_time_last_week: f64, // hours in the 7 days before this week
+  #[serde(default)]
+  pub entries_this_week: usize,
+  #[serde(default)]
+  pub short_entries_this_week: usize, // entries shorter than SHORT_ENTRY_SECONDS
   // Team data (optional)
   pub team_members: Option<Vec<TeamMemberInsights>>,
   pub team_summary: Option<TeamSummary>,
@@ -58,6 +65,14 @@ pub struct AppUsage {
     pub percentage: f64,
 }
 
+/// Today's running totals, emitted periodically as `usage-snapshot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSnapshot {
+    pub total_hours_today: f64,
+    pub top_app: Option<AppUsage>,
+    pub generated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CurrentActivityInfo {
     pub app_name: String,
@@ -86,14 +101,155 @@ pub struct DailyHours {
     pub hours: f64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InsightType {
+    Info,
+    Tip,
+    Warning,
+    Achievement,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Insight {
+    pub title: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub insight_type: InsightType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    pub current_period: String,
+    pub previous_period: String,
+    pub current_hours: f64,
+    pub previous_hours: f64,
+    pub delta: f64,
+    pub percent_change: f64,
+    pub improved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryUsage {
+    pub category: String,
+    pub hours: f64,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppWithUsage {
+    #[serde(flatten)]
+    pub application: Application,
+    pub total_seconds: i64, // Time tracked in the requested period
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklySummary {
+    pub week_start: String, // ISO date of the Monday that starts the week
+    pub week_end: String,   // ISO date of the Sunday that ends the week
+    pub total_hours: f64,
+    pub by_day: Vec<DailyHours>, // Always 7 entries, Monday first
+    pub top_apps: Vec<AppUsage>,
+    pub task_stats: TaskStats,
+    pub tasks_completed_this_week: usize,
+    pub goal_hours: f64,
+    pub goal_attainment: f64, // percentage of goal_hours reached
+}
+
+/// One 0-100 number for a period, with the factors it was built from.
+///
+/// `score = 0.4 * hours_score + 0.3 * task_score + 0.3 * focus_score`, where
+/// - `hours_score` is tracked hours as a percentage of `goal_hours` (8 a day, 40 a week, 160 a month), capped at 100
+/// - `task_score` is the completion rate of the user's tasks
+/// - `focus_score` is the average entry length as a percentage of 25 minutes, capped at 100,
+///   so a few long sessions beat many short ones
+///
+/// With no tasks assigned, the task factor is left out and the other two keep their 4:3 ratio.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivityScore {
+    pub period: String,
+    pub score: f64,
+    pub hours_score: f64,
+    pub task_score: Option<f64>, // None when the user has no tasks
+    pub focus_score: f64,
+    pub tracked_hours: f64,
+    pub goal_hours: f64,
+    pub average_entry_minutes: f64,
+}
+
+/// Structured export of a user's activity over an arbitrary date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivityReport {
+    pub version: u32, // REPORT_SCHEMA_VERSION at export time
+    pub generated_at: DateTime<Utc>,
+    pub range_start: DateTime<Utc>,
+    pub range_end: DateTime<Utc>,
+    pub total_hours: f64,
+    pub entry_count: usize,
+    pub app_usage: Vec<AppUsage>, // Every app used in the range, most used first
+    pub category_usage: Vec<CategoryUsage>,
+    pub task_stats: TaskStats,
+    pub tasks_completed: usize, // Tasks marked done within the range
+    pub daily_hours: Vec<DailyHours>, // One entry per local day in the range
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String, // "user", "assistant", or "system"
     pub content: String,
 }
 
-// Mock data flag - set to true to use mock data instead of real DB queries
-const USE_MOCK_DATA: bool = false;
+/// Time zone used to decide which day (and hour) an entry belongs to in reports
+#[derive(Debug, Clone, Copy)]
+pub enum ReportTimezone {
+    Named(chrono_tz::Tz),
+    Local, // System local zone
+}
+
+impl ReportTimezone {
+    /// Parse an IANA zone name such as "America/Los_Angeles", defaulting to the system zone
+    pub fn from_param(timezone: Option<&str>) -> Result<Self, String> {
+        match timezone.map(str::trim).filter(|tz| !tz.is_empty()) {
+            Some(name) => name
+                .parse::<chrono_tz::Tz>()
+                .map(ReportTimezone::Named)
+                .map_err(|_| format!("Unknown timezone '{}'. Use an IANA name like 'America/Los_Angeles'", name)),
+            None => Ok(ReportTimezone::Local),
+        }
+    }
+
+    /// Wall-clock time of a UTC timestamp in this zone
+    pub fn to_local(&self, time: DateTime<Utc>) -> chrono::NaiveDateTime {
+        match self {
+            ReportTimezone::Named(tz) => time.with_timezone(tz).naive_local(),
+            ReportTimezone::Local => time.with_timezone(&chrono::Local).naive_local(),
+        }
+    }
+
+    /// UTC instant at which the given local date starts in this zone
+    pub fn start_of_day(&self, date: chrono::NaiveDate) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        let start = match self {
+            ReportTimezone::Named(tz) => tz.from_local_datetime(&midnight).earliest().map(|t| t.with_timezone(&Utc)),
+            ReportTimezone::Local => chrono::Local.from_local_datetime(&midnight).earliest().map(|t| t.with_timezone(&Utc)),
+        };
+        // Midnight can be skipped by a DST jump; fall back to treating it as UTC
+        start.unwrap_or_else(|| midnight.and_utc())
+    }
+}
+
+// Set MACRO_USE_MOCK_DATA=1 (or true) to serve canned data instead of querying the database
+static USE_MOCK_DATA: Lazy<bool> = Lazy::new(|| {
+    std::env::var("MACRO_USE_MOCK_DATA")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+});
+
+/// Whether insights and AI tools use mock data rather than the database
+pub fn use_mock_data() -> bool {
+    *USE_MOCK_DATA
+}
 
 // Mock data generator
 pub fn get_mock_productivity_insights() -> ProductivityInsights {
@@ -155,6 +311,9 @@ pub fn get_mock_productivity_insights() -> ProductivityInsights {
             ],
             peak_hours: vec![9, 10, 11, 14, 15, 16], // 9am-11am and 2pm-4pm
         },
+        total_time_last_week: 30.0,
+        entries_this_week: 85,
+        short_entries_this_week: 20,
         team_members: Some(vec![
             TeamMemberInsights {
                 member_id: "user-1".to_string(),
@@ -260,38 +419,47 @@ pub fn get_mock_productivity_insights() -> ProductivityInsights {
 #[tauri::command]
 pub async fn get_productivity_insights(
     db: State<'_, Database>,
+    timezone: Option<String>,
 ) -> Result<ProductivityInsights, String> {
+    let tz = ReportTimezone::from_param(timezone.as_deref())?;
+    if use_mock_data() {
+        return Ok(get_mock_productivity_insights());
+    }
     let user_id = get_default_user_id();
     
-    // Get time entries (last 30 days)
-    let time_entries = get_time_entries_by_user(
+    // Get time entries (last 30 days) with their application names embedded
+    let entries_with_apps = get_time_entries_by_user_with_apps(
         db.clone(),
         user_id.clone(),
         Some(1000),
     ).await.map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+    let time_entries: Vec<TimeEntry> = entries_with_apps.iter().map(|e| e.entry.clone()).collect();
 
-    // Get applications
-    let applications = get_applications_by_user(
-        db.clone(),
-        user_id.clone(),
-    ).await.map_err(|e| format!("Failed to fetch applications: {}", e))?;
-
-    // Get tasks
-    let tasks = get_my_tasks(db.clone()).await
+    // Get tasks, archived ones included since their work still counts
+    let tasks = get_my_tasks(db.clone(), Some(true)).await
         .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
 
     // Calculate time totals
     let now = Utc::now();
-    let today_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let today_start = tz.start_of_day(tz.to_local(now).date());
     let week_start = now - Duration::days(7);
     let month_start = now - Duration::days(30);
 
     let total_time_today = calculate_hours_in_range(&time_entries, today_start, now);
     let total_time_this_week = calculate_hours_in_range(&time_entries, week_start, now);
     let total_time_this_month = calculate_hours_in_range(&time_entries, month_start, now);
+    let total_time_last_week = calculate_hours_in_range(&time_entries, week_start - Duration::days(7), week_start);
+
+    // Entry counts used to spot context switching
+    let entries_this_week = time_entries.iter().filter(|e| e.start_time >= week_start).count();
+    let short_entries_this_week = time_entries
+        .iter()
+        .filter(|e| e.start_time >= week_start)
+        .filter(|e| e.duration_seconds.is_some_and(|d| d < SHORT_ENTRY_SECONDS))
+        .count();
 
     // Calculate most used apps (this week)
-    let most_used_apps = calculate_app_usage(&time_entries, &applications, week_start, now);
+    let most_used_apps = calculate_app_usage(&entries_with_apps, week_start, now, DEFAULT_TOP_APPS);
 
     // Get current activity
     let current_activity = match crate::tracking::get_current_activity().await {
@@ -309,7 +477,7 @@ pub async fn get_productivity_insights(
     let task_stats = calculate_task_stats(&tasks);
 
     // Calculate productivity trend
-    let productivity_trend = calculate_productivity_trend(&time_entries, 7);
+    let productivity_trend = calculate_productivity_trend(&time_entries, 7, &tz);
 
     Ok(ProductivityInsights {
         total_time_today,
@@ -319,12 +487,47 @@ pub async fn get_productivity_insights(
         current_activity,
         task_stats,
         productivity_trend,
+        total_time_last_week,
+        entries_this_week,
+        short_entries_this_week,
         team_members: None, // Individual insights don't include team data
         team_summary: None,
     })
 }
 
-fn calculate_hours_in_range(
+// Hours tracked today and the app that took most of them, counting the running entry up to `now`
+pub(super) async fn usage_snapshot_in_db(db: &Database, user_id: &str, now: DateTime<Utc>) -> Result<UsageSnapshot, String> {
+    let tz = ReportTimezone::Local;
+    let day_start = tz.start_of_day(tz.to_local(now).date());
+    let entries = fetch_time_entries_in_range(db, user_id, day_start, now).await?;
+
+    let total_hours_today = calculate_hours_in_range(&entries, day_start, now);
+    let minutes_by_app = super::minutes_used_by_app(&entries, day_start, now);
+    let total_minutes: i64 = minutes_by_app.values().sum();
+
+    let top_app = match minutes_by_app.into_iter().filter(|(_, minutes)| *minutes > 0).max_by_key(|(_, minutes)| *minutes) {
+        Some((app_id, minutes)) => {
+            let app_name = crate::platform::database_helpers::DatabaseHelpers::get_application(db, &app_id)
+                .await?
+                .map(|app| app.name)
+                .unwrap_or_else(|| "Unknown App".to_string());
+            Some(AppUsage {
+                app_name,
+                hours: minutes as f64 / 60.0,
+                percentage: minutes as f64 / total_minutes as f64 * 100.0,
+            })
+        }
+        None => None,
+    };
+
+    Ok(UsageSnapshot {
+        total_hours_today,
+        top_app,
+        generated_at: now,
+    })
+}
+
+pub(super) fn calculate_hours_in_range(
     entries: &[TimeEntry],
     start: DateTime<Utc>,
     end: DateTime<Utc>,
@@ -345,22 +548,47 @@ fn calculate_hours_in_range(
     total_seconds as f64 / 3600.0
 }
 
+// Attach application names to plain time entries when they weren't fetched embedded
+fn with_app_names(entries: &[TimeEntry], apps: &[Application]) -> Vec<TimeEntryWithApp> {
+    entries
+        .iter()
+        .map(|entry| TimeEntryWithApp {
+            entry: entry.clone(),
+            application: entry
+                .app_id
+                .as_ref()
+                .and_then(|app_id| apps.iter().find(|app| app.id == *app_id))
+                .map(|app| crate::database::EmbeddedApplication {
+                    name: app.name.clone(),
+                    category: app.category.clone(),
+                    icon_path: app.icon_path.clone(),
+                }),
+        })
+        .collect()
+}
+
+// How many apps the top-apps lists show unless asked for more
+const DEFAULT_TOP_APPS: usize = 5;
+
 fn calculate_app_usage(
-    entries: &[TimeEntry],
-    apps: &[Application],
+    entries: &[TimeEntryWithApp],
     start: DateTime<Utc>,
     end: DateTime<Utc>,
+    limit: usize,
 ) -> Vec<AppUsage> {
     use std::collections::HashMap;
     
-    let mut app_seconds: HashMap<String, i64> = HashMap::new();
+    // Keyed by app id so two apps sharing a display name stay apart; the name is only for display
+    let mut app_seconds: HashMap<&str, (&str, i64)> = HashMap::new();
     let mut total_seconds = 0i64;
     
-    for entry in entries {
-        if let Some(app_id) = &entry.app_id {
+    for entry_with_app in entries {
+        let entry = &entry_with_app.entry;
+        if let Some(app_id) = entry.app_id.as_deref() {
             if entry.start_time >= start && entry.start_time < end {
                 if let Some(duration) = entry.duration_seconds {
-                    *app_seconds.entry(app_id.clone()).or_insert(0) += duration;
+                    let app_name = entry_with_app.app_name().unwrap_or("Unknown App");
+                    app_seconds.entry(app_id).or_insert((app_name, 0)).1 += duration;
                     total_seconds += duration;
                 }
             }
@@ -368,23 +596,17 @@ fn calculate_app_usage(
     }
     
     let mut app_usage: Vec<AppUsage> = app_seconds
-        .iter()
-        .map(|(app_id, seconds)| {
-            let app_name = apps
-                .iter()
-                .find(|app| app.id == *app_id)
-                .map(|app| app.name.clone())
-                .unwrap_or_else(|| "Unknown App".to_string());
-            
-            let hours = *seconds as f64 / 3600.0;
+        .into_values()
+        .map(|(app_name, seconds)| {
+            let hours = seconds as f64 / 3600.0;
             let percentage = if total_seconds > 0 {
-                (*seconds as f64 / total_seconds as f64) * 100.0
+                (seconds as f64 / total_seconds as f64) * 100.0
             } else {
                 0.0
             };
             
             AppUsage {
-                app_name,
+                app_name: app_name.to_string(),
                 hours,
                 percentage,
             }
@@ -392,12 +614,12 @@ fn calculate_app_usage(
         .collect();
     
     app_usage.sort_by(|a, b| b.hours.partial_cmp(&a.hours).unwrap_or(std::cmp::Ordering::Equal));
-    app_usage.truncate(5); // Top 5 apps
+    app_usage.truncate(limit);
     
     app_usage
 }
 
-fn calculate_task_stats(tasks: &[Task]) -> TaskStats {
+pub(super) fn calculate_task_stats(tasks: &[Task]) -> TaskStats {
     let total = tasks.len();
     let todo = tasks.iter().filter(|t| matches!(t.status, crate::database::TaskStatus::Todo)).count();
     let in_progress = tasks.iter().filter(|t| matches!(t.status, crate::database::TaskStatus::InProgress)).count();
@@ -417,7 +639,7 @@ fn calculate_task_stats(tasks: &[Task]) -> TaskStats {
     }
 }
 
-fn calculate_productivity_trend(entries: &[TimeEntry], days: i64) -> ProductivityTrend {
+fn calculate_productivity_trend(entries: &[TimeEntry], days: i64, tz: &ReportTimezone) -> ProductivityTrend {
     use std::collections::HashMap;
     
     let mut daily_hours: HashMap<String, i64> = HashMap::new();
@@ -428,15 +650,16 @@ fn calculate_productivity_trend(entries: &[TimeEntry], days: i64) -> Productivit
     
     for entry in entries {
         if entry.start_time >= start_date {
-            // Daily aggregation
-            let date_str = entry.start_time.date_naive().to_string();
+            // Daily aggregation, on the user's local date
+            let local_start = tz.to_local(entry.start_time);
+            let date_str = local_start.date().to_string();
             if let Some(duration) = entry.duration_seconds {
                 *daily_hours.entry(date_str).or_insert(0) += duration;
             }
             
             // Hourly aggregation for peak hours
             // Get hour from DateTime using format, then parse
-            let hour_str = entry.start_time.format("%H").to_string();
+            let hour_str = local_start.format("%H").to_string();
             if let Ok(hour) = hour_str.parse::<i32>() {
                 *hourly_counts.entry(hour).or_insert(0) += 1;
             }
@@ -465,12 +688,1002 @@ fn calculate_productivity_trend(entries: &[TimeEntry], days: i64) -> Productivit
     }
 }
 
+// ===== INSIGHT ENGINE =====
+
+// Entries shorter than this count as a context switch
+const SHORT_ENTRY_SECONDS: i64 = 120;
+// Share of short entries (with enough entries to judge) that flags context switching
+const CONTEXT_SWITCH_RATIO: f64 = 0.5;
+const CONTEXT_SWITCH_MIN_ENTRIES: usize = 20;
+const LOW_COMPLETION_RATE: f64 = 30.0;
+const HIGH_COMPLETION_RATE: f64 = 80.0;
+const DOMINANT_APP_PERCENTAGE: f64 = 60.0;
+// Week-over-week change (percent) worth calling out
+const WEEKLY_CHANGE_PERCENT: f64 = 20.0;
+
+fn insight(title: &str, description: String, insight_type: InsightType) -> Insight {
+    Insight {
+        title: title.to_string(),
+        description,
+        insight_type,
+    }
+}
+
+/// Derive recommendations from a user's productivity data
+pub fn generate_insights(insights: &ProductivityInsights) -> Vec<Insight> {
+    let mut result = Vec::new();
+
+    if insights.entries_this_week >= CONTEXT_SWITCH_MIN_ENTRIES {
+        let short_ratio = insights.short_entries_this_week as f64 / insights.entries_this_week as f64;
+        if short_ratio >= CONTEXT_SWITCH_RATIO {
+            result.push(insight(
+                "Frequent Context Switching",
+                format!(
+                    "{} of your {} sessions this week lasted under {} minutes. Try batching similar work into longer focus blocks.",
+                    insights.short_entries_this_week,
+                    insights.entries_this_week,
+                    SHORT_ENTRY_SECONDS / 60
+                ),
+                InsightType::Warning,
+            ));
+        }
+    }
+
+    let task_stats = &insights.task_stats;
+    if task_stats.total >= 3 && task_stats.completion_rate < LOW_COMPLETION_RATE {
+        result.push(insight(
+            "Low Task Completion",
+            format!(
+                "Only {} of {} tasks are done ({:.0}%). Consider closing out in-progress work before starting new tasks.",
+                task_stats.done, task_stats.total, task_stats.completion_rate
+            ),
+            InsightType::Tip,
+        ));
+    } else if task_stats.total >= 3 && task_stats.completion_rate >= HIGH_COMPLETION_RATE {
+        result.push(insight(
+            "Great Task Follow-Through",
+            format!("You've completed {:.0}% of your tasks.", task_stats.completion_rate),
+            InsightType::Achievement,
+        ));
+    }
+
+    if let Some(top_app) = insights.most_used_apps.first() {
+        if top_app.percentage >= DOMINANT_APP_PERCENTAGE {
+            result.push(insight(
+                "One App Dominates Your Time",
+                format!(
+                    "{} accounts for {:.0}% of your tracked time this week ({:.1} hours).",
+                    top_app.app_name, top_app.percentage, top_app.hours
+                ),
+                InsightType::Info,
+            ));
+        }
+    }
+
+    if insights.total_time_last_week > 0.0 {
+        let change = (insights.total_time_this_week - insights.total_time_last_week) / insights.total_time_last_week * 100.0;
+        if change <= -WEEKLY_CHANGE_PERCENT {
+            result.push(insight(
+                "Tracked Time Dropped",
+                format!(
+                    "You tracked {:.1} hours this week, down {:.0}% from {:.1} hours last week.",
+                    insights.total_time_this_week, -change, insights.total_time_last_week
+                ),
+                InsightType::Warning,
+            ));
+        } else if change >= WEEKLY_CHANGE_PERCENT {
+            result.push(insight(
+                "More Time Tracked",
+                format!(
+                    "You tracked {:.1} hours this week, up {:.0}% from last week.",
+                    insights.total_time_this_week, change
+                ),
+                InsightType::Achievement,
+            ));
+        }
+    }
+
+    if !insights.productivity_trend.peak_hours.is_empty() {
+        let hours: Vec<String> = insights
+            .productivity_trend
+            .peak_hours
+            .iter()
+            .take(3)
+            .map(|h| format!("{}:00", h))
+            .collect();
+        result.push(insight(
+            "Your Peak Hours",
+            format!("You start the most sessions around {}. Schedule deep work then.", hours.join(", ")),
+            InsightType::Info,
+        ));
+    }
+
+    result
+}
+
+/// Derive team-level recommendations from each member's productivity data
+pub fn generate_team_insights(members: &[TeamMemberInsights]) -> Vec<Insight> {
+    let mut result = Vec::new();
+    if members.is_empty() {
+        return result;
+    }
+
+    let total_week: f64 = members.iter().map(|m| m.total_time_this_week).sum();
+    let average_week = total_week / members.len() as f64;
+
+    if let Some(top) = members
+        .iter()
+        .max_by(|a, b| a.total_time_this_week.partial_cmp(&b.total_time_this_week).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        if top.total_time_this_week > 0.0 {
+            result.push(insight(
+                "Top Contributor",
+                format!("{} tracked the most time this week ({:.1} hours).", top.member_name, top.total_time_this_week),
+                InsightType::Achievement,
+            ));
+        }
+
+        if average_week > 0.0 && top.total_time_this_week >= average_week * 1.5 && members.len() > 1 {
+            result.push(insight(
+                "Uneven Workload",
+                format!(
+                    "{} is at {:.1} hours this week versus a team average of {:.1}. Consider rebalancing work.",
+                    top.member_name, top.total_time_this_week, average_week
+                ),
+                InsightType::Warning,
+            ));
+        }
+    }
+
+    let inactive: Vec<&str> = members
+        .iter()
+        .filter(|m| m.total_time_this_week == 0.0)
+        .map(|m| m.member_name.as_str())
+        .collect();
+    if !inactive.is_empty() {
+        result.push(insight(
+            "No Tracked Time",
+            format!("{} member(s) have no tracked time this week: {}.", inactive.len(), inactive.join(", ")),
+            InsightType::Info,
+        ));
+    }
+
+    let low_completion: Vec<&str> = members
+        .iter()
+        .filter(|m| m.task_stats.total >= 3 && m.task_stats.completion_rate < LOW_COMPLETION_RATE)
+        .map(|m| m.member_name.as_str())
+        .collect();
+    if !low_completion.is_empty() {
+        result.push(insight(
+            "Tasks Piling Up",
+            format!("Task completion is below {:.0}% for: {}.", LOW_COMPLETION_RATE, low_completion.join(", ")),
+            InsightType::Tip,
+        ));
+    }
+
+    result
+}
+
+// ===== PERIOD COMPARISON =====
+
+// Concrete [start, end) range for a period name used by the show_comparison tool
+fn period_range(period: &str, tz: &ReportTimezone, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    use chrono::Datelike;
+
+    let today = tz.to_local(now).date();
+    let monday = monday_of_week(today);
+    let month_start = today.with_day(1).unwrap();
+    let previous_month_start = (month_start - Duration::days(1)).with_day(1).unwrap();
+
+    match period {
+        "today" => Ok((tz.start_of_day(today), now)),
+        "week" => Ok((tz.start_of_day(monday), now)),
+        "month" => Ok((tz.start_of_day(month_start), now)),
+        "yesterday" => Ok((tz.start_of_day(today - Duration::days(1)), tz.start_of_day(today))),
+        "last_week" => Ok((tz.start_of_day(monday - Duration::days(7)), tz.start_of_day(monday))),
+        "last_month" => Ok((tz.start_of_day(previous_month_start), tz.start_of_day(month_start))),
+        other => Err(format!(
+            "Unknown period '{}'. Use today, week, month, yesterday, last_week, or last_month",
+            other
+        )),
+    }
+}
+
+pub async fn get_productivity_comparison(
+    db: &Database,
+    current: String,
+    previous: String,
+) -> Result<Comparison, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let tz = ReportTimezone::Local;
+    let now = Utc::now();
+
+    let (current_start, current_end) = period_range(&current, &tz, now)?;
+    let (previous_start, previous_end) = period_range(&previous, &tz, now)?;
+
+    // One query covering both periods
+    let time_entries = fetch_time_entries_in_range(
+        db,
+        &user_id,
+        current_start.min(previous_start),
+        current_end.max(previous_end),
+    )
+    .await?;
+
+    let current_hours = calculate_hours_in_range(&time_entries, current_start, current_end);
+    let previous_hours = calculate_hours_in_range(&time_entries, previous_start, previous_end);
+    let delta = current_hours - previous_hours;
+    let percent_change = if previous_hours > 0.0 {
+        (delta / previous_hours) * 100.0
+    } else if current_hours > 0.0 {
+        100.0
+    } else {
+        0.0
+    };
+
+    Ok(Comparison {
+        current_period: current,
+        previous_period: previous,
+        current_hours,
+        previous_hours,
+        delta,
+        percent_change,
+        improved: delta > 0.0,
+    })
+}
+
+// ===== CATEGORY USAGE =====
+
+// Sum time per application category within [start, end); apps without a category count as "Other"
+fn calculate_category_usage(
+    entries: &[TimeEntry],
+    apps: &[Application],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<CategoryUsage> {
+    use std::collections::HashMap;
+
+    let mut category_seconds: HashMap<String, i64> = HashMap::new();
+    let mut total_seconds = 0i64;
+
+    for entry in entries {
+        let app_id = match &entry.app_id {
+            Some(app_id) => app_id,
+            None => continue,
+        };
+
+        // Only count the part of the entry that falls inside the range
+        let entry_start = entry.start_time.max(start);
+        let entry_end = entry.end_time.unwrap_or_else(Utc::now).min(end);
+        if entry_end <= entry_start {
+            continue;
+        }
+        let seconds = (entry_end - entry_start).num_seconds();
+
+        let category = apps
+            .iter()
+            .find(|app| app.id == *app_id)
+            .and_then(|app| app.category.clone())
+            .filter(|category| !category.trim().is_empty())
+            .unwrap_or_else(|| "Other".to_string());
+
+        *category_seconds.entry(category).or_insert(0) += seconds;
+        total_seconds += seconds;
+    }
+
+    let mut usage: Vec<CategoryUsage> = category_seconds
+        .into_iter()
+        .map(|(category, seconds)| CategoryUsage {
+            category,
+            hours: seconds as f64 / 3600.0,
+            percentage: if total_seconds > 0 {
+                (seconds as f64 / total_seconds as f64) * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    usage.sort_by(|a, b| b.hours.partial_cmp(&a.hours).unwrap_or(std::cmp::Ordering::Equal));
+    usage
+}
+
+#[tauri::command]
+pub async fn get_category_usage(
+    db: State<'_, Database>,
+    user_id: Option<String>,
+    start: String,
+    end: String,
+) -> Result<Vec<CategoryUsage>, String> {
+    let user_id = match user_id {
+        Some(id) => id,
+        None => crate::current_user::get_current_user_id_or_error()?,
+    };
+
+    let start = DateTime::parse_from_rfc3339(&start)
+        .map_err(|e| format!("Invalid start: {}", e))?
+        .with_timezone(&Utc);
+    let end = DateTime::parse_from_rfc3339(&end)
+        .map_err(|e| format!("Invalid end: {}", e))?
+        .with_timezone(&Utc);
+    if end <= start {
+        return Err("end must be after start".to_string());
+    }
+
+    let time_entries = fetch_time_entries_in_range(&db, &user_id, start, end).await?;
+    let applications = get_applications_by_user(db.clone(), user_id.clone(), None)
+        .await
+        .map_err(|e| format!("Failed to fetch applications: {}", e))?;
+
+    Ok(calculate_category_usage(&time_entries, &applications, start, end))
+}
+
+// Join each application with the time tracked against it in [start, end), most used first
+fn calculate_apps_with_usage(
+    entries: &[TimeEntry],
+    apps: Vec<Application>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<AppWithUsage> {
+    use std::collections::HashMap;
+
+    let mut seconds_by_app: HashMap<&str, i64> = HashMap::new();
+    let mut last_used_by_app: HashMap<&str, DateTime<Utc>> = HashMap::new();
+
+    for entry in entries {
+        let app_id = match &entry.app_id {
+            Some(app_id) => app_id.as_str(),
+            None => continue,
+        };
+
+        let entry_start = entry.start_time.max(start);
+        let entry_end = entry.end_time.unwrap_or_else(Utc::now).min(end);
+        if entry_end <= entry_start {
+            continue;
+        }
+
+        *seconds_by_app.entry(app_id).or_insert(0) += (entry_end - entry_start).num_seconds();
+        let last_used = last_used_by_app.entry(app_id).or_insert(entry_end);
+        if entry_end > *last_used {
+            *last_used = entry_end;
+        }
+    }
+
+    let mut usage: Vec<AppWithUsage> = apps
+        .into_iter()
+        .map(|mut application| {
+            let total_seconds = seconds_by_app.get(application.id.as_str()).copied().unwrap_or(0);
+            if let Some(last_used) = last_used_by_app.get(application.id.as_str()) {
+                if !application.last_used.is_some_and(|stored| stored >= *last_used) {
+                    application.last_used = Some(*last_used);
+                }
+            }
+            AppWithUsage { application, total_seconds }
+        })
+        .collect();
+
+    usage.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds).then_with(|| a.application.name.cmp(&b.application.name)));
+    usage
+}
+
+#[tauri::command]
+pub async fn get_my_applications_with_usage(
+    db: State<'_, Database>,
+    period: String,
+) -> Result<Vec<AppWithUsage>, String> {
+    if !matches!(period.as_str(), "today" | "week" | "month") {
+        return Err(format!("Unknown period '{}'. Use today, week, or month", period));
+    }
+
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let (start, end) = period_range(&period, &ReportTimezone::Local, Utc::now())?;
+
+    // One entries query for every app instead of a request per app
+    let time_entries = fetch_time_entries_in_range(&db, &user_id, start, end).await?;
+    let applications = get_applications_by_user(db.clone(), user_id.clone(), None)
+        .await
+        .map_err(|e| format!("Failed to fetch applications: {}", e))?;
+
+    Ok(calculate_apps_with_usage(&time_entries, applications, start, end))
+}
+
+// ===== MOST USED APPS =====
+
+// Top `limit` apps by time tracked in the period, from one query with app names embedded
+async fn most_used_apps_in_db(
+    db: &Database,
+    user_id: &str,
+    period: &str,
+    tz: &ReportTimezone,
+    limit: usize,
+) -> Result<Vec<AppUsage>, String> {
+    if !matches!(period, "today" | "week" | "month") {
+        return Err(format!("Unknown period '{}'. Use today, week, or month", period));
+    }
+
+    let (start, end) = period_range(period, tz, Utc::now())?;
+    let user_filter = format!("eq.{}", user_id);
+    let start_filter = format!("lt.{}", end.format("%Y-%m-%dT%H:%M:%SZ"));
+    // Only entries still running or ending after the period starts, not the whole history
+    let end_filter = format!("(end_time.is.null,end_time.gt.{})", start.format("%Y-%m-%dT%H:%M:%SZ"));
+    let response = db
+        .execute_query_with_params(
+            "time_entries",
+            "GET",
+            &[
+                ("user_id", user_filter.as_str()),
+                ("start_time", start_filter.as_str()),
+                ("or", end_filter.as_str()),
+                ("order", "start_time.asc"),
+            ],
+            Some("*,applications(name,category,icon_path)"),
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+    let entries: Vec<TimeEntryWithApp> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse time entries: {}", e))?;
+
+    Ok(calculate_app_usage(&entries, start, end, limit))
+}
+
+#[tauri::command]
+pub async fn get_most_used_apps(
+    db: State<'_, Database>,
+    period: String,
+    limit: Option<usize>,
+    timezone: Option<String>,
+) -> Result<Vec<AppUsage>, String> {
+    let tz = ReportTimezone::from_param(timezone.as_deref())?;
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    most_used_apps_in_db(&db, &user_id, &period, &tz, limit.unwrap_or(DEFAULT_TOP_APPS)).await
+}
+
+// ===== TOOL PAYLOADS =====
+
+// What the stats tools return when there is nothing tracked, instead of made-up numbers.
+// Tools that don't cover a particular period pass None
+fn no_data_payload(period: Option<&str>) -> serde_json::Value {
+    match period {
+        Some(period) => serde_json::json!({
+            "no_data": true,
+            "period": period,
+            "message": "No time tracked yet for this period"
+        }),
+        None => serde_json::json!({
+            "no_data": true,
+            "message": "Nothing tracked yet"
+        }),
+    }
+}
+
+// Days of history the trend tools look at for a period argument
+fn trend_days(period: &str) -> i64 {
+    match period {
+        "today" => 1,
+        "month" => 30,
+        _ => 7,
+    }
+}
+
+// Calendar-period totals for show_time_tracking_stats, from entries covering this week and month.
+// Nothing tracked in the period the user asked about is reported as no data
+fn time_stats_payload(entries: &[TimeEntry], period: &str, tz: &ReportTimezone, now: DateTime<Utc>) -> serde_json::Value {
+    let hours_in = |name: &str| {
+        period_range(name, tz, now).map_or(0.0, |(start, end)| calculate_hours_in_range(entries, start, end))
+    };
+    if hours_in(period) == 0.0 {
+        return no_data_payload(Some(period));
+    }
+    let (today, week, month) = (hours_in("today"), hours_in("week"), hours_in("month"));
+
+    serde_json::json!({
+        "insights": {
+            "total_time_today": today,
+            "total_time_this_week": week,
+            "total_time_this_month": month
+        }
+    })
+}
+
+// ===== WEEKLY SUMMARY =====
+
+// Weekly hours target used for goal attainment unless the caller sets one
+const DEFAULT_WEEKLY_GOAL_HOURS: f64 = 40.0;
+
+// Monday of the week containing the given date
+pub(super) fn monday_of_week(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    use chrono::Datelike;
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[tauri::command]
+pub async fn get_weekly_summary(
+    db: State<'_, Database>,
+    week_start: Option<String>,
+    timezone: Option<String>,
+    goal_hours: Option<f64>,
+) -> Result<WeeklySummary, String> {
+    let tz = ReportTimezone::from_param(timezone.as_deref())?;
+    let goal_hours = goal_hours.unwrap_or(DEFAULT_WEEKLY_GOAL_HOURS);
+    if !goal_hours.is_finite() || goal_hours < 0.0 {
+        return Err(format!("Invalid goal_hours: {}", goal_hours));
+    }
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+
+    let anchor = match week_start.as_deref() {
+        Some(date) => chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid week_start (expected YYYY-MM-DD): {}", e))?,
+        None => tz.to_local(Utc::now()).date(),
+    };
+    let monday = monday_of_week(anchor);
+    let start = tz.start_of_day(monday);
+    let end = tz.start_of_day(monday + Duration::days(7));
+
+    // Only the entries overlapping this week
+    let time_entries = fetch_time_entries_in_range(&db, &user_id, start, end).await?;
+
+    // Only the apps this week's entries used, in one request
+    let app_ids: Vec<String> = time_entries.iter().filter_map(|entry| entry.app_id.clone()).collect();
+    let applications = crate::platform::database_helpers::DatabaseHelpers::get_applications_by_ids(&db, &app_ids).await?;
+
+    // Archived tasks were still worked on in the period
+    let tasks = get_tasks_by_assignee(db.clone(), user_id.clone(), Some(true))
+        .await
+        .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
+
+    // Zero-filled per-day totals so the chart has no gaps
+    let by_day: Vec<DailyHours> = (0..7)
+        .map(|offset| {
+            let date = monday + Duration::days(offset);
+            DailyHours {
+                date: date.to_string(),
+                hours: calculate_hours_in_range(&time_entries, tz.start_of_day(date), tz.start_of_day(date + Duration::days(1))),
+            }
+        })
+        .collect();
+
+    let total_hours = calculate_hours_in_range(&time_entries, start, end);
+    let top_apps = calculate_app_usage(&with_app_names(&time_entries, &applications), start, end, DEFAULT_TOP_APPS);
+    let task_stats = calculate_task_stats(&tasks);
+    let tasks_completed_this_week = tasks
+        .iter()
+        .filter(|t| matches!(t.status, crate::database::TaskStatus::Done))
+        .filter(|t| t.updated_at.is_some_and(|updated| updated >= start && updated < end))
+        .count();
+
+    let goal_attainment = if goal_hours > 0.0 {
+        (total_hours / goal_hours) * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(WeeklySummary {
+        week_start: monday.to_string(),
+        week_end: (monday + Duration::days(6)).to_string(),
+        total_hours,
+        by_day,
+        top_apps,
+        task_stats,
+        tasks_completed_this_week,
+        goal_hours,
+        goal_attainment,
+    })
+}
+
+// ===== PRODUCTIVITY SCORE =====
+
+// Average entry length that earns a full focus score
+const FOCUS_TARGET_MINUTES: f64 = 25.0;
+
+// Hours goal for a whole period, scaled from the weekly goal
+fn goal_hours_for(period: &str) -> f64 {
+    match period {
+        "today" => DEFAULT_WEEKLY_GOAL_HOURS / 5.0,
+        "month" => DEFAULT_WEEKLY_GOAL_HOURS * 4.0,
+        _ => DEFAULT_WEEKLY_GOAL_HOURS,
+    }
+}
+
+fn calculate_productivity_score(
+    period: &str,
+    entries: &[TimeEntry],
+    task_stats: &TaskStats,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ProductivityScore {
+    let tracked_hours = calculate_hours_in_range(entries, start, end);
+    let goal_hours = goal_hours_for(period);
+    let hours_score = (tracked_hours / goal_hours * 100.0).min(100.0);
+
+    let app_entries = entries
+        .iter()
+        .filter(|entry| entry.app_id.is_some() && entry.start_time < end && !entry.end_time.is_some_and(|e| e <= start))
+        .count();
+    let average_entry_minutes = if app_entries > 0 {
+        tracked_hours * 60.0 / app_entries as f64
+    } else {
+        0.0
+    };
+    let focus_score = (average_entry_minutes / FOCUS_TARGET_MINUTES * 100.0).min(100.0);
+
+    let task_score = (task_stats.total > 0).then_some(task_stats.completion_rate);
+    let score = match task_score {
+        Some(task_score) => 0.4 * hours_score + 0.3 * task_score + 0.3 * focus_score,
+        None => (0.4 * hours_score + 0.3 * focus_score) / 0.7,
+    };
+
+    ProductivityScore {
+        period: period.to_string(),
+        score,
+        hours_score,
+        task_score,
+        focus_score,
+        tracked_hours,
+        goal_hours,
+        average_entry_minutes,
+    }
+}
+
+#[tauri::command]
+pub async fn get_productivity_score(
+    db: State<'_, Database>,
+    period: String,
+) -> Result<ProductivityScore, String> {
+    if !matches!(period.as_str(), "today" | "week" | "month") {
+        return Err(format!("Unknown period '{}'. Use today, week, or month", period));
+    }
+
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let now = Utc::now();
+    let (start, end) = period_range(&period, &ReportTimezone::Local, now)?;
+
+    let time_entries = end_running_entries_at(fetch_time_entries_in_range(&db, &user_id, start, end).await?, now);
+    // Tasks archived since still count toward the score
+    let tasks = get_tasks_by_assignee(db.clone(), user_id.clone(), Some(true))
+        .await
+        .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
+
+    Ok(calculate_productivity_score(&period, &time_entries, &calculate_task_stats(&tasks), start, end))
+}
+
+// ===== DAILY HOURS =====
+
+// Hours per local date across [start, end), with a zero for every day that has none
+fn daily_hours_in_range(entries: &[TimeEntry], start: DateTime<Utc>, end: DateTime<Utc>, tz: &ReportTimezone) -> Vec<DailyHours> {
+    let first_day = tz.to_local(start).date();
+    let last_day = tz.to_local(end - Duration::seconds(1)).date();
+    first_day
+        .iter_days()
+        .take_while(|date| *date <= last_day)
+        .map(|date| {
+            let day_start = tz.start_of_day(date).max(start);
+            let day_end = tz.start_of_day(date + Duration::days(1)).min(end);
+            DailyHours {
+                date: date.to_string(),
+                hours: calculate_hours_in_range(entries, day_start, day_end),
+            }
+        })
+        .collect()
+}
+
+/// Tracked hours for each day from `start` to `end` (inclusive YYYY-MM-DD dates), for calendar views
+#[tauri::command]
+pub async fn get_daily_hours(
+    db: State<'_, Database>,
+    user_id: Option<String>,
+    start: String,
+    end: String,
+    timezone: Option<String>,
+) -> Result<Vec<DailyHours>, String> {
+    let tz = ReportTimezone::from_param(timezone.as_deref())?;
+    let user_id = match user_id {
+        Some(user_id) => user_id,
+        None => crate::current_user::get_current_user_id_or_error()?,
+    };
+
+    let first_day = chrono::NaiveDate::parse_from_str(start.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid start (expected YYYY-MM-DD): {}", e))?;
+    let last_day = chrono::NaiveDate::parse_from_str(end.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid end (expected YYYY-MM-DD): {}", e))?;
+    if last_day < first_day {
+        return Err("end must not be before start".to_string());
+    }
+    if (last_day - first_day).num_days() >= MAX_REPORT_DAYS {
+        return Err(format!("Daily hours can cover at most {} days", MAX_REPORT_DAYS));
+    }
+
+    let range_start = tz.start_of_day(first_day);
+    let range_end = tz.start_of_day(last_day + Duration::days(1));
+
+    // One query for the whole range, bucketed here
+    let time_entries = fetch_time_entries_in_range(&db, &user_id, range_start, range_end).await?;
+    let time_entries = end_running_entries_at(time_entries, Utc::now());
+
+    Ok(daily_hours_in_range(&time_entries, range_start, range_end, &tz))
+}
+
+// Count a running entry up to `now`, not to the end of the day it's in
+fn end_running_entries_at(entries: Vec<TimeEntry>, now: DateTime<Utc>) -> Vec<TimeEntry> {
+    entries
+        .into_iter()
+        .map(|mut entry| {
+            if entry.end_time.is_none() {
+                entry.end_time = Some(now.max(entry.start_time));
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Hours one application was used on each of the last `days` local days, oldest first, for sparklines
+#[tauri::command]
+pub async fn get_app_daily_usage(
+    db: State<'_, Database>,
+    app_id: String,
+    days: i64,
+    timezone: Option<String>,
+) -> Result<Vec<DailyHours>, String> {
+    let tz = ReportTimezone::from_param(timezone.as_deref())?;
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    app_daily_usage_in_db(&db, &user_id, &app_id, days, &tz, Utc::now()).await
+}
+
+async fn app_daily_usage_in_db(
+    db: &Database,
+    user_id: &str,
+    app_id: &str,
+    days: i64,
+    tz: &ReportTimezone,
+    now: DateTime<Utc>,
+) -> Result<Vec<DailyHours>, String> {
+    if !(1..=MAX_REPORT_DAYS).contains(&days) {
+        return Err(format!("days must be between 1 and {}", MAX_REPORT_DAYS));
+    }
+
+    let today = tz.to_local(now).date();
+    let range_start = tz.start_of_day(today - Duration::days(days - 1));
+    let range_end = tz.start_of_day(today + Duration::days(1));
+
+    // Only this app's entries overlapping the range, in one query
+    let time_entries = fetch_app_time_entries_in_range(db, user_id, Some(app_id), range_start, range_end).await?;
+    let time_entries = end_running_entries_at(time_entries, now);
+
+    Ok(daily_hours_in_range(&time_entries, range_start, range_end, tz))
+}
+
+// ===== REPORT EXPORT =====
+
+// Bump whenever the ProductivityReport layout changes incompatibly
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+// Longest range a single export may cover
+const MAX_REPORT_DAYS: i64 = 366;
+
+fn build_productivity_report(
+    entries: &[TimeEntry],
+    apps: Vec<Application>,
+    tasks: &[Task],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tz: &ReportTimezone,
+    generated_at: DateTime<Utc>,
+) -> ProductivityReport {
+    let total_hours = calculate_hours_in_range(entries, start, end);
+    let total_seconds = (total_hours * 3600.0).round();
+    let category_usage = calculate_category_usage(entries, &apps, start, end);
+
+    let app_usage: Vec<AppUsage> = calculate_apps_with_usage(entries, apps, start, end)
+        .into_iter()
+        .filter(|usage| usage.total_seconds > 0)
+        .map(|usage| AppUsage {
+            app_name: usage.application.name,
+            hours: usage.total_seconds as f64 / 3600.0,
+            percentage: if total_seconds > 0.0 {
+                (usage.total_seconds as f64 / total_seconds) * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    // Zero-filled per-day totals, like the weekly summary
+    let daily_hours = daily_hours_in_range(entries, start, end, tz);
+
+    let tasks_completed = tasks
+        .iter()
+        .filter(|t| matches!(t.status, crate::database::TaskStatus::Done))
+        .filter(|t| t.updated_at.is_some_and(|updated| updated >= start && updated < end))
+        .count();
+
+    ProductivityReport {
+        version: REPORT_SCHEMA_VERSION,
+        generated_at,
+        range_start: start,
+        range_end: end,
+        total_hours,
+        entry_count: entries.len(),
+        app_usage,
+        category_usage,
+        task_stats: calculate_task_stats(tasks),
+        tasks_completed,
+        daily_hours,
+    }
+}
+
+#[tauri::command]
+pub async fn export_report_json(
+    db: State<'_, Database>,
+    start: String,
+    end: String,
+) -> Result<String, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+
+    let start = DateTime::parse_from_rfc3339(&start)
+        .map_err(|e| format!("Invalid start: {}", e))?
+        .with_timezone(&Utc);
+    let end = DateTime::parse_from_rfc3339(&end)
+        .map_err(|e| format!("Invalid end: {}", e))?
+        .with_timezone(&Utc);
+    if end <= start {
+        return Err("end must be after start".to_string());
+    }
+    if end - start > Duration::days(MAX_REPORT_DAYS) {
+        return Err(format!("Reports can cover at most {} days", MAX_REPORT_DAYS));
+    }
+
+    let time_entries = fetch_time_entries_in_range(&db, &user_id, start, end).await?;
+    let applications = get_applications_by_user(db.clone(), user_id.clone(), None)
+        .await
+        .map_err(|e| format!("Failed to fetch applications: {}", e))?;
+    // The export lists archived tasks too
+    let tasks = get_tasks_by_assignee(db.clone(), user_id.clone(), Some(true))
+        .await
+        .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
+
+    let report = build_productivity_report(
+        &time_entries,
+        applications,
+        &tasks,
+        start,
+        end,
+        &ReportTimezone::Local,
+        Utc::now(),
+    );
+
+    serde_json::to_string_pretty(&report).map_err(|e| format!("Failed to serialize report: {}", e))
+}
+
+// ===== MENTIONS =====
+
+// "@name" or "@someone@example.com" at the start of the message or after whitespace
+static MENTION_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(r"(?:^|\s)@([A-Za-z0-9._%+-]+(?:@[A-Za-z0-9.-]+\.[A-Za-z]{2,})?)").unwrap()
+});
+
+/// A mention in the user's message and the workspace member it refers to
+#[derive(Debug, Clone)]
+pub(super) struct ResolvedMention {
+    pub token: String, // Without the leading "@"
+    pub user: User,
+}
+
+#[derive(Debug)]
+pub(super) enum Mentions {
+    Resolved(Vec<ResolvedMention>),
+    NeedsClarification(String), // Question to send back instead of calling the AI
+}
+
+// Mention tokens in order of appearance, without duplicates or trailing punctuation
+pub(super) fn extract_mentions(message: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for capture in MENTION_REGEX.captures_iter(message) {
+        let token = capture[1].trim_end_matches(['.', '-']).to_string();
+        if !token.is_empty() && !tokens.iter().any(|seen| seen.eq_ignore_ascii_case(&token)) {
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+// Lowercase letters and digits only, so "@sarah.dev" and "Sarah Dev" compare equal
+fn squash(value: &str) -> String {
+    value.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+// Members whose full name, first name or email local part matches the token
+pub(super) fn members_matching<'a>(token: &str, members: &'a [User]) -> Vec<&'a User> {
+    let wanted = squash(token);
+    members
+        .iter()
+        .filter(|member| {
+            let first_name = member.name.split_whitespace().next().unwrap_or_default();
+            let local_part = member.email.as_deref().and_then(|email| email.split('@').next()).unwrap_or_default();
+            [member.name.as_str(), first_name, local_part]
+                .iter()
+                .any(|candidate| !candidate.is_empty() && squash(candidate) == wanted)
+        })
+        .collect()
+}
+
+/// Resolve every mention in the message to a member of the workspace; anything
+/// that matches nobody or several people turns into a clarifying question
+pub(super) async fn resolve_mentions(db: &Database, workspace_id: &str, message: &str) -> Result<Mentions, String> {
+    let tokens = extract_mentions(message);
+    if tokens.is_empty() {
+        return Ok(Mentions::Resolved(Vec::new()));
+    }
+
+    let members = fetch_users_by_workspace(db, workspace_id).await?;
+    let mut resolved = Vec::new();
+    let mut questions = Vec::new();
+
+    for token in tokens {
+        let matches: Vec<User> = if token.contains('@') {
+            // Full address: look it up directly, but only accept members of this workspace
+            fetch_user_by_email(db, &token)
+                .await?
+                .filter(|user| members.iter().any(|member| member.id == user.id))
+                .into_iter()
+                .collect()
+        } else {
+            members_matching(&token, &members).into_iter().cloned().collect()
+        };
+
+        match matches.as_slice() {
+            [user] => resolved.push(ResolvedMention { token, user: user.clone() }),
+            [] => questions.push(format!(
+                "I couldn't find anyone called @{} in this workspace. Could you check the name, or use their email?",
+                token
+            )),
+            several => {
+                let names: Vec<String> = several
+                    .iter()
+                    .map(|user| match &user.email {
+                        Some(email) => format!("{} ({})", user.name, email),
+                        None => user.name.clone(),
+                    })
+                    .collect();
+                questions.push(format!("@{} matches {}. Which one did you mean?", token, names.join(", ")));
+            }
+        }
+    }
+
+    if questions.is_empty() {
+        Ok(Mentions::Resolved(resolved))
+    } else {
+        Ok(Mentions::NeedsClarification(questions.join("\n")))
+    }
+}
+
+/// Point member tools at the resolved ids. The model sometimes passes the typed
+/// name instead of an id, and with a single mention there is only one it can mean
+pub(super) fn apply_resolved_mentions(arguments: &mut serde_json::Value, mentions: &[ResolvedMention]) {
+    let Some(member_id) = arguments.get("member_id").and_then(|v| v.as_str()) else {
+        return;
+    };
+    if mentions.iter().any(|mention| mention.user.id == member_id) {
+        return;
+    }
+
+    let typed = squash(member_id);
+    let resolved = mentions
+        .iter()
+        .find(|mention| squash(&mention.token) == typed || squash(&mention.user.name) == typed)
+        .or(match mentions {
+            [only] => Some(only),
+            _ => None,
+        });
+    if let Some(mention) = resolved {
+        arguments["member_id"] = serde_json::Value::String(mention.user.id.clone());
+    }
+}
+
 // ===== TEAM DATA FUNCTIONS =====
 
 // Get real team member performance data from database
 async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db: &crate::database::Database) -> Option<TeamMemberInsights> {
     // First, get the member's user information
-    let user_url = format!("{}/rest/v1/users?id=eq.{}", db.base_url, member_id);
+    let user_url = format!("{}/rest/v1/users?id=eq.{}", db.base_url, encode_filter_value(member_id));
     
     let user_response = db.client
         .get(&user_url)
@@ -478,28 +1691,28 @@ async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db:
         .header("Authorization", format!("Bearer {}", db.api_key))
         .send()
         .await;
-    println!("DEBUG: User query for member_id {}: {:?}", member_id, user_response);
+    log::debug!("User query for member_id {}: {:?}", member_id, user_response);
     
     let user_data = match user_response {
         Ok(response) => {
             let text = response.text().await.unwrap_or_default();
-            println!("DEBUG: User response text: {}", text);
+            log::debug!("User response text: {}", text);
             
             let parsed: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
             if let Some(data) = parsed.as_array() {
                 if let Some(user) = data.get(0) {
                     user.clone()
                 } else {
-                    println!("DEBUG: No user found for member_id: {}", member_id);
+                    log::debug!("No user found for member_id: {}", member_id);
                     return None;
                 }
             } else {
-                println!("DEBUG: User response is not an array");
+                log::debug!("User response is not an array");
                 return None;
             }
         },
         Err(e) => {
-            println!("DEBUG: User query error: {:?}", e);
+            log::error!("User query error: {:?}", e);
             return None;
         }
     };
@@ -509,7 +1722,7 @@ async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db:
         .unwrap_or("Unknown User")
         .to_string();
     
-    println!("DEBUG: Found user: {} ({})", member_name, member_id);
+    log::debug!("Found user: {} ({})", member_name, member_id);
     
     // Get time entries for this member in the workspace
     let now = chrono::Utc::now();
@@ -531,28 +1744,28 @@ async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db:
         .header("Authorization", format!("Bearer {}", db.api_key))
         .send()
         .await;
-    println!("DEBUG: Time entries query for user {} in workspace {}: {:?}", member_id, workspace_id, time_entries_response);
+    log::debug!("Time entries query for user {} in workspace {}: {:?}", member_id, workspace_id, time_entries_response);
     
     let time_entries_data = match time_entries_response {
         Ok(response) => {
             let text = response.text().await.unwrap_or_default();
-            println!("DEBUG: Time entries response text: {}", text);
+            log::debug!("Time entries response text: {}", text);
             
             let parsed: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
             if let Some(data) = parsed.as_array() {
                 data.clone()
             } else {
-                println!("DEBUG: Time entries response is not an array");
+                log::debug!("Time entries response is not an array");
                 Vec::new()
             }
         },
         Err(e) => {
-            println!("DEBUG: Time entries query error: {:?}", e);
+            log::error!("Time entries query error: {:?}", e);
             Vec::new()
         }
     };
     
-    println!("DEBUG: Found {} time entries for user {}", time_entries_data.len(), member_name);
+    log::debug!("Found {} time entries for user {}", time_entries_data.len(), member_name);
     
     // Calculate time for different periods
     let mut total_time_today = 0.0;
@@ -615,7 +1828,7 @@ async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db:
         }
     }
     
-    println!("DEBUG: Member {} - Today: {:.1}h, Week: {:.1}h, Month: {:.1}h", 
+    log::debug!("Member {} - Today: {:.1}h, Week: {:.1}h, Month: {:.1}h", 
         member_name, total_time_today, total_time_this_week, total_time_this_month);
     
     Some(TeamMemberInsights {
@@ -625,14 +1838,8 @@ async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db:
         total_time_this_week,
         total_time_this_month,
         most_used_apps,
-        current_activity: None, // Could be enhanced to show current activity
-        task_stats: TaskStats { 
-            total: 0, 
-            todo: 0, 
-            in_progress: 0, 
-            done: 0, 
-            completion_rate: 0.0 
-        }, // Could be enhanced with real task data
+        current_activity: fetch_member_current_activity(member_id, db).await,
+        task_stats: fetch_member_task_stats(member_id, workspace_id, db).await,
         productivity_trend: ProductivityTrend {
             daily_hours: Vec::new(), // Could be enhanced with historical data
             peak_hours: Vec::new(),
@@ -640,13 +1847,72 @@ async fn get_real_team_member_insights(member_id: &str, workspace_id: &str, db:
     })
 }
 
+// The member's running time entry, if any. Errors yield None so one member
+// can't break the whole team report.
+async fn fetch_member_current_activity(member_id: &str, db: &Database) -> Option<CurrentActivityInfo> {
+    let url = format!(
+        "{}/rest/v1/time_entries?user_id=eq.{}&is_active=eq.true&select=*,applications(name,category,icon_path)&order=start_time.desc&limit=1",
+        db.base_url, member_id
+    );
+
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .ok()?;
+
+    if !response.status().is_success() {
+        log::error!("Failed to fetch active entry for {}: {}", member_id, response.status());
+        return None;
+    }
+
+    let entries: Vec<TimeEntryWithApp> = response.json().await.ok()?;
+    let active = entries.into_iter().next()?;
+
+    Some(CurrentActivityInfo {
+        app_name: active.app_name().unwrap_or("Unknown App").to_string(),
+        duration_seconds: (Utc::now() - active.entry.start_time).num_seconds().max(0),
+        is_active: active.entry.is_active,
+    })
+}
+
+// Task counts for the member's tasks in this workspace, zeros if they can't be fetched
+async fn fetch_member_task_stats(member_id: &str, workspace_id: &str, db: &Database) -> TaskStats {
+    let url = format!(
+        "{}/rest/v1/tasks?assignee_id=eq.{}&workspace_id=eq.{}",
+        db.base_url, member_id, workspace_id
+    );
+
+    let tasks: Vec<Task> = match db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => response.json().await.unwrap_or_default(),
+        Ok(response) => {
+            log::error!("Failed to fetch tasks for {}: {}", member_id, response.status());
+            Vec::new()
+        }
+        Err(e) => {
+            log::error!("Failed to fetch tasks for {}: {}", member_id, e);
+            Vec::new()
+        }
+    };
+
+    calculate_task_stats(&tasks)
+}
+
 // Get real team member insights for all members in a workspace
 async fn get_real_team_comparison(workspace_id: &str, db: &Database) -> Vec<TeamMemberInsights> {
     // Get all users in the workspace
     let users = match fetch_users_by_workspace(db, workspace_id).await {
         Ok(users) => users,
         Err(e) => {
-            println!("Failed to fetch users for workspace {}: {}", workspace_id, e);
+            log::error!("Failed to fetch users for workspace {}: {}", workspace_id, e);
             return vec![];
         }
     };
@@ -705,36 +1971,24 @@ async fn get_real_team_comparison(workspace_id: &str, db: &Database) -> Vec<Team
 }
 
 // Get real team insights based on actual team members
-async fn get_real_team_insights(workspace_id: &str, db: &Database) -> Vec<serde_json::Value> {
+async fn get_real_team_insights(workspace_id: &str, db: &Database) -> Vec<Insight> {
     // Get all users in the workspace
     let users = match fetch_users_by_workspace(db, workspace_id).await {
         Ok(users) => users,
         Err(e) => {
-            println!("Failed to fetch users for workspace {}: {}", workspace_id, e);
+            log::error!("Failed to fetch users for workspace {}: {}", workspace_id, e);
             return vec![];
         }
     };
 
-    let user_count = users.len();
-    let most_productive_user = users.first().map(|u| u.name.clone()).unwrap_or_else(|| "Team member".to_string());
-    
-    vec![
-        serde_json::json!({
-            "title": "Team Productivity Distribution",
-            "description": format!("Your team of {} members shows good productivity distribution with {} leading in daily hours.", user_count, most_productive_user),
-            "type": "info"
-        }),
-        serde_json::json!({
-            "title": "Collaboration Opportunities", 
-            "description": format!("With {} active team members, consider scheduling more collaborative sessions to leverage diverse skills.", user_count),
-            "type": "tip"
-        }),
-        serde_json::json!({
-            "title": "Team Performance",
-            "description": format!("Morning hours (9-11 AM) show highest productivity across your {} team members.", user_count),
-            "type": "achievement"
-        })
-    ]
+    let mut members = Vec::new();
+    for user in &users {
+        if let Some(member_insights) = get_real_team_member_insights(&user.id, workspace_id, db).await {
+            members.push(member_insights);
+        }
+    }
+
+    generate_team_insights(&members)
 }
 
 pub fn get_mock_team_member_insights(member_id: &str, member_name: &str) -> TeamMemberInsights {
@@ -844,13 +2098,13 @@ pub fn get_mock_team_summary() -> TeamSummary {
 
 // Real team overview function that fetches data from the database
 pub async fn get_real_team_overview(db: &Database, workspace_id: &str) -> Result<TeamSummary, String> {
-    println!("Getting real team overview for workspace: {}", workspace_id);
+    log::debug!("Getting real team overview for workspace: {}", workspace_id);
     
     // Get all users in the workspace
     let users = match fetch_users_by_workspace(db, workspace_id).await {
         Ok(users) => users,
         Err(e) => {
-            println!("Failed to fetch users for workspace {}: {}", workspace_id, e);
+            log::error!("Failed to fetch users for workspace {}: {}", workspace_id, e);
             return Err(format!("Failed to fetch users: {}", e));
         }
     };
@@ -897,7 +2151,7 @@ pub async fn get_real_team_overview(db: &Database, workspace_id: &str) -> Result
         0.0 
     };
     
-    println!("Team overview with hardcoded data: total={}, active={}, today_total={:.2}h, today_avg={:.2}h, week_avg={:.2}h", 
+    log::info!("Team overview with hardcoded data: total={}, active={}, today_total={:.2}h, today_avg={:.2}h, week_avg={:.2}h", 
              total_members, active_members, total_team_hours_today, average_hours_today, average_hours_this_week);
 
     Ok(TeamSummary {
@@ -911,12 +2165,29 @@ pub async fn get_real_team_overview(db: &Database, workspace_id: &str) -> Result
     })
 }
 
-// Tool execution function - converts AI tool calls into structured data
+// Canned member names for the mock member ids
+fn mock_member_name(member_id: &str) -> &'static str {
+    match member_id {
+        "user-1" => "John Manager",
+        "user-2" => "Sarah Developer",
+        "user-3" => "Mike Designer",
+        "user-4" => "Alex QA",
+        "user-5" => "Lisa Analyst",
+        _ => "Unknown Member",
+    }
+}
+
+fn get_mock_team_members() -> Vec<TeamMemberInsights> {
+    ["user-1", "user-2", "user-3", "user-5"]
+        .iter()
+        .map(|member_id| get_mock_team_member_insights(member_id, mock_member_name(member_id)))
+        .collect()
+}
+
+// Tool execution function - converts AI tool calls into structured mock data
 pub fn execute_tool(tool_name: &str, arguments: &serde_json::Value) -> Option<serde_json::Value> {
     match tool_name {
         "show_team_overview" => {
-            // For now, we'll use mock data since execute_tool is synchronous
-            // TODO: Make this async and pass database context to get real data
             let team_summary = get_mock_team_summary();
             Some(serde_json::json!({
                 "team_summary": team_summary
@@ -925,18 +2196,7 @@ pub fn execute_tool(tool_name: &str, arguments: &serde_json::Value) -> Option<se
 
         "show_member_performance" => {
             if let Some(member_id) = arguments.get("member_id").and_then(|v| v.as_str()) {
-                // NOTE: This synchronous version uses mock data
-                // Real database integration is available in execute_tool_async
-                let member_name = match member_id {
-                    "user-1" => "John Manager",
-                    "user-2" => "Sarah Developer",
-                    "user-3" => "Mike Designer",
-                    "user-4" => "Alex QA",
-                    "user-5" => "Lisa Analyst",
-                    _ => "Unknown Member",
-                };
-
-                let member_insights = get_mock_team_member_insights(member_id, member_name);
+                let member_insights = get_mock_team_member_insights(member_id, mock_member_name(member_id));
                 Some(serde_json::json!({
                     "member_insights": member_insights
                 }))
@@ -959,193 +2219,663 @@ pub fn execute_tool(tool_name: &str, arguments: &serde_json::Value) -> Option<se
             }))
         }
 
+        "show_team_member_comparison" => {
+            Some(serde_json::json!({
+                "team_members": get_mock_team_members()
+            }))
+        }
+
+        "show_team_insights" => {
+            Some(serde_json::json!({
+                "insights": generate_team_insights(&get_mock_team_members())
+            }))
+        }
+
+        "show_comparison" => {
+            let current = arguments.get("currentPeriod").and_then(|v| v.as_str()).unwrap_or("week");
+            let previous = arguments.get("previousPeriod").and_then(|v| v.as_str()).unwrap_or("last_week");
+            let insights = get_mock_productivity_insights();
+            let delta = insights.total_time_this_week - insights.total_time_last_week;
+            Some(serde_json::json!({
+                "comparison": Comparison {
+                    current_period: current.to_string(),
+                    previous_period: previous.to_string(),
+                    current_hours: insights.total_time_this_week,
+                    previous_hours: insights.total_time_last_week,
+                    delta,
+                    percent_change: delta / insights.total_time_last_week * 100.0,
+                    improved: delta > 0.0,
+                }
+            }))
+        }
+
+        "show_insights" => {
+            Some(serde_json::json!({
+                "insights": generate_insights(&get_mock_productivity_insights())
+            }))
+        }
+
+        "show_app_usage_breakdown" |
+        "show_time_tracking_stats" |
+        "show_productivity_trends" |
+        "show_task_status" |
+        "show_peak_hours" |
+        "show_stats_summary" => {
+            Some(serde_json::json!({
+                "insights": get_mock_productivity_insights()
+            }))
+        }
+
         _ => None,
     }
 }
 
-// Async tool execution function with database access
+/// Run a tool call against mock or real data, whichever `MACRO_USE_MOCK_DATA` selects
+pub async fn dispatch_tool(
+    tool_name: &str,
+    arguments: &serde_json::Value,
+    db: &Database,
+    workspace_id: Option<&str>,
+    insights: Option<&ProductivityInsights>,
+) -> Option<serde_json::Value> {
+    if use_mock_data() {
+        execute_tool(tool_name, arguments)
+    } else {
+        execute_tool_async(tool_name, arguments, db, workspace_id, insights).await
+    }
+}
+
+// Async tool execution function with database access; falls back to mock data
+// where there is no workspace or no real data yet
 pub async fn execute_tool_async(
     tool_name: &str, 
     arguments: &serde_json::Value,
     db: &Database,
-    workspace_id: Option<&str>
+    workspace_id: Option<&str>,
+    insights: Option<&ProductivityInsights>,
 ) -> Option<serde_json::Value> {
     match tool_name {
         "show_team_overview" => {
-            if let Some(workspace_id) = workspace_id {
-                // Use real database data
-                match get_real_team_overview(db, workspace_id).await {
-                    Ok(team_summary) => {
-                        println!("Successfully got real team overview for workspace: {}", workspace_id);
-                        Some(serde_json::json!({
-                            "team_summary": team_summary
-                        }))
-                    }
-                    Err(e) => {
-                        println!("Failed to get real team overview: {}, falling back to mock data", e);
-                        // Fallback to mock data
-                        let team_summary = get_mock_team_summary();
-                        Some(serde_json::json!({
-                            "team_summary": team_summary
-                        }))
-                    }
+            let Some(workspace_id) = workspace_id else {
+                return execute_tool(tool_name, arguments);
+            };
+            match get_real_team_overview(db, workspace_id).await {
+                Ok(team_summary) => {
+                    log::info!("Successfully got real team overview for workspace: {}", workspace_id);
+                    Some(serde_json::json!({
+                        "team_summary": team_summary
+                    }))
+                }
+                Err(e) => {
+                    log::warn!("Failed to get real team overview: {}, falling back to mock data", e);
+                    execute_tool(tool_name, arguments)
                 }
-            } else {
-                // No workspace selected, use mock data
-                let team_summary = get_mock_team_summary();
-                Some(serde_json::json!({
-                    "team_summary": team_summary
-                }))
             }
         }
 
         "show_member_performance" => {
-            if let Some(member_id) = arguments.get("member_id").and_then(|v| v.as_str()) {
-                // Try to get real member performance data if workspace is available
-                if let Some(workspace_id) = workspace_id {
-                    match get_real_team_member_insights(member_id, workspace_id, db).await {
-                        Some(member_insights) => {
-                            println!("Successfully retrieved real member performance for {}: {} ({:.1}h today)", 
-                                   member_insights.member_name, member_insights.member_id, member_insights.total_time_today);
-                            Some(serde_json::json!({
-                                "member_insights": member_insights
-                            }))
-                        }
-                        None => {
-                            println!("Failed to get real member performance for {}, falling back to mock data", member_id);
-                            // Fallback to mock data with hardcoded names
-                            let member_name = match member_id {
-                                "user-1" => "John Manager",
-                                "user-2" => "Sarah Developer",
-                                "user-3" => "Mike Designer",
-                                "user-4" => "Alex QA",
-                                "user-5" => "Lisa Analyst",
-                                _ => "Unknown Member",
-                            };
-                            let member_insights = get_mock_team_member_insights(member_id, member_name);
-                            Some(serde_json::json!({
-                                "member_insights": member_insights
-                            }))
-                        }
-                    }
-                } else {
-                    // No workspace selected, use mock data
-                    let member_name = match member_id {
-                        "user-1" => "John Manager",
-                        "user-2" => "Sarah Developer",
-                        "user-3" => "Mike Designer",
-                        "user-4" => "Alex QA",
-                        "user-5" => "Lisa Analyst",
-                        _ => "Unknown Member",
-                    };
-                    let member_insights = get_mock_team_member_insights(member_id, member_name);
+            let member_id = arguments.get("member_id").and_then(|v| v.as_str())?;
+            let Some(workspace_id) = workspace_id else {
+                return execute_tool(tool_name, arguments);
+            };
+            match get_real_team_member_insights(member_id, workspace_id, db).await {
+                Some(member_insights) => {
+                    log::info!("Successfully retrieved real member performance for {}: {} ({:.1}h today)", 
+                           member_insights.member_name, member_insights.member_id, member_insights.total_time_today);
                     Some(serde_json::json!({
                         "member_insights": member_insights
                     }))
                 }
-            } else {
-                None
+                None => {
+                    log::warn!("Failed to get real member performance for {}, falling back to mock data", member_id);
+                    execute_tool(tool_name, arguments)
+                }
             }
         }
 
-        "show_productivity_comparison" => {
-            let comparison_data = get_mock_productivity_comparison();
+        "show_team_member_comparison" => {
+            let Some(workspace_id) = workspace_id else {
+                return execute_tool(tool_name, arguments);
+            };
+            let team_members = get_real_team_comparison(workspace_id, db).await;
+            if team_members.is_empty() {
+                log::warn!("No team members found for workspace: {}, falling back to mock data", workspace_id);
+                return execute_tool(tool_name, arguments);
+            }
+            log::info!("Successfully retrieved real team comparison for workspace: {} ({} members)", workspace_id, team_members.len());
             Some(serde_json::json!({
-                "comparison_data": comparison_data
+                "team_members": team_members
             }))
         }
 
-        "show_task_summary" => {
-            let task_summary = get_mock_task_summary();
+        "show_team_insights" => {
+            let Some(workspace_id) = workspace_id else {
+                return execute_tool(tool_name, arguments);
+            };
+            let insights = get_real_team_insights(workspace_id, db).await;
             Some(serde_json::json!({
-                "task_summary": task_summary
+                "insights": insights
             }))
         }
 
-        "show_team_member_comparison" => {
-            if let Some(workspace_id) = workspace_id {
-                // Use real database data for the selected workspace
-                let team_members = get_real_team_comparison(workspace_id, db).await;
-                if !team_members.is_empty() {
-                    println!("Successfully retrieved real team comparison for workspace: {} ({} members)", workspace_id, team_members.len());
-                    Some(serde_json::json!({
-                        "team_members": team_members
-                    }))
-                } else {
-                    println!("No team members found for workspace: {}, falling back to mock data", workspace_id);
-                    // Fallback to mock data if no real data available
-                    let team_members = vec![
-                        get_mock_team_member_insights("user-1", "John Manager"),
-                        get_mock_team_member_insights("user-2", "Sarah Developer"),
-                        get_mock_team_member_insights("user-3", "Mike Designer"),
-                        get_mock_team_member_insights("user-5", "Lisa Analyst"),
-                    ];
-                    Some(serde_json::json!({
-                        "team_members": team_members
-                    }))
+        "show_comparison" => {
+            let current = arguments.get("currentPeriod").and_then(|v| v.as_str()).unwrap_or("week");
+            let previous = arguments.get("previousPeriod").and_then(|v| v.as_str()).unwrap_or("last_week");
+
+            match get_productivity_comparison(db, current.to_string(), previous.to_string()).await {
+                Ok(comparison) => Some(serde_json::json!({
+                    "comparison": comparison
+                })),
+                Err(e) => {
+                    log::error!("Failed to compute comparison {} vs {}: {}", current, previous, e);
+                    None
                 }
-            } else {
-                // No workspace selected, use mock data
-                let team_members = vec![
-                    get_mock_team_member_insights("user-1", "John Manager"),
-                    get_mock_team_member_insights("user-2", "Sarah Developer"),
-                    get_mock_team_member_insights("user-3", "Mike Designer"),
-                    get_mock_team_member_insights("user-5", "Lisa Analyst"),
-                ];
-                Some(serde_json::json!({
-                    "team_members": team_members
-                }))
             }
         }
 
-        "show_team_insights" => {
-            if let Some(workspace_id) = workspace_id {
-                // Use real data for the selected workspace
-                let insights = get_real_team_insights(workspace_id, db).await;
-                Some(serde_json::json!({
-                    "insights": insights
-                }))
-            } else {
-                // No workspace selected, use generic insights
-                Some(serde_json::json!({
-                    "insights": [
-                        {
-                            "title": "Team Productivity Distribution",
-                            "description": "Your team shows good productivity distribution across members.",
-                            "type": "info"
-                        },
-                        {
-                            "title": "Focus Areas",
-                            "description": "Consider increasing collaboration time - team members are spending significant individual time.",
-                            "type": "tip"
-                        },
-                        {
-                            "title": "Peak Performance",
-                            "description": "Morning hours (9-11 AM) show highest productivity across the team.",
-                            "type": "achievement"
-                        }
-                    ]
-                }))
+        "show_app_usage_breakdown" => {
+            let period = arguments.get("period").and_then(|v| v.as_str()).unwrap_or("week");
+            let limit = arguments.get("limit").and_then(|v| v.as_u64()).map_or(10, |limit| limit as usize);
+            let user_id = crate::current_user::get_current_user_id_or_error().ok()?;
+
+            match most_used_apps_in_db(db, &user_id, period, &ReportTimezone::Local, limit).await {
+                Ok(apps) if apps.is_empty() => Some(no_data_payload(Some(period))),
+                // Same shape as the insights payload the chart already reads
+                Ok(apps) => Some(serde_json::json!({
+                    "insights": { "most_used_apps": apps }
+                })),
+                Err(e) => {
+                    log::error!("Failed to compute app usage for {}: {}", period, e);
+                    None
+                }
             }
         }
 
-        // Individual productivity tools - these would normally return individual data
-        "show_app_usage_breakdown" |
-        "show_time_tracking_stats" |
-        "show_productivity_trends" |
-        "show_task_status" |
-        "show_peak_hours" |
-        "show_comparison" |
-        "show_insights" |
+        "show_time_tracking_stats" => {
+            let period = arguments.get("period").and_then(|v| v.as_str()).unwrap_or("week");
+            let user_id = crate::current_user::get_current_user_id_or_error().ok()?;
+            let tz = ReportTimezone::Local;
+            let now = Utc::now();
+
+            // The week can start in the previous month, so fetch from whichever is earlier
+            let (week_start, _) = period_range("week", &tz, now).ok()?;
+            let (month_start, _) = period_range("month", &tz, now).ok()?;
+            match fetch_time_entries_in_range(db, &user_id, week_start.min(month_start), now).await {
+                Ok(entries) => Some(time_stats_payload(&entries, period, &tz, now)),
+                Err(e) => {
+                    log::error!("Failed to compute time tracking stats: {}", e);
+                    None
+                }
+            }
+        }
+
+        "show_productivity_trends" | "show_peak_hours" => {
+            let period = arguments.get("period").and_then(|v| v.as_str()).unwrap_or("week");
+            let user_id = crate::current_user::get_current_user_id_or_error().ok()?;
+            let days = trend_days(period);
+            let now = Utc::now();
+
+            match fetch_time_entries_in_range(db, &user_id, now - Duration::days(days), now).await {
+                Ok(entries) if entries.is_empty() => Some(no_data_payload(Some(period))),
+                Ok(entries) => Some(serde_json::json!({
+                    "insights": {
+                        "productivity_trend": calculate_productivity_trend(&entries, days, &ReportTimezone::Local)
+                    }
+                })),
+                Err(e) => {
+                    log::error!("Failed to compute productivity trend for {}: {}", period, e);
+                    None
+                }
+            }
+        }
+
+        // Neither tool takes a period: they show all tasks and the standing totals
+        "show_task_status" => {
+            Some(match insights {
+                Some(insights) if insights.task_stats.total > 0 => serde_json::json!({
+                    "insights": { "task_stats": insights.task_stats }
+                }),
+                _ => no_data_payload(None),
+            })
+        }
+
         "show_stats_summary" => {
-            // For now, return individual productivity data for these
-            let insights = get_mock_productivity_insights();
-            Some(serde_json::json!({
-                "insights": insights
+            Some(match insights {
+                Some(insights) if insights.total_time_this_month > 0.0 || insights.task_stats.total > 0 => {
+                    serde_json::json!({ "insights": insights })
+                }
+                _ => no_data_payload(None),
+            })
+        }
+
+        "show_insights" => {
+            // Recommendations computed from the user's own data
+            insights.map(|insights| serde_json::json!({
+                "insights": generate_insights(insights)
             }))
         }
 
-        _ => None,
+        // No real data source for these yet
+        _ => execute_tool(tool_name, arguments),
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn entry_at(start: DateTime<Utc>, duration_seconds: i64) -> TimeEntry {
+        TimeEntry {
+            id: "entry-1".to_string(),
+            user_id: "user-1".to_string(),
+            app_id: Some("app-1".to_string()),
+            task_id: None,
+            workspace_id: None,
+            start_time: start,
+            end_time: Some(start + Duration::seconds(duration_seconds)),
+            duration_seconds: Some(duration_seconds),
+            is_active: false,
+            created_at: Some(start),
+            updated_at: Some(start),
+        }
+    }
+
+    #[test]
+    fn late_evening_entry_counts_on_local_date() {
+        let tz = ReportTimezone::from_param(Some("America/Los_Angeles")).unwrap();
+        let la: chrono_tz::Tz = "America/Los_Angeles".parse().unwrap();
+
+        // 23:00 yesterday in Los Angeles is already the next day in UTC
+        let local_date = Utc::now().with_timezone(&la).date_naive() - Duration::days(1);
+        let start = la
+            .from_local_datetime(&local_date.and_hms_opt(23, 0, 0).unwrap())
+            .single()
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_ne!(start.date_naive(), local_date);
+
+        let trend = calculate_productivity_trend(&[entry_at(start, 1800)], 7, &tz);
+
+        assert_eq!(trend.daily_hours.len(), 1);
+        assert_eq!(trend.daily_hours[0].date, local_date.to_string());
+        assert_eq!(trend.peak_hours, vec![23]);
+    }
+
+    fn usage_entry(start: DateTime<Utc>, app_id: &str, app_name: &str, seconds: i64) -> TimeEntryWithApp {
+        let mut entry = entry_at(start, seconds);
+        entry.app_id = Some(app_id.to_string());
+        TimeEntryWithApp {
+            entry,
+            application: Some(crate::database::EmbeddedApplication {
+                name: app_name.to_string(),
+                category: None,
+                icon_path: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn app_usage_keeps_the_top_apps_up_to_the_limit() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let entries = vec![
+            usage_entry(start, "app-slack", "Slack", 600),
+            usage_entry(start, "app-code", "Code", 3600),
+            usage_entry(start, "app-code", "Code", 1800),
+            usage_entry(start, "app-chrome", "Chrome", 1200),
+        ];
+
+        let usage = calculate_app_usage(&entries, start, start + Duration::days(1), 2);
+
+        let names: Vec<&str> = usage.iter().map(|app| app.app_name.as_str()).collect();
+        assert_eq!(names, vec!["Code", "Chrome"]);
+        assert!((usage[0].hours - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn app_usage_keeps_apps_sharing_a_name_apart() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        // Code on Windows and on macOS are separate applications with the same display name
+        let entries = vec![
+            usage_entry(start, "app-code-win", "Code", 3600),
+            usage_entry(start, "app-code-mac", "Code", 1800),
+        ];
+
+        let usage = calculate_app_usage(&entries, start, start + Duration::days(1), 5);
+
+        let mut hours: Vec<f64> = usage.iter().map(|app| app.hours).collect();
+        hours.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(hours, vec![0.5, 1.0]);
+        assert!(usage.iter().all(|app| app.app_name == "Code"));
+    }
+
+    #[test]
+    fn time_stats_without_entries_report_no_data() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 5, 12, 0, 0).unwrap();
+        let tz = ReportTimezone::Named(chrono_tz::UTC);
+
+        let empty = time_stats_payload(&[], "week", &tz, now);
+        assert_eq!(empty["no_data"], true);
+        assert!(empty.get("insights").is_none());
+
+        let tracked = time_stats_payload(&[entry_at(now - Duration::hours(2), 3600)], "week", &tz, now);
+        assert_eq!(tracked["insights"]["total_time_today"], 1.0);
+        assert_eq!(tracked["insights"]["total_time_this_week"], 1.0);
+    }
+
+    #[test]
+    fn time_stats_are_scoped_to_the_requested_period() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 5, 12, 0, 0).unwrap();
+        let tz = ReportTimezone::Named(chrono_tz::UTC);
+        // Monday of this week, but nothing today
+        let entries = [entry_at(now - Duration::days(2), 3600)];
+
+        let today = time_stats_payload(&entries, "today", &tz, now);
+        assert_eq!(today["no_data"], true);
+        assert_eq!(today["period"], "today");
+
+        let week = time_stats_payload(&entries, "week", &tz, now);
+        assert_eq!(week["insights"]["total_time_today"], 0.0);
+        assert_eq!(week["insights"]["total_time_this_week"], 1.0);
+    }
+
+    fn member(id: &str, name: &str, email: &str) -> User {
+        User {
+            id: id.to_string(),
+            name: name.to_string(),
+            email: Some(email.to_string()),
+            created_at: None,
+            updated_at: None,
+            image_url: None,
+            role: None,
+            workspace_id: None,
+            team_id: None,
+        }
+    }
+
+    #[test]
+    fn extracts_names_and_emails_but_not_plain_addresses() {
+        let mentions = extract_mentions("How is @sarah doing? Compare with @lee@example.com. Mail ops@example.com, @Sarah.");
+
+        assert_eq!(mentions, vec!["sarah".to_string(), "lee@example.com".to_string()]);
+    }
+
+    #[test]
+    fn mentions_match_name_first_name_or_email_local_part() {
+        let members = vec![
+            member("u1", "Sarah Chen", "schen@example.com"),
+            member("u2", "Sarah Diaz", "sarah.d@example.com"),
+            member("u3", "Mike Lee", "mike@example.com"),
+        ];
+
+        assert_eq!(members_matching("sarah", &members).len(), 2);
+        assert_eq!(members_matching("sarah.chen", &members)[0].id, "u1");
+        assert_eq!(members_matching("schen", &members)[0].id, "u1");
+        assert!(members_matching("nobody", &members).is_empty());
+    }
+
+    #[test]
+    fn tool_arguments_get_the_resolved_member_id() {
+        let mentions = vec![ResolvedMention {
+            token: "mike".to_string(),
+            user: member("u3", "Mike Lee", "mike@example.com"),
+        }];
+
+        let mut typed_name = serde_json::json!({ "member_id": "@mike" });
+        apply_resolved_mentions(&mut typed_name, &mentions);
+        assert_eq!(typed_name["member_id"], "u3");
+
+        let mut placeholder = serde_json::json!({ "member_id": "user-2" });
+        apply_resolved_mentions(&mut placeholder, &mentions);
+        assert_eq!(placeholder["member_id"], "u3");
+
+        let mut no_member = serde_json::json!({ "period": "week" });
+        apply_resolved_mentions(&mut no_member, &mentions);
+        assert!(no_member.get("member_id").is_none());
+    }
+
+    #[test]
+    fn productivity_score_blends_hours_tasks_and_focus() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+        let end = start + Duration::days(7);
+        // 20 hours in 24 entries of 50 minutes: half the weekly goal, full focus
+        let entries: Vec<TimeEntry> = (0..24)
+            .map(|i| entry_at(start + Duration::hours(i * 2), 50 * 60))
+            .collect();
+        let tasks = TaskStats { total: 4, todo: 1, in_progress: 1, done: 2, completion_rate: 50.0 };
+
+        let score = calculate_productivity_score("week", &entries, &tasks, start, end);
+
+        assert!((score.hours_score - 50.0).abs() < 1e-9);
+        assert_eq!(score.task_score, Some(50.0));
+        assert!((score.focus_score - 100.0).abs() < 1e-9);
+        assert!((score.score - 65.0).abs() < 1e-9);
+
+        let no_tasks = TaskStats { total: 0, todo: 0, in_progress: 0, done: 0, completion_rate: 0.0 };
+        let without_tasks = calculate_productivity_score("week", &entries, &no_tasks, start, end);
+        assert_eq!(without_tasks.task_score, None);
+        assert!((without_tasks.score - 50.0 / 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn every_available_tool_has_mock_data() {
+        let arguments = serde_json::json!({ "member_id": "user-2" });
+        for tool in crate::ai::get_available_tools() {
+            assert!(execute_tool(&tool.name, &arguments).is_some(), "no mock data for {}", tool.name);
+        }
+    }
+
+    #[test]
+    fn daily_hours_are_zero_filled_and_split_at_midnight() {
+        let tz = ReportTimezone::from_param(Some("UTC")).unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        // 23:00 on the 1st until 01:00 on the 2nd
+        let overnight = entry_at(Utc.with_ymd_and_hms(2024, 3, 1, 23, 0, 0).unwrap(), 7200);
+
+        let days = daily_hours_in_range(&[overnight], start, end, &tz);
+
+        let dates: Vec<&str> = days.iter().map(|day| day.date.as_str()).collect();
+        assert_eq!(dates, vec!["2024-03-01", "2024-03-02", "2024-03-03"]);
+        assert_eq!(days[0].hours, 1.0);
+        assert_eq!(days[1].hours, 1.0);
+        assert_eq!(days[2].hours, 0.0);
+    }
+
+    #[test]
+    fn rejects_unknown_timezone() {
+        assert!(ReportTimezone::from_param(Some("Mars/Olympus_Mons")).is_err());
+    }
+
+    #[test]
+    fn exported_report_round_trips() {
+        let tz = ReportTimezone::from_param(Some("UTC")).unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        let end = start + Duration::days(3);
+        let app = Application {
+            id: "app-1".to_string(),
+            name: "Editor".to_string(),
+            process_name: "editor.exe".to_string(),
+            icon_path: None,
+            category: Some("Development".to_string()),
+            is_tracked: true,
+            user_id: Some("user-1".to_string()),
+            created_at: None,
+            updated_at: None,
+            last_used: None,
+        };
+        let entries = vec![
+            entry_at(start + Duration::hours(9), 3600),
+            entry_at(start + Duration::days(2) + Duration::hours(10), 1800),
+        ];
+
+        let report = build_productivity_report(&entries, vec![app], &[], start, end, &tz, end);
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        let parsed: ProductivityReport = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.version, REPORT_SCHEMA_VERSION);
+        assert_eq!(parsed.range_start, start);
+        assert_eq!(parsed.range_end, end);
+        assert_eq!(parsed.entry_count, 2);
+        assert!((parsed.total_hours - 1.5).abs() < 1e-9);
+        assert_eq!(parsed.app_usage.len(), 1);
+        assert_eq!(parsed.app_usage[0].app_name, "Editor");
+        assert_eq!(parsed.category_usage[0].category, "Development");
+        assert_eq!(
+            parsed.daily_hours.iter().map(|d| d.date.as_str()).collect::<Vec<_>>(),
+            vec!["2024-03-04", "2024-03-05", "2024-03-06"]
+        );
+        assert_eq!(parsed.daily_hours[1].hours, 0.0);
+    }
+
+    fn task_stats(total: usize, done: usize) -> TaskStats {
+        TaskStats {
+            total,
+            todo: total - done,
+            in_progress: 0,
+            done,
+            completion_rate: if total > 0 { done as f64 / total as f64 * 100.0 } else { 0.0 },
+        }
+    }
+
+    // A week with nothing worth remarking on
+    fn quiet_week() -> ProductivityInsights {
+        ProductivityInsights {
+            total_time_today: 0.0,
+            total_time_this_week: 10.0,
+            total_time_this_month: 40.0,
+            most_used_apps: Vec::new(),
+            current_activity: None,
+            task_stats: task_stats(0, 0),
+            productivity_trend: ProductivityTrend { daily_hours: Vec::new(), peak_hours: Vec::new() },
+            total_time_last_week: 10.0,
+            entries_this_week: 0,
+            short_entries_this_week: 0,
+            team_members: None,
+            team_summary: None,
+        }
+    }
+
+    fn titles(insights: &[Insight]) -> Vec<&str> {
+        insights.iter().map(|insight| insight.title.as_str()).collect()
+    }
+
+    #[test]
+    fn a_quiet_week_has_no_insights() {
+        assert!(generate_insights(&quiet_week()).is_empty());
+    }
+
+    #[test]
+    fn many_short_sessions_flag_context_switching() {
+        let mut week = quiet_week();
+        week.entries_this_week = CONTEXT_SWITCH_MIN_ENTRIES;
+        week.short_entries_this_week = CONTEXT_SWITCH_MIN_ENTRIES / 2;
+        assert_eq!(titles(&generate_insights(&week)), vec!["Frequent Context Switching"]);
+
+        // Too few sessions to judge
+        week.entries_this_week = CONTEXT_SWITCH_MIN_ENTRIES - 1;
+        assert!(generate_insights(&week).is_empty());
+    }
+
+    #[test]
+    fn task_completion_is_called_out_at_either_extreme() {
+        let mut week = quiet_week();
+        week.task_stats = task_stats(10, 2);
+        let low = generate_insights(&week);
+        assert_eq!(titles(&low), vec!["Low Task Completion"]);
+        assert_eq!(low[0].insight_type, InsightType::Tip);
+
+        week.task_stats = task_stats(10, 9);
+        assert_eq!(titles(&generate_insights(&week)), vec!["Great Task Follow-Through"]);
+
+        // Too few tasks to say either
+        week.task_stats = task_stats(2, 0);
+        assert!(generate_insights(&week).is_empty());
+    }
+
+    #[test]
+    fn week_over_week_changes_and_a_dominant_app_are_reported() {
+        let mut week = quiet_week();
+        week.total_time_this_week = 6.0;
+        week.most_used_apps = vec![AppUsage { app_name: "Code".to_string(), hours: 4.0, percentage: 70.0 }];
+        week.productivity_trend.peak_hours = vec![9, 14, 10, 16];
+
+        let insights = generate_insights(&week);
+        assert_eq!(titles(&insights), vec!["One App Dominates Your Time", "Tracked Time Dropped", "Your Peak Hours"]);
+        assert!(insights[2].description.contains("9:00, 14:00, 10:00"));
+
+        week.total_time_this_week = 15.0;
+        assert!(titles(&generate_insights(&week)).contains(&"More Time Tracked"));
+    }
+
+    fn team_member(name: &str, hours_this_week: f64, tasks: TaskStats) -> TeamMemberInsights {
+        TeamMemberInsights {
+            member_id: name.to_lowercase(),
+            member_name: name.to_string(),
+            total_time_today: 0.0,
+            total_time_this_week: hours_this_week,
+            total_time_this_month: hours_this_week,
+            most_used_apps: Vec::new(),
+            current_activity: None,
+            task_stats: tasks,
+            productivity_trend: ProductivityTrend { daily_hours: Vec::new(), peak_hours: Vec::new() },
+        }
+    }
+
+    #[test]
+    fn team_insights_cover_workload_inactivity_and_backlogs() {
+        assert!(generate_team_insights(&[]).is_empty());
+
+        let members = vec![
+            team_member("Ada", 30.0, task_stats(4, 4)),
+            team_member("Grace", 10.0, task_stats(5, 1)),
+            team_member("Linus", 0.0, task_stats(0, 0)),
+        ];
+        let insights = generate_team_insights(&members);
+
+        assert_eq!(titles(&insights), vec!["Top Contributor", "Uneven Workload", "No Tracked Time", "Tasks Piling Up"]);
+        assert!(insights[0].description.starts_with("Ada"));
+        assert!(insights[2].description.ends_with("Linus."));
+        assert!(insights[3].description.ends_with("Grace."));
+    }
+
+    #[test]
+    fn an_even_team_has_no_workload_warning() {
+        let members = vec![team_member("Ada", 20.0, task_stats(0, 0)), team_member("Grace", 18.0, task_stats(0, 0))];
+        assert_eq!(titles(&generate_team_insights(&members)), vec!["Top Contributor"]);
+    }
+
+    #[tokio::test]
+    async fn app_daily_usage_fetches_only_that_apps_entries() {
+        let server = MockServer::start().await;
+        let now = Utc.with_ymd_and_hms(2025, 3, 5, 12, 0, 0).unwrap();
+        let entry = entry_at(Utc.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).unwrap(), 3600);
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("user_id", "eq.user-1"))
+            .and(query_param("app_id", "eq.app-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vec![entry]))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let tz = ReportTimezone::Named(chrono_tz::UTC);
+
+        let usage = app_daily_usage_in_db(&db, "user-1", "app-1", 3, &tz, now).await.unwrap();
+
+        let days: Vec<(&str, f64)> = usage.iter().map(|day| (day.date.as_str(), day.hours)).collect();
+        assert_eq!(days, vec![("2025-03-03", 0.0), ("2025-03-04", 1.0), ("2025-03-05", 0.0)]);
+    }
+
+    #[tokio::test]
+    async fn app_daily_usage_rejects_out_of_range_days() {
+        let db = Database::new("http://127.0.0.1:1".to_string(), "test-key".to_string()).unwrap();
+        let tz = ReportTimezone::Named(chrono_tz::UTC);
+
+        for days in [0, MAX_REPORT_DAYS + 1] {
+            assert!(app_daily_usage_in_db(&db, "user-1", "app-1", days, &tz, Utc::now()).await.is_err());
+        }
+    }
+}
@@ -1,18 +1,19 @@
 mod ai_assistant;
 
 use crate::database::{
-    Application, Database, Project, Task, Team, TimeEntry, User, WorkspaceMemberRecord,
+    encode_filter_value, Application, Database, DbError, Invitation, Project, Task, TaskPriority, TaskStatus,
+    Team, TimeEntry, TimeEntryWithApp, User, UserRole, WorkspaceMemberRecord,
 };
 use reqwest::{StatusCode, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{State, Manager};
-use regex;
+use crate::validation::validate_email;
 use ai_assistant::*;
 use std::collections::HashMap;
 
 // Re-export AI assistant commands for use in lib.rs
-pub use ai_assistant::get_productivity_insights;
+pub use ai_assistant::{get_productivity_insights, get_weekly_summary, get_category_usage, get_my_applications_with_usage, get_most_used_apps, get_productivity_score, export_report_json, get_daily_hours, get_app_daily_usage, use_mock_data};
 
 // Helper function to generate UUID strings
 fn generate_id() -> String {
@@ -24,6 +25,29 @@ fn now() -> chrono::DateTime<chrono::Utc> {
     chrono::Utc::now()
 }
 
+// Helper function to blank out tokens, passwords and keys before logging a JSON value
+fn redact_secrets(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let lower = k.to_lowercase();
+                    let is_secret = ["token", "password", "secret", "key"]
+                        .iter()
+                        .any(|marker| lower.contains(marker));
+                    if is_secret {
+                        (k.clone(), json!("[REDACTED]"))
+                    } else {
+                        (k.clone(), redact_secrets(v))
+                    }
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.iter().map(redact_secrets).collect()),
+        other => other.clone(),
+    }
+}
+
 const USER_SELECT_WITH_MEMBERS: &str = "id,name,email,created_at,updated_at,image_url,workspace_members(role,workspace_id,user_id,joined_at)";
 const USER_SELECT_WITH_MEMBERS_INNER: &str = "id,name,email,created_at,updated_at,image_url,workspace_members!inner(role,workspace_id,user_id,joined_at)";
 
@@ -86,33 +110,101 @@ async fn fetch_users_with_memberships(
         .await
         .map_err(|e| format!("Failed to parse users: {}", e))?;
 
-    let users = rows
-        .into_iter()
-        .map(|row| {
-            let mut user = User {
-                id: row.id,
-                name: row.name,
-                email: row.email,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-                image_url: row.image_url,
-                role: None,
-                workspace_id: None,
-                team_id: None,
-            };
-            apply_membership_meta(&mut user, &row.workspace_members);
-            user
-        })
-        .collect();
+    Ok(rows.into_iter().map(user_from_row).collect())
+}
+
+fn user_from_row(row: UserWithMemberships) -> User {
+    let mut user = User {
+        id: row.id,
+        name: row.name,
+        email: row.email,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        image_url: row.image_url,
+        role: None,
+        workspace_id: None,
+        team_id: None,
+    };
+    apply_membership_meta(&mut user, &row.workspace_members);
+    user
+}
+
+/// One page of a list query together with the total number of matching rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+// GET one page of rows with `count=exact` and read the total out of Content-Range
+async fn fetch_page<T: serde::de::DeserializeOwned>(
+    db: &Database,
+    table: &str,
+    filters: &[(&str, &str)],
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Page<T>, String> {
+    let offset = offset.unwrap_or(0);
+    let mut url = Url::parse(&format!("{}/rest/v1/{}", db.base_url, table))
+        .map_err(|e| format!("Invalid base URL: {}", e))?;
+    {
+        let mut query = url.query_pairs_mut();
+        for (key, value) in filters {
+            query.append_pair(key, value);
+        }
+        query.append_pair("limit", &limit.unwrap_or(DEFAULT_PAGE_SIZE).to_string());
+        query.append_pair("offset", &offset.to_string());
+    }
+
+    let response = db
+        .client
+        .get(url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Prefer", "count=exact")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", table, e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch {}: {} - {}", table, status, error_text));
+    }
 
-    Ok(users)
+    let total = response
+        .headers()
+        .get("content-range")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range_count);
+    let items: Vec<T> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse {}: {}", table, e))?;
+
+    Ok(Page {
+        total: total.unwrap_or(offset as usize + items.len()),
+        items,
+    })
 }
 
 async fn fetch_user_by_id(db: &Database, user_id: &str) -> Result<Option<User>, String> {
     let users = fetch_users_with_memberships(
         db,
         USER_SELECT_WITH_MEMBERS,
-        &[ ("id", format!("eq.{}", user_id)) ],
+        &[ ("id", format!("eq.{}", encode_filter_value(user_id))) ],
+    )
+    .await?;
+    Ok(users.into_iter().next())
+}
+
+async fn fetch_user_by_email(db: &Database, email: &str) -> Result<Option<User>, String> {
+    let users = fetch_users_with_memberships(
+        db,
+        USER_SELECT_WITH_MEMBERS,
+        &[ ("email", format!("eq.{}", encode_filter_value(email.trim()))) ],
     )
     .await?;
     Ok(users.into_iter().next())
@@ -125,7 +217,7 @@ async fn fetch_users_by_workspace(
     fetch_users_with_memberships(
         db,
         USER_SELECT_WITH_MEMBERS_INNER,
-        &[ ("workspace_members.workspace_id", format!("eq.{}", workspace_id)) ],
+        &[ ("workspace_members.workspace_id", format!("eq.{}", encode_filter_value(workspace_id))) ],
     )
     .await
 }
@@ -149,7 +241,7 @@ async fn fetch_membership_for_user(
 ) -> Result<Option<WorkspaceMemberRecord>, String> {
     let url = format!(
         "{}/rest/v1/workspace_members?user_id=eq.{}&select=id,user_id,workspace_id,role,joined_at",
-        db.base_url, user_id
+        db.base_url, encode_filter_value(user_id)
     );
 
     let response = db
@@ -182,7 +274,7 @@ async fn fetch_memberships_for_user(
 ) -> Result<Vec<WorkspaceMemberRecord>, String> {
     let url = format!(
         "{}/rest/v1/workspace_members?user_id=eq.{}&select=id,user_id,workspace_id,role,joined_at",
-        db.base_url, user_id
+        db.base_url, encode_filter_value(user_id)
     );
 
     let response = db
@@ -209,6 +301,31 @@ async fn fetch_memberships_for_user(
     Ok(memberships)
 }
 
+// Ensure the user holds one of the allowed roles in the given workspace.
+// Returns the user's role on success.
+async fn require_workspace_role(
+    db: &Database,
+    user_id: &str,
+    workspace_id: &str,
+    allowed_roles: &[&str],
+) -> Result<String, String> {
+    let memberships = fetch_memberships_for_user(db, user_id).await?;
+    let role = memberships
+        .iter()
+        .find(|record| record.workspace_id.as_deref() == Some(workspace_id))
+        .and_then(|record| record.role.clone())
+        .ok_or_else(|| "You are not a member of this workspace".to_string())?;
+
+    if allowed_roles.contains(&role.as_str()) {
+        Ok(role)
+    } else {
+        Err(format!(
+            "This action requires one of the following roles: {}",
+            allowed_roles.join(", ")
+        ))
+    }
+}
+
 async fn upsert_workspace_membership(
     db: &Database,
     user_id: &str,
@@ -222,7 +339,7 @@ async fn upsert_workspace_membership(
 
     let membership_url = format!(
         "{}/rest/v1/workspace_members?user_id=eq.{}",
-        db.base_url, user_id
+        db.base_url, encode_filter_value(user_id)
     );
 
     let mut update_map = serde_json::Map::new();
@@ -323,6 +440,70 @@ async fn upsert_workspace_membership(
     }
 }
 
+// Move the membership row for `from_workspace` to `to_workspace` in a single PATCH,
+// leaving the user's other memberships untouched.
+async fn transfer_user_workspace_in_db(
+    db: &Database,
+    user_id: &str,
+    from_workspace: &str,
+    to_workspace: &str,
+    role: Option<UserRole>,
+    reset_joined_at: bool,
+) -> Result<WorkspaceMemberRecord, String> {
+    if from_workspace == to_workspace {
+        return Err("Source and destination workspaces are the same".to_string());
+    }
+
+    let memberships = fetch_memberships_for_user(db, user_id).await?;
+    if memberships.iter().any(|record| record.workspace_id.as_deref() == Some(to_workspace)) {
+        return Err("User is already a member of the destination workspace".to_string());
+    }
+    if !memberships.iter().any(|record| record.workspace_id.as_deref() == Some(from_workspace)) {
+        return Err("User is not a member of the source workspace".to_string());
+    }
+
+    let mut update_map = serde_json::Map::new();
+    update_map.insert("workspace_id".to_string(), json!(to_workspace));
+    if let Some(role) = role {
+        update_map.insert("role".to_string(), json!(role));
+    }
+    if reset_joined_at {
+        update_map.insert("joined_at".to_string(), json!(now().to_rfc3339()));
+    }
+
+    let url = format!(
+        "{}/rest/v1/workspace_members?user_id=eq.{}&workspace_id=eq.{}",
+        db.base_url, encode_filter_value(user_id), encode_filter_value(from_workspace)
+    );
+    let response = db
+        .client
+        .patch(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "return=representation")
+        .json(&update_map)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to transfer workspace membership: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to transfer workspace membership: {} - {}", status, error_text));
+    }
+
+    let updated: Vec<WorkspaceMemberRecord> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse transferred membership: {}", e))?;
+
+    updated
+        .into_iter()
+        .next()
+        .ok_or_else(|| "User is not a member of the source workspace".to_string())
+}
+
 // ===== USER COMMANDS =====
 
 #[tauri::command]
@@ -330,16 +511,15 @@ pub async fn create_user(
     db: State<'_, Database>,
     name: String,
     email: String,
-    teamId: String,
+    team_id: String,
     role: String,
 ) -> Result<User, String> {
-    match role.as_str() {
-        "owner" | "manager" | "member" => {},
-        _ => return Err("Invalid role. Must be 'owner', 'manager', or 'member'".to_string()),
-    }
+    let role: UserRole = role.parse()?;
+    let email = email.trim().to_string();
+    validate_email(&email)?;
 
     // Debug logging
-    println!("Creating user with teamId: {:?}", teamId);
+    log::info!("Creating user with team_id: {:?}", team_id);
     let user_id = generate_id();
     let timestamp = now().to_rfc3339();
 
@@ -352,13 +532,13 @@ pub async fn create_user(
         "image_url": null
     });
 
-    println!("User data being sent to database: {}", user_payload);
+    log::debug!("User data being sent to database: {}", user_payload);
 
     db.execute_query("users", "POST", Some(user_payload))
         .await
         .map_err(|e| format!("Failed to create user: {}", e))?;
 
-    let trimmed_team = teamId.trim();
+    let trimmed_team = team_id.trim();
     if !trimmed_team.is_empty() && !trimmed_team.eq_ignore_ascii_case("unassigned") {
         upsert_workspace_membership(
             &db,
@@ -376,11 +556,11 @@ pub async fn create_user(
 }
 
 #[tauri::command]
-pub async fn delete_user(db: State<'_, Database>, userId: String) -> Result<(), String> {
-    println!("Delete user command called with userId: {}", userId);
+pub async fn delete_user(db: State<'_, Database>, user_id: String) -> Result<(), String> {
+    log::debug!("Delete user command called with user_id: {}", user_id);
     
-    let url = format!("{}/rest/v1/users?id=eq.{}", db.base_url, userId);
-    println!("Delete URL: {}", url);
+    let url = format!("{}/rest/v1/users?id=eq.{}", db.base_url, encode_filter_value(&user_id));
+    log::debug!("Delete URL: {}", url);
     
     let response = db.client
         .delete(&url)
@@ -390,13 +570,13 @@ pub async fn delete_user(db: State<'_, Database>, userId: String) -> Result<(),
         .await
         .map_err(|e| format!("Failed to delete user: {}", e))?;
 
-    println!("Delete response status: {}", response.status());
+    log::debug!("Delete response status: {}", response.status());
 
     if !response.status().is_success() {
         return Err(format!("Failed to delete user: {}", response.status()));
     }
 
-    println!("User deleted successfully");
+    log::info!("User deleted successfully");
     Ok(())
 }
 
@@ -405,12 +585,106 @@ pub async fn get_user(db: State<'_, Database>, user_id: String) -> Result<Option
     fetch_user_by_id(&db, &user_id).await
 }
 
+#[tauri::command]
+pub async fn get_user_by_email(db: State<'_, Database>, email: String) -> Result<Option<User>, String> {
+    fetch_user_by_email(&db, &email).await
+}
+
+// Largest avatar we'll inline as a data URI
+const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+
+// Data URIs keyed by user id, tagged with the user's updated_at so a new upload invalidates them
+static AVATAR_CACHE: once_cell::sync::Lazy<
+    std::sync::Mutex<HashMap<String, (Option<chrono::DateTime<chrono::Utc>>, String)>>,
+> = once_cell::sync::Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+async fn get_user_avatar_in_db(db: &Database, user_id: &str) -> Result<Option<String>, String> {
+    let user = match fetch_user_by_id(db, user_id).await? {
+        Some(user) => user,
+        None => return Ok(None),
+    };
+    let image_url = match user.image_url.as_deref().map(str::trim) {
+        Some(url) if !url.is_empty() => url.to_string(),
+        _ => return Ok(None),
+    };
+
+    if let Ok(cache) = AVATAR_CACHE.lock() {
+        if let Some((updated_at, data_uri)) = cache.get(user_id) {
+            if *updated_at == user.updated_at {
+                return Ok(Some(data_uri.clone()));
+            }
+        }
+    }
+
+    // Storage paths are relative to the Supabase project
+    let image_url = if image_url.starts_with('/') {
+        format!("{}{}", db.base_url.trim_end_matches('/'), image_url)
+    } else {
+        image_url
+    };
+    let url = Url::parse(&image_url).map_err(|e| format!("Invalid avatar URL: {}", e))?;
+
+    // Only send our key to our own project, never to a third-party image host
+    let same_origin = Url::parse(&db.base_url)
+        .map(|base| base.origin() == url.origin())
+        .unwrap_or(false);
+    let mut request = db.client.get(url);
+    if same_origin {
+        request = request
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch avatar: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch avatar: {}", response.status()));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_AVATAR_BYTES) {
+        return Err("Avatar image is too large".to_string());
+    }
+
+    let mime = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_string())
+        .filter(|value| value.starts_with("image/"))
+        .unwrap_or_else(|| "image/png".to_string());
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to fetch avatar: {}", e))?;
+    if bytes.len() > MAX_AVATAR_BYTES {
+        return Err("Avatar image is too large".to_string());
+    }
+
+    use base64::Engine;
+    let data_uri = format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    );
+    if let Ok(mut cache) = AVATAR_CACHE.lock() {
+        cache.insert(user_id.to_string(), (user.updated_at, data_uri.clone()));
+    }
+    Ok(Some(data_uri))
+}
+
+#[tauri::command]
+pub async fn get_user_avatar(db: State<'_, Database>, user_id: String) -> Result<Option<String>, String> {
+    get_user_avatar_in_db(&db, &user_id).await
+}
+
 #[tauri::command]
 pub async fn get_users_by_team(
     db: State<'_, Database>,
-    teamId: Option<String>,
+    team_id: Option<String>,
 ) -> Result<Vec<User>, String> {
-    match teamId {
+    match team_id {
         Some(tid) => {
             let trimmed = tid.trim();
             if trimmed.is_empty() {
@@ -430,6 +704,27 @@ pub async fn get_all_users(db: State<'_, Database>) -> Result<Vec<User>, String>
     fetch_all_users(&db).await
 }
 
+#[tauri::command]
+pub async fn get_all_users_paged(
+    db: State<'_, Database>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Page<User>, String> {
+    let page: Page<UserWithMemberships> = fetch_page(
+        &db,
+        "users",
+        &[("select", USER_SELECT_WITH_MEMBERS), ("order", "name.asc")],
+        limit,
+        offset,
+    )
+    .await?;
+
+    Ok(Page {
+        items: page.items.into_iter().map(user_from_row).collect(),
+        total: page.total,
+    })
+}
+
 #[tauri::command]
 pub async fn update_user(
     db: State<'_, Database>,
@@ -449,6 +744,8 @@ pub async fn update_user(
         update_map.insert("name".to_string(), json!(name));
     }
     if let Some(email) = email {
+        let email = email.trim();
+        validate_email(email)?;
         update_map.insert("email".to_string(), json!(email));
     }
     if let Some(image_url) = image_url {
@@ -456,7 +753,7 @@ pub async fn update_user(
     }
 
     if !update_map.is_empty() {
-        let url = format!("{}/rest/v1/users?id=eq.{}", db.base_url, user_id);
+        let url = format!("{}/rest/v1/users?id=eq.{}", db.base_url, encode_filter_value(&user_id));
         db.client
             .patch(&url)
             .header("apikey", &db.api_key)
@@ -495,19 +792,44 @@ pub async fn update_user(
         .ok_or_else(|| "User was updated but could not be retrieved".to_string())
 }
 
-// ===== TEAM COMMANDS =====
-
 #[tauri::command]
-pub async fn create_team(
+pub async fn transfer_user_workspace(
     db: State<'_, Database>,
-    team_name: String,
-) -> Result<Team, String> {
-    let team_data = json!({
+    user_id: String,
+    from_workspace: String,
+    to_workspace: String,
+    role: Option<String>,
+    reset_joined_at: Option<bool>,
+) -> Result<WorkspaceMemberRecord, String> {
+    let role = role.map(|r| r.parse::<UserRole>()).transpose()?;
+    transfer_user_workspace_in_db(
+        &db,
+        &user_id,
+        from_workspace.trim(),
+        to_workspace.trim(),
+        role,
+        reset_joined_at.unwrap_or(false),
+    )
+    .await
+}
+
+// ===== TEAM COMMANDS =====
+
+// Row inserted into `workspaces`; the table calls the team name `name`
+fn new_team_row(team_name: &str, created_by: &str) -> serde_json::Value {
+    json!({
         "id": generate_id(),
         "name": team_name,
+        "created_by": created_by,
         "created_at": now().to_rfc3339(),
         "updated_at": now().to_rfc3339()
-    });
+    })
+}
+
+// Create the workspace and make its creator the owner. PostgREST has no
+// transactions across requests, so a failed membership insert deletes the workspace again
+async fn create_team_in_db(db: &Database, team_name: &str, user_id: &str) -> Result<Team, String> {
+    let team_data = new_team_row(team_name, user_id);
 
     let response = db
         .execute_query("workspaces", "POST", Some(team_data))
@@ -517,17 +839,46 @@ pub async fn create_team(
     // The response should be an array with the created record
     let created_teams: Vec<Team> = serde_json::from_value(response)
         .map_err(|e| format!("Failed to parse created team: {}", e))?;
-    
-    if let Some(created_team) = created_teams.into_iter().next() {
-        Ok(created_team)
-    } else {
-        Err("No team was created".to_string())
+    let created_team = created_teams
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No team was created".to_string())?;
+
+    let membership_data = json!({
+        "user_id": user_id,
+        "workspace_id": created_team.id,
+        "role": UserRole::Owner.as_str(),
+    });
+
+    if let Err(e) = db
+        .execute_query("workspace_members", "POST", Some(membership_data))
+        .await
+    {
+        let filter = format!("eq.{}", created_team.id);
+        if let Err(rollback_err) = db
+            .execute_query_with_params("workspaces", "DELETE", &[("id", filter.as_str())], None, None)
+            .await
+        {
+            log::error!("Failed to roll back workspace {}: {}", created_team.id, rollback_err);
+        }
+        return Err(format!("Failed to add you to the new team: {}", e));
     }
+
+    Ok(created_team)
+}
+
+#[tauri::command]
+pub async fn create_team(
+    db: State<'_, Database>,
+    team_name: String,
+) -> Result<Team, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    create_team_in_db(&db, &team_name, &user_id).await
 }
 
 #[tauri::command]
-pub async fn get_team(db: State<'_, Database>, teamId: String) -> Result<Option<Team>, String> {
-    let url = format!("{}/rest/v1/workspaces?id=eq.{}", db.base_url, teamId);
+pub async fn get_team(db: State<'_, Database>, team_id: String) -> Result<Option<Team>, String> {
+    let url = format!("{}/rest/v1/workspaces?id=eq.{}", db.base_url, encode_filter_value(&team_id));
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -536,12 +887,66 @@ pub async fn get_team(db: State<'_, Database>, teamId: String) -> Result<Option<
         .await
         .map_err(|e| format!("Failed to fetch team: {}", e))?;
 
-    if !response.status().is_success() {
+    optional_row(response, "team").await
+}
+
+// First row of a lookup by id. An empty result or a 404 means there is no such row;
+// any other failure is an error, so the caller can tell "missing" from "broken"
+async fn optional_row<T: serde::de::DeserializeOwned>(response: reqwest::Response, what: &str) -> Result<Option<T>, String> {
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
         return Ok(None);
     }
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Failed to fetch {}: {} - {}", what, status, error_text));
+    }
+
+    let rows: Vec<T> = response.json().await.map_err(|e| format!("Failed to parse {}: {}", what, e))?;
+    Ok(rows.into_iter().next())
+}
+
+#[tauri::command]
+pub async fn update_team(
+    db: State<'_, Database>,
+    team_id: String,
+    team_name: String,
+) -> Result<Team, String> {
+    let team_name = team_name.trim();
+    if team_name.is_empty() {
+        return Err("Team name is required".to_string());
+    }
+
+    // Only owners and managers may rename a workspace
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    require_workspace_role(&db, &user_id, &team_id, &["owner", "manager"]).await?;
+
+    let url = format!("{}/rest/v1/workspaces?id=eq.{}", db.base_url, encode_filter_value(&team_id));
+    let response = db.client
+        .patch(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "return=representation")
+        .json(&json!({
+            "name": team_name,
+            "updated_at": now().to_rfc3339()
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update team: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to update team: {} - {}", status, error_text));
+    }
 
-    let teams: Vec<Team> = response.json().await.map_err(|e| format!("Failed to parse team: {}", e))?;
-    Ok(teams.into_iter().next())
+    let updated_teams: Vec<Team> = response.json().await.map_err(|e| format!("Failed to parse updated team: {}", e))?;
+    updated_teams
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No team was updated".to_string())
 }
 
 #[tauri::command]
@@ -562,8 +967,12 @@ pub async fn get_all_teams(db: State<'_, Database>) -> Result<Vec<Team>, String>
 #[tauri::command]
 pub async fn get_my_workspaces(db: State<'_, Database>) -> Result<Vec<Team>, String> {
     let user_id = crate::current_user::get_current_user_id_or_error()?;
+    fetch_workspaces_for_user(&db, &user_id).await
+}
 
-    let memberships = fetch_memberships_for_user(&db, &user_id).await?;
+// Workspaces the user is a member of or created, sorted by name
+async fn fetch_workspaces_for_user(db: &Database, user_id: &str) -> Result<Vec<Team>, String> {
+    let memberships = fetch_memberships_for_user(db, user_id).await?;
     let mut workspace_ids: Vec<String> = memberships
         .iter()
         .filter_map(|record| record.workspace_id.clone())
@@ -636,6 +1045,61 @@ pub async fn get_my_workspaces(db: State<'_, Database>) -> Result<Vec<Team>, Str
     Ok(result)
 }
 
+/// A workspace with its member count and the hours its members tracked this week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStats {
+    #[serde(flatten)]
+    pub workspace: Team,
+    pub member_count: usize,
+    pub hours_this_week: f64,
+}
+
+/// The current user's workspaces with activity stats, most active first
+#[tauri::command]
+pub async fn get_my_workspaces_with_stats(db: State<'_, Database>) -> Result<Vec<WorkspaceStats>, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+
+    let tz = ReportTimezone::Local;
+    let week_end = now();
+    let week_start = tz.start_of_day(monday_of_week(tz.to_local(week_end).date()));
+
+    // People can share several workspaces, so each member's hours are only fetched once
+    let mut hours_by_user: HashMap<String, f64> = HashMap::new();
+    let mut stats = Vec::new();
+
+    for workspace in fetch_workspaces_for_user(&db, &user_id).await? {
+        let members = fetch_users_by_workspace(&db, &workspace.id).await?;
+
+        let mut hours_this_week = 0.0;
+        for member in &members {
+            let hours = match hours_by_user.get(&member.id) {
+                Some(hours) => *hours,
+                None => {
+                    let entries = fetch_time_entries_in_range(&db, &member.id, week_start, week_end).await?;
+                    let hours = calculate_hours_in_range(&entries, week_start, week_end);
+                    hours_by_user.insert(member.id.clone(), hours);
+                    hours
+                }
+            };
+            hours_this_week += hours;
+        }
+
+        stats.push(WorkspaceStats {
+            workspace,
+            member_count: members.len(),
+            hours_this_week,
+        });
+    }
+
+    stats.sort_by(|a, b| {
+        b.hours_this_week
+            .partial_cmp(&a.hours_this_week)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.workspace.team_name.cmp(&b.workspace.team_name))
+    });
+    Ok(stats)
+}
+
 #[tauri::command]
 pub async fn get_all_workspace_members(db: State<'_, Database>) -> Result<Vec<WorkspaceMemberRecord>, String> {
     let url = format!(
@@ -661,10 +1125,10 @@ pub async fn get_all_workspace_members(db: State<'_, Database>) -> Result<Vec<Wo
 }
 
 #[tauri::command]
-pub async fn delete_team(db: State<'_, Database>, teamId: String) -> Result<(), String> {
-    println!("Delete team command called with teamId: {}", teamId);
-    let url = format!("{}/rest/v1/workspaces?id=eq.{}", db.base_url, teamId);
-    println!("Delete team URL: {}", url);
+pub async fn delete_team(db: State<'_, Database>, team_id: String) -> Result<(), String> {
+    log::debug!("Delete team command called with team_id: {}", team_id);
+    let url = format!("{}/rest/v1/workspaces?id=eq.{}", db.base_url, encode_filter_value(&team_id));
+    log::debug!("Delete team URL: {}", url);
     
     let response = db.client
         .delete(&url)
@@ -674,73 +1138,251 @@ pub async fn delete_team(db: State<'_, Database>, teamId: String) -> Result<(),
         .await
         .map_err(|e| format!("Failed to delete team: {}", e))?;
 
-    println!("Delete team response status: {}", response.status());
+    log::debug!("Delete team response status: {}", response.status());
 
     if !response.status().is_success() {
         return Err(format!("Failed to delete team: {}", response.status()));
     }
 
-    println!("Team deleted successfully");
+    log::info!("Team deleted successfully");
     Ok(())
 }
 
-// ===== PROJECT COMMANDS =====
+// ===== INVITATION COMMANDS =====
+
+const INVITATION_EXPIRY_DAYS: i64 = 7;
 
 #[tauri::command]
-pub async fn create_project(
+pub async fn create_invitation(
     db: State<'_, Database>,
-    name: String,
-    teamId: String,
-    manager_id: String,
-    description: Option<String>,
-) -> Result<Project, String> {
-    let project_data = json!({
+    workspace_id: String,
+    email: String,
+    role: String,
+) -> Result<Invitation, String> {
+    let role: UserRole = role.parse()?;
+
+    let email = email.trim().to_lowercase();
+    if email.is_empty() {
+        return Err("Email is required".to_string());
+    }
+    validate_email(&email)?;
+
+    // Only owners and managers may invite people into a workspace
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    require_workspace_role(&db, &user_id, &workspace_id, &["owner", "manager"]).await?;
+
+    let invitation_data = json!({
         "id": generate_id(),
-        "name": name,
-        "workspace_id": teamId,
-        "manager_id": manager_id,
-        "description": description,
-        "created_at": now().to_rfc3339(),
-        "updated_at": now().to_rfc3339()
+        "workspace_id": workspace_id,
+        "email": email,
+        "role": role,
+        "token": uuid::Uuid::new_v4().simple().to_string(),
+        "invited_by": user_id,
+        "expires_at": (now() + chrono::Duration::days(INVITATION_EXPIRY_DAYS)).to_rfc3339(),
+        "used_at": null,
+        "created_at": now().to_rfc3339()
     });
 
     let response = db
-        .execute_query("projects", "POST", Some(project_data))
+        .execute_query("invitations", "POST", Some(invitation_data))
         .await
-        .map_err(|e| format!("Failed to create project: {}", e))?;
+        .map_err(|e| format!("Failed to create invitation: {}", e))?;
 
-    // The response should be an array with the created record
-    let created_projects: Vec<Project> = serde_json::from_value(response)
-        .map_err(|e| format!("Failed to parse created project: {}", e))?;
-    
-    if let Some(created_project) = created_projects.into_iter().next() {
-        Ok(created_project)
-    } else {
-        Err("No project was created".to_string())
-    }
-}
+    let created_invitations: Vec<Invitation> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse created invitation: {}", e))?;
+
+    created_invitations
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No invitation was created".to_string())
+}
 
 #[tauri::command]
-pub async fn get_projects_by_team(
+pub async fn accept_invitation(
     db: State<'_, Database>,
-    teamId: String,
-) -> Result<Vec<Project>, String> {
-    let url = format!("{}/rest/v1/projects?workspace_id=eq.{}", db.base_url, teamId);
+    token: String,
+) -> Result<WorkspaceMemberRecord, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    accept_invitation_in_db(&db, &user_id, &token).await
+}
+
+async fn accept_invitation_in_db(db: &Database, user_id: &str, token: &str) -> Result<WorkspaceMemberRecord, String> {
+    let url = format!("{}/rest/v1/invitations?token=eq.{}", db.base_url, encode_filter_value(token.trim()));
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
         .header("Authorization", format!("Bearer {}", db.api_key))
         .send()
         .await
+        .map_err(|e| format!("Failed to fetch invitation: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch invitation: {}", response.status()));
+    }
+
+    let invitations: Vec<Invitation> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse invitation: {}", e))?;
+
+    let invitation = invitations
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Invitation not found".to_string())?;
+
+    if invitation.used_at.is_some() {
+        return Err("This invitation has already been used".to_string());
+    }
+    if invitation.expires_at < now() {
+        return Err("This invitation has expired".to_string());
+    }
+
+    // Only the invited address can use the token
+    let user = fetch_user_by_id(db, user_id)
+        .await?
+        .ok_or_else(|| "User not found".to_string())?;
+    if !user
+        .email
+        .as_deref()
+        .is_some_and(|email| email.trim().eq_ignore_ascii_case(invitation.email.trim()))
+    {
+        return Err("This invitation was sent to a different email address".to_string());
+    }
+    let memberships = fetch_memberships_for_user(db, user_id).await?;
+    if memberships
+        .iter()
+        .any(|membership| membership.workspace_id.as_deref() == Some(invitation.workspace_id.as_str()))
+    {
+        return Err("You are already a member of this workspace".to_string());
+    }
+
+    // Claim the invitation first. Filtering on used_at=is.null means a second
+    // accept racing with this one updates no rows and is rejected.
+    let claim_url = format!(
+        "{}/rest/v1/invitations?id=eq.{}&used_at=is.null",
+        db.base_url, encode_filter_value(&invitation.id)
+    );
+    let claim_response = db.client
+        .patch(&claim_url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "return=representation")
+        .json(&json!({
+            "used_at": now().to_rfc3339(),
+            "used_by": user_id
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update invitation: {}", e))?;
+
+    if !claim_response.status().is_success() {
+        return Err(format!("Failed to update invitation: {}", claim_response.status()));
+    }
+
+    let claimed: Vec<Invitation> = claim_response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse updated invitation: {}", e))?;
+
+    if claimed.is_empty() {
+        return Err("This invitation has already been used".to_string());
+    }
+
+    let membership_data = json!({
+        "user_id": user_id,
+        "workspace_id": invitation.workspace_id,
+        "role": invitation.role,
+    });
+
+    let created: Vec<WorkspaceMemberRecord> = match db
+        .execute_query("workspace_members", "POST", Some(membership_data))
+        .await
+    {
+        Ok(response) => serde_json::from_value(response)
+            .map_err(|e| format!("Failed to parse created membership: {}", e))?,
+        // The membership will sync later, so the invitation stays claimed
+        Err(e @ DbError::Queued) => return Err(format!("Failed to create workspace membership: {}", e)),
+        Err(e) => {
+            // Release the invitation so the user can try again
+            let release_url = format!("{}/rest/v1/invitations?id=eq.{}", db.base_url, encode_filter_value(&invitation.id));
+            let _ = db.client
+                .patch(&release_url)
+                .header("apikey", &db.api_key)
+                .header("Authorization", format!("Bearer {}", db.api_key))
+                .header("Content-Type", "application/json")
+                .json(&json!({ "used_at": null, "used_by": null }))
+                .send()
+                .await;
+            return Err(format!("Failed to create workspace membership: {}", e));
+        }
+    };
+
+    created
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Invitation was accepted but no membership was created".to_string())
+}
+
+// ===== PROJECT COMMANDS =====
+
+// Row inserted into `projects`; the team is stored in the `workspace_id` column
+fn new_project_row(name: &str, workspace_id: &str, manager_id: &str, description: Option<&str>) -> serde_json::Value {
+    json!({
+        "id": generate_id(),
+        "name": name,
+        "workspace_id": workspace_id,
+        "manager_id": manager_id,
+        "description": description,
+        "created_at": now().to_rfc3339(),
+        "updated_at": now().to_rfc3339()
+    })
+}
+
+#[tauri::command]
+pub async fn create_project(
+    db: State<'_, Database>,
+    name: String,
+    team_id: String,
+    manager_id: String,
+    description: Option<String>,
+) -> Result<Project, String> {
+    let project_data = new_project_row(&name, &team_id, &manager_id, description.as_deref());
+
+    let response = db
+        .execute_query("projects", "POST", Some(project_data))
+        .await
+        .map_err(|e| format!("Failed to create project: {}", e))?;
+
+    // The response should be an array with the created record
+    let created_projects: Vec<Project> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse created project: {}", e))?;
+    
+    if let Some(created_project) = created_projects.into_iter().next() {
+        Ok(created_project)
+    } else {
+        Err("No project was created".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn get_projects_by_team(
+    db: State<'_, Database>,
+    team_id: String,
+) -> Result<Vec<Project>, String> {
+    let filter = format!("eq.{}", team_id);
+    let response = db
+        .execute_query_with_params("projects", "GET", &[("workspace_id", &filter)], None, None)
+        .await
         .map_err(|e| format!("Failed to fetch projects: {}", e))?;
 
-    let projects: Vec<Project> = response.json().await.map_err(|e| format!("Failed to parse projects: {}", e))?;
+    let projects: Vec<Project> = serde_json::from_value(response).map_err(|e| format!("Failed to parse projects: {}", e))?;
     Ok(projects)
 }
 
 #[tauri::command]
 pub async fn get_project(db: State<'_, Database>, project_id: String) -> Result<Option<Project>, String> {
-    let url = format!("{}/rest/v1/projects?id=eq.{}", db.base_url, project_id);
+    let url = format!("{}/rest/v1/projects?id=eq.{}", db.base_url, encode_filter_value(&project_id));
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -749,12 +1391,124 @@ pub async fn get_project(db: State<'_, Database>, project_id: String) -> Result<
         .await
         .map_err(|e| format!("Failed to fetch project: {}", e))?;
 
+    optional_row(response, "project").await
+}
+
+/// Task counts and tracked time for one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectProgress {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub task_stats: TaskStats,
+    pub total_tracked_hours: f64,
+}
+
+// Only the columns needed to total tracked time per task
+#[derive(Debug, Deserialize)]
+struct TaskTimeRow {
+    task_id: Option<String>,
+    start_time: chrono::DateTime<chrono::Utc>,
+    end_time: Option<chrono::DateTime<chrono::Utc>>,
+    duration_seconds: Option<i64>,
+}
+
+// Progress for each project, using one query for tasks and one for their time entries
+async fn project_progress_in_db(db: &Database, project_ids: &[String]) -> Result<Vec<ProjectProgress>, String> {
+    if project_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tasks_url = format!(
+        "{}/rest/v1/tasks?project_id=in.({})",
+        db.base_url,
+        project_ids.iter().map(|id| encode_filter_value(id)).collect::<Vec<_>>().join(",")
+    );
+    let response = db.client
+        .get(&tasks_url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
+
     if !response.status().is_success() {
-        return Ok(None);
+        return Err(format!("Failed to fetch tasks: {}", response.status()));
+    }
+    let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse tasks: {}", e))?;
+
+    let mut seconds_by_task: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+    if !tasks.is_empty() {
+        let task_ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        let entries_url = format!(
+            "{}/rest/v1/time_entries?task_id=in.({})&select=task_id,start_time,end_time,duration_seconds",
+            db.base_url,
+            task_ids.iter().map(|id| encode_filter_value(id)).collect::<Vec<_>>().join(",")
+        );
+        let response = db.client
+            .get(&entries_url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch time entries: {}", response.status()));
+        }
+        let rows: Vec<TaskTimeRow> = response.json().await.map_err(|e| format!("Failed to parse time entries: {}", e))?;
+
+        for row in rows {
+            let seconds = row
+                .duration_seconds
+                .unwrap_or_else(|| (row.end_time.unwrap_or_else(now) - row.start_time).num_seconds())
+                .max(0);
+            if let Some(task_id) = row.task_id {
+                *seconds_by_task.entry(task_id).or_insert(0) += seconds;
+            }
+        }
     }
 
-    let projects: Vec<Project> = response.json().await.map_err(|e| format!("Failed to parse project: {}", e))?;
-    Ok(projects.into_iter().next())
+    let progress = project_ids
+        .iter()
+        .map(|project_id| {
+            let project_tasks: Vec<Task> = tasks
+                .iter()
+                .filter(|t| t.project_id.as_deref() == Some(project_id.as_str()))
+                .cloned()
+                .collect();
+            let tracked_seconds: i64 = project_tasks
+                .iter()
+                .map(|t| seconds_by_task.get(&t.id).copied().unwrap_or(0))
+                .sum();
+
+            ProjectProgress {
+                project_id: project_id.clone(),
+                task_stats: calculate_task_stats(&project_tasks),
+                total_tracked_hours: tracked_seconds as f64 / 3600.0,
+            }
+        })
+        .collect();
+
+    Ok(progress)
+}
+
+#[tauri::command]
+pub async fn get_project_progress(db: State<'_, Database>, project_id: String) -> Result<ProjectProgress, String> {
+    project_progress_in_db(&db, std::slice::from_ref(&project_id))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No progress for project {}", project_id))
+}
+
+#[tauri::command]
+pub async fn get_all_project_progress(
+    db: State<'_, Database>,
+    team_id: String,
+) -> Result<Vec<ProjectProgress>, String> {
+    let projects = get_projects_by_team(db.clone(), team_id).await?;
+    let project_ids: Vec<String> = projects.into_iter().map(|p| p.id).collect();
+    project_progress_in_db(&db, &project_ids).await
 }
 
 #[tauri::command]
@@ -776,6 +1530,15 @@ pub async fn get_all_projects(db: State<'_, Database>) -> Result<Vec<Project>, S
     Ok(projects)
 }
 
+#[tauri::command]
+pub async fn get_all_projects_paged(
+    db: State<'_, Database>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Page<Project>, String> {
+    fetch_page(&db, "projects", &[("order", "name.asc")], limit, offset).await
+}
+
 // ===== TASK COMMANDS =====
 
 #[tauri::command]
@@ -788,32 +1551,92 @@ pub async fn create_task(
     status: Option<String>,
     priority: Option<String>,
     due_date: Option<String>,
+    validate_refs: Option<bool>,
 ) -> Result<Task, String> {
-    match status.as_deref().unwrap_or("todo") {
-        "todo" | "in_progress" | "done" => {},
-        _ => return Err("Invalid status. Must be 'todo', 'in_progress', or 'done'".to_string()),
+    create_task_in_db(
+        &db,
+        title,
+        workspace_id,
+        assigned_user_ids,
+        description,
+        status,
+        priority,
+        due_date,
+        validate_refs.unwrap_or(true),
+    )
+    .await
+}
+
+// The ids from `ids` that have no row in `table`
+async fn missing_ids(db: &Database, table: &str, ids: &[String]) -> Result<Vec<String>, String> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
     }
+    let id_filter = format!("in.({})", ids.join(","));
+    let response = db
+        .execute_query_with_params(table, "GET", &[("id", id_filter.as_str())], Some("id"), None)
+        .await
+        .map_err(|e| format!("Failed to look up {}: {}", table, e))?;
+    let rows: Vec<serde_json::Value> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse {}: {}", table, e))?;
+
+    Ok(ids
+        .iter()
+        .filter(|id| !rows.iter().any(|row| row["id"].as_str() == Some(id.as_str())))
+        .cloned()
+        .collect())
+}
 
-    if let Some(priority_val) = priority.as_deref() {
-        match priority_val {
-            "low" | "medium" | "high" => {},
-            _ => return Err("Invalid priority. Must be 'low', 'medium', or 'high'".to_string()),
+// Reject a task whose workspace or assignees don't exist, so typos don't leave orphans
+async fn check_task_refs(db: &Database, workspace_id: Option<&str>, assigned_user_ids: &[String]) -> Result<(), String> {
+    if let Some(workspace_id) = workspace_id {
+        if !missing_ids(db, "workspaces", &[workspace_id.to_string()]).await?.is_empty() {
+            return Err(format!("Workspace {} does not exist", workspace_id));
         }
     }
 
+    let missing_users = missing_ids(db, "users", assigned_user_ids).await?;
+    if !missing_users.is_empty() {
+        return Err(format!("Assigned users do not exist: {}", missing_users.join(", ")));
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_task_in_db(
+    db: &Database,
+    title: String,
+    workspace_id: Option<String>,
+    assigned_user_ids: Option<Vec<String>>,
+    description: Option<String>,
+    status: Option<String>,
+    priority: Option<String>,
+    due_date: Option<String>,
+    validate_refs: bool,
+) -> Result<Task, String> {
+    let status: TaskStatus = match status.as_deref() {
+        Some(status) => status.parse()?,
+        None => TaskStatus::Todo,
+    };
+    let priority: Option<TaskPriority> = priority.as_deref().map(str::parse).transpose()?;
+
+    if validate_refs {
+        check_task_refs(db, workspace_id.as_deref(), assigned_user_ids.as_deref().unwrap_or_default()).await?;
+    }
+
     let task_data = json!({
         "id": generate_id(),
         "title": title,
         "description": description,
         "workspace_id": workspace_id,
-        "status": status.unwrap_or("todo".to_string()),
+        "status": status,
         "priority": priority,
         "due_date": due_date,
         "created_at": now().to_rfc3339(),
         "updated_at": now().to_rfc3339()
     });
     
-    println!("create_task: Creating task with data: {}", task_data);
+    log::debug!("create_task: Creating task with data: {}", task_data);
 
     let response = db
         .execute_query("tasks", "POST", Some(task_data))
@@ -834,13 +1657,13 @@ pub async fn create_task(
                     "created_at": now().to_rfc3339()
                 });
                 
-                println!("create_task: Creating assignee with data: {}", assignee_data);
+                log::debug!("create_task: Creating assignee with data: {}", assignee_data);
                 
                 let _assignee_response = db
                     .execute_query("assignee", "POST", Some(assignee_data))
                     .await
                     .map_err(|e| {
-                        println!("Warning: Failed to create assignee: {}", e);
+                        log::warn!("Failed to create assignee: {}", e);
                         // Don't fail the entire task creation if assignee creation fails
                         e
                     });
@@ -859,7 +1682,7 @@ pub async fn get_all_assignees(
 ) -> Result<Vec<serde_json::Value>, String> {
     let url = format!("{}/rest/v1/assignee", db.base_url);
     
-    println!("get_all_assignees: Fetching from URL: {}", url);
+    log::debug!("get_all_assignees: Fetching from URL: {}", url);
     
     let response = db.client
         .get(&url)
@@ -869,7 +1692,7 @@ pub async fn get_all_assignees(
         .await
         .map_err(|e| format!("Failed to fetch assignees: {}", e))?;
 
-    println!("get_all_assignees: Response status: {}", response.status());
+    log::debug!("get_all_assignees: Response status: {}", response.status());
 
     if !response.status().is_success() {
         let status = response.status();
@@ -882,16 +1705,25 @@ pub async fn get_all_assignees(
         .await
         .map_err(|e| format!("Failed to parse assignees: {}", e))?;
 
-    println!("get_all_assignees: Found {} assignee records", assignees.len());
+    log::debug!("get_all_assignees: Found {} assignee records", assignees.len());
     
     Ok(assignees)
 }
 
+// Query-string filter that hides archived tasks unless they were asked for
+fn archived_filter(include_archived: Option<bool>) -> &'static str {
+    if include_archived.unwrap_or(false) {
+        ""
+    } else {
+        "archived_at=is.null"
+    }
+}
+
 #[tauri::command]
-pub async fn get_all_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String> {
-    let url = format!("{}/rest/v1/tasks", db.base_url);
+pub async fn get_all_tasks(db: State<'_, Database>, include_archived: Option<bool>) -> Result<Vec<Task>, String> {
+    let url = format!("{}/rest/v1/tasks?{}", db.base_url, archived_filter(include_archived));
     
-    println!("get_all_tasks: Fetching from URL: {}", url);
+    log::debug!("get_all_tasks: Fetching from URL: {}", url);
     
     let response = db.client
         .get(&url)
@@ -901,7 +1733,7 @@ pub async fn get_all_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String>
         .await
         .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
 
-    println!("get_all_tasks: Response status: {}", response.status());
+    log::debug!("get_all_tasks: Response status: {}", response.status());
 
     if !response.status().is_success() {
         let status = response.status();
@@ -914,18 +1746,18 @@ pub async fn get_all_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String>
         .await
         .map_err(|e| format!("Failed to parse tasks: {}", e))?;
 
-    println!("get_all_tasks: Found {} task records", tasks.len());
+    log::debug!("get_all_tasks: Found {} task records", tasks.len());
     
     Ok(tasks)
 }
 
 #[tauri::command]
 pub async fn get_task_assignees(task_id: String, db: State<'_, Database>) -> Result<Vec<serde_json::Value>, String> {
-    println!("get_task_assignees: Fetching assignees for task_id: {}", task_id);
+    log::debug!("get_task_assignees: Fetching assignees for task_id: {}", task_id);
     
-    let url = format!("{}/rest/v1/assignee?task_id=eq.{}", db.base_url, task_id);
+    let url = format!("{}/rest/v1/assignee?task_id=eq.{}", db.base_url, encode_filter_value(&task_id));
     
-    println!("get_task_assignees: Fetching from URL: {}", url);
+    log::debug!("get_task_assignees: Fetching from URL: {}", url);
     
     let response = db.client
         .get(&url)
@@ -935,7 +1767,7 @@ pub async fn get_task_assignees(task_id: String, db: State<'_, Database>) -> Res
         .await
         .map_err(|e| format!("Failed to fetch task assignees: {}", e))?;
 
-    println!("get_task_assignees: Response status: {}", response.status());
+    log::debug!("get_task_assignees: Response status: {}", response.status());
 
     if !response.status().is_success() {
         let status = response.status();
@@ -948,7 +1780,7 @@ pub async fn get_task_assignees(task_id: String, db: State<'_, Database>) -> Res
         .await
         .map_err(|e| format!("Failed to parse task assignees: {}", e))?;
 
-    println!("get_task_assignees: Found {} assignees for task {}", assignees.len(), task_id);
+    log::debug!("get_task_assignees: Found {} assignees for task {}", assignees.len(), task_id);
     
     Ok(assignees)
 }
@@ -957,17 +1789,19 @@ pub async fn get_task_assignees(task_id: String, db: State<'_, Database>) -> Res
 pub async fn get_tasks_by_project(
     db: State<'_, Database>,
     project_id: String,
+    include_archived: Option<bool>,
 ) -> Result<Vec<Task>, String> {
-    let url = format!("{}/rest/v1/tasks?project_id=eq.{}", db.base_url, project_id);
-    let response = db.client
-        .get(&url)
-        .header("apikey", &db.api_key)
-        .header("Authorization", format!("Bearer {}", db.api_key))
-        .send()
+    let filter = format!("eq.{}", project_id);
+    let mut filters = vec![("project_id", filter.as_str())];
+    if !include_archived.unwrap_or(false) {
+        filters.push(("archived_at", "is.null"));
+    }
+    let response = db
+        .execute_query_with_params("tasks", "GET", &filters, None, None)
         .await
         .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
 
-    let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse tasks: {}", e))?;
+    let tasks: Vec<Task> = serde_json::from_value(response).map_err(|e| format!("Failed to parse tasks: {}", e))?;
     Ok(tasks)
 }
 
@@ -975,9 +1809,15 @@ pub async fn get_tasks_by_project(
 pub async fn get_tasks_by_workspace(
     db: State<'_, Database>,
     workspace_id: String,
+    include_archived: Option<bool>,
 ) -> Result<Vec<Task>, String> {
-    let url = format!("{}/rest/v1/tasks?workspace_id=eq.{}", db.base_url, workspace_id);
-    println!("get_tasks_by_workspace: Getting tasks for workspace {} from URL: {}", workspace_id, url);
+    let url = format!(
+        "{}/rest/v1/tasks?workspace_id=eq.{}&{}",
+        db.base_url,
+        encode_filter_value(&workspace_id),
+        archived_filter(include_archived)
+    );
+    log::debug!("get_tasks_by_workspace: Getting tasks for workspace {} from URL: {}", workspace_id, url);
     
     let response = db.client
         .get(&url)
@@ -987,10 +1827,10 @@ pub async fn get_tasks_by_workspace(
         .await
         .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
 
-    println!("get_tasks_by_workspace: Response status: {}", response.status());
+    log::debug!("get_tasks_by_workspace: Response status: {}", response.status());
 
     let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse tasks: {}", e))?;
-    println!("get_tasks_by_workspace: Found {} tasks for workspace {}", tasks.len(), workspace_id);
+    log::debug!("get_tasks_by_workspace: Found {} tasks for workspace {}", tasks.len(), workspace_id);
     
     Ok(tasks)
 }
@@ -999,9 +1839,15 @@ pub async fn get_tasks_by_workspace(
 pub async fn get_tasks_by_assignee(
     db: State<'_, Database>,
     assignee_id: String,
+    include_archived: Option<bool>,
 ) -> Result<Vec<Task>, String> {
-    let url = format!("{}/rest/v1/tasks?assignee_id=eq.{}", db.base_url, assignee_id);
-    println!("get_tasks_by_assignee: URL: {}", url);
+    let url = format!(
+        "{}/rest/v1/tasks?assignee_id=eq.{}&{}",
+        db.base_url,
+        encode_filter_value(&assignee_id),
+        archived_filter(include_archived)
+    );
+    log::debug!("get_tasks_by_assignee: URL: {}", url);
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -1010,17 +1856,117 @@ pub async fn get_tasks_by_assignee(
         .await
         .map_err(|e| format!("Failed to fetch tasks: {}", e))?;
 
-    println!("get_tasks_by_assignee: Response status: {}", response.status());
+    log::debug!("get_tasks_by_assignee: Response status: {}", response.status());
 
     let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse tasks: {}", e))?;
-    println!("get_tasks_by_assignee: Found {} tasks", tasks.len());
-    for task in &tasks {
-        println!("  Task: {} - {} - assignee: {:?}", task.id, task.title, task.assignee_id);
+    log::debug!("get_tasks_by_assignee: Found {} tasks", tasks.len());
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_task(db: State<'_, Database>, task_id: String) -> Result<Option<Task>, String> {
+    fetch_task_by_id(&db, &task_id).await
+}
+
+async fn fetch_task_by_id(db: &Database, task_id: &str) -> Result<Option<Task>, String> {
+    let url = format!("{}/rest/v1/tasks?id=eq.{}", db.base_url, encode_filter_value(task_id));
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch task: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch task: {} - {}", status, error_text));
+    }
+
+    let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse task: {}", e))?;
+    Ok(tasks.into_iter().next())
+}
+
+#[tauri::command]
+pub async fn search_tasks(
+    db: State<'_, Database>,
+    project_id: Option<String>,
+    status: Option<String>,
+    priority: Option<String>,
+    due_before: Option<String>,
+    include_archived: Option<bool>,
+) -> Result<Vec<Task>, String> {
+    let status: Option<TaskStatus> = status.as_deref().map(str::parse).transpose()?;
+    let priority: Option<TaskPriority> = priority.as_deref().map(str::parse).transpose()?;
+
+    // Only add the filters that were actually supplied
+    let mut filters: Vec<String> = Vec::new();
+    if let Some(project_id) = project_id {
+        filters.push(format!("project_id=eq.{}", encode_filter_value(&project_id)));
+    }
+    if let Some(status) = status {
+        filters.push(format!("status=eq.{}", status));
+    }
+    if let Some(priority) = priority {
+        filters.push(format!("priority=eq.{}", priority));
+    }
+    if let Some(due_before) = due_before {
+        let due_before = chrono::DateTime::parse_from_rfc3339(&due_before)
+            .map_err(|e| format!("Invalid due_before date: {}", e))?
+            .with_timezone(&chrono::Utc);
+        filters.push(format!("due_date=lt.{}", due_before.format("%Y-%m-%dT%H:%M:%SZ")));
     }
+    if !include_archived.unwrap_or(false) {
+        filters.push(archived_filter(None).to_string());
+    }
+    filters.push("order=due_date.asc.nullslast".to_string());
+
+    let url = format!("{}/rest/v1/tasks?{}", db.base_url, filters.join("&"));
+    log::debug!("search_tasks: URL: {}", url);
+
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to search tasks: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to search tasks: {} - {}", status, error_text));
+    }
+
+    let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse tasks: {}", e))?;
+    log::debug!("search_tasks: Found {} tasks", tasks.len());
     Ok(tasks)
 }
 
+// Build the optimistic-concurrency filter for a PATCH. The timestamp is normalised
+// to UTC with a Z suffix so it survives in the query string without encoding.
+fn updated_at_filter(expected_updated_at: Option<&str>) -> Result<String, String> {
+    match expected_updated_at {
+        Some(expected) => {
+            let expected = chrono::DateTime::parse_from_rfc3339(expected)
+                .map_err(|e| format!("Invalid expected_updated_at: {}", e))?
+                .with_timezone(&chrono::Utc);
+            Ok(format!(
+                "&updated_at=eq.{}",
+                encode_filter_value(&expected.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+            ))
+        }
+        None => Ok(String::new()),
+    }
+}
+
+fn conflict_error(kind: &str) -> String {
+    format!("Conflict: the {} was changed by someone else. Reload and try again.", kind)
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_task(
     db: State<'_, Database>,
     task_id: String,
@@ -1030,7 +1976,10 @@ pub async fn update_task(
     status: Option<String>,
     priority: Option<String>,
     due_date: Option<String>,
+    expected_updated_at: Option<String>,
 ) -> Result<Task, String> {
+    let version_filter = updated_at_filter(expected_updated_at.as_deref())?;
+
     let mut update_data = json!({
         "updated_at": now().to_rfc3339()
     });
@@ -1045,16 +1994,16 @@ pub async fn update_task(
         update_data["assignee_id"] = json!(assignee_id);
     }
     if let Some(status) = status {
-        update_data["status"] = json!(status);
+        update_data["status"] = json!(status.parse::<TaskStatus>()?);
     }
     if let Some(priority) = priority {
-        update_data["priority"] = json!(priority);
+        update_data["priority"] = json!(priority.parse::<TaskPriority>()?);
     }
     if let Some(due_date) = due_date {
         update_data["due_date"] = json!(due_date);
     }
 
-    let url = format!("{}/rest/v1/tasks?id=eq.{}", db.base_url, task_id);
+    let url = format!("{}/rest/v1/tasks?id=eq.{}{}", db.base_url, encode_filter_value(&task_id), version_filter);
     let response = db.client
         .patch(&url)
         .header("apikey", &db.api_key)
@@ -1071,33 +2020,498 @@ pub async fn update_task(
     
     if let Some(updated_task) = updated_tasks.into_iter().next() {
         Ok(updated_task)
+    } else if !version_filter.is_empty() {
+        Err(conflict_error("task"))
     } else {
         Err("No task was updated".to_string())
     }
 }
 
-#[tauri::command]
-pub async fn delete_task(db: State<'_, Database>, taskId: String) -> Result<(), String> {
-    println!("Delete task command called with taskId: {}", taskId);
-    
-    let url = format!("{}/rest/v1/tasks?id=eq.{}", db.base_url, taskId);
-    println!("Delete task URL: {}", url);
-    
-    let response = db.client
-        .delete(&url)
-        .header("apikey", &db.api_key)
-        .header("Authorization", format!("Bearer {}", db.api_key))
-        .send()
+// Read the affected row count out of a PostgREST Content-Range header
+// ("0-4/5", "*/5", or "*/*" when the count is unknown).
+fn parse_content_range_count(content_range: &str) -> Option<usize> {
+    let (range, total) = content_range.trim().split_once('/')?;
+    if let Ok(count) = total.parse::<usize>() {
+        return Some(count);
+    }
+
+    let (start, end) = range.split_once('-')?;
+    let start = start.parse::<usize>().ok()?;
+    let end = end.parse::<usize>().ok()?;
+    Some(end.saturating_sub(start) + 1)
+}
+
+async fn reassign_tasks_in_db(
+    db: &Database,
+    from_assignee: &str,
+    to_assignee: &str,
+    project_id: Option<&str>,
+) -> Result<usize, String> {
+    // Completed tasks keep the assignee who did them
+    let mut url = format!("{}/rest/v1/tasks?assignee_id=eq.{}&status=neq.done", db.base_url, encode_filter_value(&from_assignee));
+    if let Some(project_id) = project_id {
+        url.push_str(&format!("&project_id=eq.{}", encode_filter_value(&project_id)));
+    }
+
+    let response = db.client
+        .patch(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "return=minimal, count=exact")
+        .json(&json!({
+            "assignee_id": to_assignee,
+            "updated_at": now().to_rfc3339()
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reassign tasks: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to reassign tasks: {} - {}", status, error_text));
+    }
+
+    let count = response
+        .headers()
+        .get("content-range")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range_count)
+        .unwrap_or(0);
+
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn reassign_tasks(
+    db: State<'_, Database>,
+    from_assignee: String,
+    to_assignee: String,
+    project_id: Option<String>,
+) -> Result<usize, String> {
+    if from_assignee.trim().is_empty() || to_assignee.trim().is_empty() {
+        return Err("Both from_assignee and to_assignee are required".to_string());
+    }
+
+    let count = reassign_tasks_in_db(&db, &from_assignee, &to_assignee, project_id.as_deref()).await?;
+    log::info!("reassign_tasks: Moved {} tasks from {} to {}", count, from_assignee, to_assignee);
+    Ok(count)
+}
+
+/// Open tasks for the current user that are past due or due shortly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingTasks {
+    pub overdue: Vec<Task>,
+    pub due_soon: Vec<Task>,
+}
+
+const DUE_REMINDER_INTERVAL_SECS: u64 = 15 * 60;
+const DUE_REMINDER_WINDOW_HOURS: i64 = 24;
+
+static DUE_REMINDERS_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Today's usage of an app that has a daily limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppLimitStatus {
+    pub app_id: String,
+    pub app_name: String,
+    pub minutes_used: i64,
+    pub limit: i64,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AppLimitRow {
+    app_id: String,
+    daily_minutes: i64,
+}
+
+const APP_LIMIT_CHECK_INTERVAL_SECS: u64 = 60;
+
+static APP_LIMIT_MONITOR_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+// How often the snapshot loop checks whether one is due; the interval itself is a tracking setting
+const USAGE_SNAPSHOT_CHECK_INTERVAL_SECS: u64 = 60;
+
+static USAGE_SNAPSHOTS_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+async fn fetch_upcoming_tasks(db: &Database, user_id: &str, within_hours: i64) -> Result<UpcomingTasks, String> {
+    let current = now();
+    let horizon = current + chrono::Duration::hours(within_hours);
+    let url = format!(
+        "{}/rest/v1/tasks?assignee_id=eq.{}&status=neq.done&archived_at=is.null&due_date=lte.{}&order=due_date.asc",
+        db.base_url,
+        encode_filter_value(&user_id),
+        horizon.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch upcoming tasks: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch upcoming tasks: {} - {}", status, error_text));
+    }
+
+    let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse upcoming tasks: {}", e))?;
+    let (overdue, due_soon) = tasks
+        .into_iter()
+        .filter(|task| task.due_date.is_some())
+        .partition(|task| task.due_date.is_some_and(|due| due < current));
+
+    Ok(UpcomingTasks { overdue, due_soon })
+}
+
+#[tauri::command]
+pub async fn get_upcoming_tasks(db: State<'_, Database>, within_hours: i64) -> Result<UpcomingTasks, String> {
+    if within_hours < 0 {
+        return Err("within_hours must not be negative".to_string());
+    }
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    fetch_upcoming_tasks(&db, &user_id, within_hours).await
+}
+
+// Periodically emit `task-due-soon` once for each task that becomes due within the reminder window
+fn start_due_task_reminders(app_handle: tauri::AppHandle, db: Database) {
+    use tauri::Emitter;
+
+    if DUE_REMINDERS_STARTED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut notified: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(DUE_REMINDER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            let user_id = match crate::current_user::get_current_user_id() {
+                Some(user_id) => user_id,
+                None => continue,
+            };
+
+            match fetch_upcoming_tasks(&db, &user_id, DUE_REMINDER_WINDOW_HOURS).await {
+                Ok(upcoming) => {
+                    for task in upcoming.due_soon {
+                        if notified.insert(task.id.clone()) {
+                            let _ = app_handle.emit("task-due-soon", &task);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Due task check failed: {}", e),
+            }
+        }
+    });
+}
+
+// ===== APP DAILY LIMITS =====
+
+async fn fetch_app_limits(db: &Database, user_id: &str) -> Result<Vec<AppLimitRow>, String> {
+    let url = format!(
+        "{}/rest/v1/app_limits?user_id=eq.{}&select=app_id,daily_minutes",
+        db.base_url,
+        encode_filter_value(user_id)
+    );
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch app limits: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch app limits: {} - {}", status, error_text));
+    }
+
+    response.json().await.map_err(|e| format!("Failed to parse app limits: {}", e))
+}
+
+// Minutes each app was used within [start, end), counting running entries up to `end`
+fn minutes_used_by_app(
+    entries: &[TimeEntry],
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> HashMap<String, i64> {
+    let mut seconds: HashMap<String, i64> = HashMap::new();
+    for entry in entries {
+        let Some(app_id) = &entry.app_id else { continue };
+        let entry_start = entry.start_time.max(start);
+        let entry_end = entry.end_time.unwrap_or(end).min(end);
+        if entry_end > entry_start {
+            *seconds.entry(app_id.clone()).or_insert(0) += (entry_end - entry_start).num_seconds();
+        }
+    }
+    seconds.into_iter().map(|(app_id, secs)| (app_id, secs / 60)).collect()
+}
+
+// Today's status for every limited app, plus the ids of apps with a running entry
+async fn app_limit_status_in_db(
+    db: &Database,
+    user_id: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(Vec<AppLimitStatus>, std::collections::HashSet<String>), String> {
+    let limits = fetch_app_limits(db, user_id).await?;
+    if limits.is_empty() {
+        return Ok((Vec::new(), std::collections::HashSet::new()));
+    }
+
+    let tz = ReportTimezone::Local;
+    let day_start = tz.start_of_day(tz.to_local(now).date());
+    let entries = fetch_time_entries_in_range(db, user_id, day_start, now).await?;
+    let used = minutes_used_by_app(&entries, day_start, now);
+    let running = entries
+        .iter()
+        .filter(|entry| entry.end_time.is_none())
+        .filter_map(|entry| entry.app_id.clone())
+        .collect();
+
+    let app_filter = format!(
+        "in.({})",
+        limits.iter().map(|limit| limit.app_id.as_str()).collect::<Vec<_>>().join(",")
+    );
+    let response = db
+        .execute_query_with_params("applications", "GET", &[("id", app_filter.as_str())], None, None)
+        .await
+        .map_err(|e| format!("Failed to fetch applications: {}", e))?;
+    let apps: Vec<Application> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse applications: {}", e))?;
+    let mut statuses: Vec<AppLimitStatus> = limits
+        .into_iter()
+        .map(|limit| {
+            let minutes_used = used.get(&limit.app_id).copied().unwrap_or(0);
+            let app_name = apps
+                .iter()
+                .find(|app| app.id == limit.app_id)
+                .map(|app| app.name.clone())
+                .unwrap_or_else(|| "Unknown App".to_string());
+            AppLimitStatus {
+                app_name,
+                minutes_used,
+                limit: limit.daily_minutes,
+                exceeded: minutes_used >= limit.daily_minutes,
+                app_id: limit.app_id,
+            }
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.app_name.to_lowercase().cmp(&b.app_name.to_lowercase()));
+
+    Ok((statuses, running))
+}
+
+/// Set a daily limit in minutes for one of the current user's apps; None or 0 removes it
+#[tauri::command]
+pub async fn set_app_limit(
+    db: State<'_, Database>,
+    app_id: String,
+    daily_minutes: Option<u32>,
+) -> Result<(), String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+
+    let app = crate::platform::database_helpers::DatabaseHelpers::get_application(&db, &app_id)
+        .await?
+        .filter(|app| app.user_id.as_deref() == Some(user_id.as_str()))
+        .ok_or_else(|| "Application not found".to_string())?;
+
+    let daily_minutes = daily_minutes.filter(|minutes| *minutes > 0);
+    let Some(daily_minutes) = daily_minutes else {
+        let url = format!(
+            "{}/rest/v1/app_limits?user_id=eq.{}&app_id=eq.{}",
+            db.base_url,
+            encode_filter_value(&user_id),
+            encode_filter_value(&app.id)
+        );
+        let response = db.client
+            .delete(&url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to remove app limit: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to remove app limit: {}", response.status()));
+        }
+        log::info!("Removed daily limit for {}", app.name);
+        return Ok(());
+    };
+
+    let url = format!("{}/rest/v1/app_limits?on_conflict=user_id,app_id", db.base_url);
+    let response = db.client
+        .post(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "resolution=merge-duplicates,return=minimal")
+        .json(&json!({
+            "user_id": user_id,
+            "app_id": app.id,
+            "daily_minutes": daily_minutes,
+            "updated_at": now().to_rfc3339()
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to set app limit: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to set app limit: {} - {}", status, error_text));
+    }
+
+    log::info!("Set daily limit for {} to {} minutes", app.name, daily_minutes);
+    Ok(())
+}
+
+/// Minutes used today against each limited app's daily limit
+#[tauri::command]
+pub async fn get_app_limit_status(db: State<'_, Database>) -> Result<Vec<AppLimitStatus>, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let (statuses, _) = app_limit_status_in_db(&db, &user_id, now()).await?;
+    Ok(statuses)
+}
+
+// Periodically emit `app-limit-exceeded` once per day for a running app that goes over its limit
+fn start_app_limit_monitor(app_handle: tauri::AppHandle, db: Database) {
+    use tauri::Emitter;
+
+    if APP_LIMIT_MONITOR_STARTED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut notified: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut notified_day = chrono::Local::now().date_naive();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(APP_LIMIT_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            let today = chrono::Local::now().date_naive();
+            if today != notified_day {
+                notified.clear();
+                notified_day = today;
+            }
+
+            let user_id = match crate::current_user::get_current_user_id() {
+                Some(user_id) => user_id,
+                None => continue,
+            };
+
+            match app_limit_status_in_db(&db, &user_id, now()).await {
+                Ok((statuses, running)) => {
+                    for status in statuses {
+                        if status.exceeded && running.contains(&status.app_id) && notified.insert(status.app_id.clone()) {
+                            log::info!("{} is over its daily limit ({} of {} minutes)", status.app_name, status.minutes_used, status.limit);
+                            let _ = app_handle.emit("app-limit-exceeded", &status);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("App limit check failed: {}", e),
+            }
+        }
+    });
+}
+
+// While tracking, emit a `usage-snapshot` with today's totals every configured number of seconds
+fn start_usage_snapshots(app_handle: tauri::AppHandle, db: Database) {
+    use tauri::Emitter;
+
+    if USAGE_SNAPSHOTS_STARTED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut last_sent: Option<chrono::DateTime<chrono::Utc>> = None;
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(USAGE_SNAPSHOT_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            let Some(seconds) = crate::tracking_settings::usage_snapshot_seconds() else { continue };
+            let at = now();
+            if last_sent.is_some_and(|sent| at - sent < chrono::Duration::seconds(seconds as i64)) {
+                continue;
+            }
+
+            let tracking = match crate::tracking::get_tracker() {
+                Some(tracker) => tracker.is_tracking().await,
+                None => false,
+            };
+            let user_id = match crate::current_user::get_current_user_id() {
+                Some(user_id) if tracking => user_id,
+                _ => continue,
+            };
+
+            match ai_assistant::usage_snapshot_in_db(&db, &user_id, at).await {
+                Ok(snapshot) => {
+                    last_sent = Some(at);
+                    let _ = app_handle.emit("usage-snapshot", &snapshot);
+                }
+                Err(e) => log::warn!("Usage snapshot failed: {}", e),
+            }
+        }
+    });
+}
+
+// Set or clear a task's archived_at. Archived tasks keep their time entries and history
+async fn set_task_archived_in_db(db: &Database, task_id: &str, archived: bool) -> Result<Task, String> {
+    let filter = format!("eq.{}", task_id);
+    let archived_at = if archived { json!(now().to_rfc3339()) } else { serde_json::Value::Null };
+    let response = db
+        .execute_query_with_params(
+            "tasks",
+            "PATCH",
+            &[("id", filter.as_str())],
+            None,
+            Some(json!({ "archived_at": archived_at, "updated_at": now().to_rfc3339() })),
+        )
+        .await
+        .map_err(|e| format!("Failed to update task: {}", e))?;
+
+    let tasks: Vec<Task> = serde_json::from_value(response).map_err(|e| format!("Failed to parse task: {}", e))?;
+    tasks.into_iter().next().ok_or_else(|| "Task not found".to_string())
+}
+
+#[tauri::command]
+pub async fn archive_task(db: State<'_, Database>, task_id: String) -> Result<Task, String> {
+    set_task_archived_in_db(&db, &task_id, true).await
+}
+
+#[tauri::command]
+pub async fn unarchive_task(db: State<'_, Database>, task_id: String) -> Result<Task, String> {
+    set_task_archived_in_db(&db, &task_id, false).await
+}
+
+// Permanent: the task's time entries lose their link to it. Prefer archive_task
+#[tauri::command]
+pub async fn delete_task(db: State<'_, Database>, task_id: String) -> Result<(), String> {
+    log::debug!("Delete task command called with task_id: {}", task_id);
+    
+    let url = format!("{}/rest/v1/tasks?id=eq.{}", db.base_url, encode_filter_value(&task_id));
+    log::debug!("Delete task URL: {}", url);
+    
+    let response = db.client
+        .delete(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
         .await
         .map_err(|e| format!("Failed to delete task: {}", e))?;
 
-    println!("Delete task response status: {}", response.status());
+    log::debug!("Delete task response status: {}", response.status());
 
     if !response.status().is_success() {
         return Err(format!("Failed to delete task: {}", response.status()));
     }
 
-    println!("Task deleted successfully");
+    log::info!("Task deleted successfully");
     Ok(())
 }
 
@@ -1143,8 +2557,15 @@ pub async fn create_application(
 pub async fn get_applications_by_user(
     db: State<'_, Database>,
     user_id: String,
+    order_by: Option<String>,
 ) -> Result<Vec<Application>, String> {
-    let url = format!("{}/rest/v1/applications?user_id=eq.{}", db.base_url, user_id);
+    let order = match order_by.as_deref().map(str::trim) {
+        None | Some("") => "",
+        Some("last_used") => "&order=last_used.desc.nullslast",
+        Some("name") => "&order=name.asc",
+        Some(other) => return Err(format!("Invalid order_by '{}'. Must be 'last_used' or 'name'", other)),
+    };
+    let url = format!("{}/rest/v1/applications?user_id=eq.{}{}", db.base_url, encode_filter_value(&user_id), order);
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -1157,7 +2578,14 @@ pub async fn get_applications_by_user(
     Ok(applications)
 }
 
+/// Fetch the applications with the given ids in one request
+#[tauri::command]
+pub async fn get_applications_by_ids(db: State<'_, Database>, ids: Vec<String>) -> Result<Vec<Application>, String> {
+    crate::platform::database_helpers::DatabaseHelpers::get_applications_by_ids(&db, &ids).await
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_application(
     db: State<'_, Database>,
     app_id: String,
@@ -1166,7 +2594,10 @@ pub async fn update_application(
     icon_path: Option<String>,
     category: Option<String>,
     is_tracked: Option<bool>,
+    expected_updated_at: Option<String>,
 ) -> Result<Application, String> {
+    let version_filter = updated_at_filter(expected_updated_at.as_deref())?;
+
     let mut update_data = json!({
         "updated_at": now().to_rfc3339()
     });
@@ -1187,15 +2618,15 @@ pub async fn update_application(
     // even if it's false, because Tauri might serialize false as None
     if is_tracked.is_some() {
         let tracked_value = is_tracked.unwrap();
-        println!("DEBUG: Setting is_tracked to: {}", tracked_value);
+        log::debug!("Setting is_tracked to: {}", tracked_value);
         update_data["is_tracked"] = json!(tracked_value);
     } else {
-        println!("DEBUG: is_tracked is None, not updating this field");
+        log::debug!("is_tracked is None, not updating this field");
     }
 
-    println!("DEBUG: Update data being sent: {}", serde_json::to_string_pretty(&update_data).unwrap_or_else(|_| "Failed to serialize".to_string()));
+    log::debug!("Update data being sent: {}", serde_json::to_string_pretty(&update_data).unwrap_or_else(|_| "Failed to serialize".to_string()));
     
-    let url = format!("{}/rest/v1/applications?id=eq.{}", db.base_url, app_id);
+    let url = format!("{}/rest/v1/applications?id=eq.{}{}", db.base_url, encode_filter_value(&app_id), version_filter);
     let response = db.client
         .patch(&url)
         .header("apikey", &db.api_key)
@@ -1211,15 +2642,85 @@ pub async fn update_application(
     let updated_apps: Vec<Application> = response.json().await.map_err(|e| format!("Failed to parse updated application: {}", e))?;
     
     if let Some(updated_app) = updated_apps.into_iter().next() {
-        println!("DEBUG: Updated app from database: {:?}", updated_app);
+        log::debug!("Updated app from database: {:?}", updated_app);
         Ok(updated_app)
+    } else if !version_filter.is_empty() {
+        Err(conflict_error("application"))
     } else {
         Err("No application was updated".to_string())
     }
 }
 
+/// Process identifier an application goes by on one platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessAlias {
+    pub app_id: String,
+    pub platform: String,
+    pub process_name: String,
+}
+
+/// Record the process name one of the current user's apps uses on a platform,
+/// e.g. `com.microsoft.VSCode` on macOS for an app set up as `Code.exe`
+#[tauri::command]
+pub async fn add_process_alias(
+    db: State<'_, Database>,
+    app_id: String,
+    platform: String,
+    process_name: String,
+) -> Result<ProcessAlias, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+
+    let platform = platform.trim().to_lowercase();
+    if !["windows", "macos", "linux"].contains(&platform.as_str()) {
+        return Err(format!("Invalid platform '{}'. Must be 'windows', 'macos' or 'linux'", platform));
+    }
+    let process_name = process_name.trim();
+    if process_name.is_empty() {
+        return Err("Process name is required".to_string());
+    }
+
+    let app = crate::platform::database_helpers::DatabaseHelpers::get_application(&db, &app_id)
+        .await?
+        .filter(|app| app.user_id.as_deref() == Some(user_id.as_str()))
+        .ok_or_else(|| "Application not found".to_string())?;
+
+    let url = format!("{}/rest/v1/process_aliases?on_conflict=app_id,platform", db.base_url);
+    let response = db.client
+        .post(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "resolution=merge-duplicates,return=minimal")
+        .json(&json!({
+            "app_id": app.id,
+            "user_id": user_id,
+            "platform": platform,
+            "process_name": process_name,
+            "updated_at": now().to_rfc3339()
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to save process alias: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to save process alias: {} - {}", status, error_text));
+    }
+
+    log::info!("{} is '{}' on {}", app.name, process_name, platform);
+    Ok(ProcessAlias {
+        app_id: app.id,
+        platform,
+        process_name: process_name.to_string(),
+    })
+}
+
 // ===== TIME ENTRY COMMANDS =====
 
+// Allowed difference between a supplied duration and the start/end interval
+const DURATION_TOLERANCE_SECONDS: i64 = 1;
+
 #[tauri::command]
 pub async fn create_time_entry(
     db: State<'_, Database>,
@@ -1231,29 +2732,107 @@ pub async fn create_time_entry(
     duration_seconds: Option<i64>,
     is_active: Option<bool>,
 ) -> Result<TimeEntry, String> {
-    let time_entry_data = json!({
-        "id": generate_id(),
-        "user_id": user_id,
-        "app_id": app_id,
-        "task_id": task_id,
-        "start_time": start_time,
-        "end_time": end_time,
-        "duration_seconds": duration_seconds,
-        "is_active": is_active.unwrap_or(false),
-        "created_at": now().to_rfc3339(),
-        "updated_at": now().to_rfc3339()
-    });
-
-    let response = db
-        .execute_query("time_entries", "POST", Some(time_entry_data))
-        .await
-        .map_err(|e| format!("Failed to create time entry: {}", e))?;
+    let start = chrono::DateTime::parse_from_rfc3339(&start_time)
+        .map_err(|e| format!("Invalid start_time: {}", e))?
+        .with_timezone(&chrono::Utc);
+    let end = match end_time.as_deref() {
+        Some(end_str) => Some(
+            chrono::DateTime::parse_from_rfc3339(end_str)
+                .map_err(|e| format!("Invalid end_time: {}", e))?
+                .with_timezone(&chrono::Utc),
+        ),
+        None => None,
+    };
 
-    // The response should be an array with the created record
-    let created_entries: Vec<TimeEntry> = serde_json::from_value(response)
+    let duration_seconds = match end {
+        Some(end) => {
+            if end < start {
+                return Err("end_time must not be before start_time".to_string());
+            }
+            let interval_seconds = (end - start).num_seconds();
+            if let Some(supplied) = duration_seconds {
+                if (supplied - interval_seconds).abs() > DURATION_TOLERANCE_SECONDS {
+                    return Err(format!(
+                        "duration_seconds ({}) does not match the interval between start_time and end_time ({} seconds)",
+                        supplied, interval_seconds
+                    ));
+                }
+            }
+            Some(interval_seconds)
+        }
+        None => {
+            if duration_seconds.is_some() {
+                return Err("duration_seconds requires an end_time".to_string());
+            }
+            None
+        }
+    };
+
+    // Overlapping entries for the same app would double-count time in reports
+    let mut overlap_url = format!(
+        "{}/rest/v1/time_entries?user_id=eq.{}&or=(end_time.is.null,end_time.gt.{})&select=id&limit=1",
+        db.base_url,
+        encode_filter_value(&user_id),
+        start.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    match &app_id {
+        Some(app_id) => overlap_url.push_str(&format!("&app_id=eq.{}", encode_filter_value(app_id))),
+        None => overlap_url.push_str("&app_id=is.null"),
+    }
+    if let Some(end) = end {
+        overlap_url.push_str(&format!("&start_time=lt.{}", end.format("%Y-%m-%dT%H:%M:%SZ")));
+    }
+
+    let overlap_response = db.client
+        .get(&overlap_url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to check for overlapping time entries: {}", e))?;
+
+    if !overlap_response.status().is_success() {
+        let status = overlap_response.status();
+        let error_text = overlap_response.text().await.unwrap_or_default();
+        return Err(format!("Failed to check for overlapping time entries: {} - {}", status, error_text));
+    }
+    let overlapping: Vec<serde_json::Value> = overlap_response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse overlapping time entries: {}", e))?;
+    if !overlapping.is_empty() {
+        return Err("This time entry overlaps an existing entry for the same app".to_string());
+    }
+
+    let workspace_id = crate::platform::database_helpers::DatabaseHelpers::current_workspace_id(&db, &user_id).await;
+    let time_entry_data = json!({
+        "id": generate_id(),
+        "user_id": user_id,
+        "app_id": app_id,
+        "task_id": task_id,
+        "workspace_id": workspace_id,
+        "start_time": start.to_rfc3339(),
+        "end_time": end.map(|end| end.to_rfc3339()),
+        "duration_seconds": duration_seconds,
+        "is_active": is_active.unwrap_or(false),
+        "created_at": now().to_rfc3339(),
+        "updated_at": now().to_rfc3339()
+    });
+
+    let response = db
+        .upsert("time_entries", "id", time_entry_data)
+        .await
+        .map_err(|e| format!("Failed to create time entry: {}", e))?;
+
+    // The response should be an array with the created record
+    let created_entries: Vec<TimeEntry> = serde_json::from_value(response)
         .map_err(|e| format!("Failed to parse created time entry: {}", e))?;
     
     if let Some(created_entry) = created_entries.into_iter().next() {
+        if let Some(app_id) = &created_entry.app_id {
+            let used_at = created_entry.end_time.unwrap_or(created_entry.start_time);
+            crate::platform::database_helpers::DatabaseHelpers::mark_application_used(&db, app_id, used_at);
+        }
         Ok(created_entry)
     } else {
         Err("No time entry was created".to_string())
@@ -1265,8 +2844,84 @@ pub async fn get_time_entries_by_user(
     db: State<'_, Database>,
     user_id: String,
     limit: Option<u32>,
+    active_only: Option<bool>,
+    include_active: Option<bool>,
+    order: Option<String>,
 ) -> Result<Vec<TimeEntry>, String> {
-    let mut url = format!("{}/rest/v1/time_entries?user_id=eq.{}&order=start_time.desc", db.base_url, user_id);
+    let (is_active, order) = time_entry_list_filters(active_only, include_active, order.as_deref())?;
+
+    let user_filter = format!("eq.{}", user_id);
+    let limit = limit.map(|limit| limit.to_string());
+    let mut filters: Vec<(&str, &str)> = vec![("user_id", user_filter.as_str()), ("order", order)];
+    if let Some(is_active) = is_active {
+        filters.push(("is_active", is_active));
+    }
+    if let Some(limit) = &limit {
+        filters.push(("limit", limit.as_str()));
+    }
+
+    let response = db
+        .execute_query_with_params("time_entries", "GET", &filters, None, None)
+        .await
+        .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+
+    let entries: Vec<TimeEntry> = serde_json::from_value(response).map_err(|e| format!("Failed to parse time entries: {}", e))?;
+    Ok(entries)
+}
+
+// is_active filter and sort order shared by the time entry list commands
+fn time_entry_list_filters(
+    active_only: Option<bool>,
+    include_active: Option<bool>,
+    order: Option<&str>,
+) -> Result<(Option<&'static str>, &'static str), String> {
+    // active_only=true -> just the running entry; include_active=false -> just completed ones
+    let is_active = match (active_only.unwrap_or(false), include_active.unwrap_or(true)) {
+        (true, false) => return Err("active_only and include_active=false exclude every entry".to_string()),
+        (true, true) => Some("eq.true"),
+        (false, false) => Some("eq.false"),
+        (false, true) => None,
+    };
+    let order = match order.map(str::trim) {
+        None | Some("desc") => "start_time.desc",
+        Some("asc") => "start_time.asc",
+        Some(other) => return Err(format!("Invalid order '{}'. Must be 'asc' or 'desc'", other)),
+    };
+    Ok((is_active, order))
+}
+
+#[tauri::command]
+pub async fn get_time_entries_by_user_paged(
+    db: State<'_, Database>,
+    user_id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    active_only: Option<bool>,
+    include_active: Option<bool>,
+    order: Option<String>,
+) -> Result<Page<TimeEntry>, String> {
+    let (is_active, order) = time_entry_list_filters(active_only, include_active, order.as_deref())?;
+
+    let user_filter = format!("eq.{}", user_id);
+    let mut filters: Vec<(&str, &str)> = vec![("user_id", user_filter.as_str()), ("order", order)];
+    if let Some(is_active) = is_active {
+        filters.push(("is_active", is_active));
+    }
+
+    fetch_page(&db, "time_entries", &filters, limit, offset).await
+}
+
+#[tauri::command]
+pub async fn get_time_entries_by_user_with_apps(
+    db: State<'_, Database>,
+    user_id: String,
+    limit: Option<u32>,
+) -> Result<Vec<TimeEntryWithApp>, String> {
+    // Embed the application row so callers don't need a second lookup per entry
+    let mut url = format!(
+        "{}/rest/v1/time_entries?user_id=eq.{}&order=start_time.desc&select=*,applications(name,category,icon_path)",
+        db.base_url, encode_filter_value(&user_id)
+    );
     if let Some(limit) = limit {
         url.push_str(&format!("&limit={}", limit));
     }
@@ -1279,16 +2934,309 @@ pub async fn get_time_entries_by_user(
         .await
         .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
 
-    let entries: Vec<TimeEntry> = response.json().await.map_err(|e| format!("Failed to parse time entries: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch time entries: {} - {}", status, error_text));
+    }
+
+    let entries: Vec<TimeEntryWithApp> = response.json().await.map_err(|e| format!("Failed to parse time entries: {}", e))?;
     Ok(entries)
 }
 
+/// The running entry with its app and how long it has been open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveEntry {
+    #[serde(flatten)]
+    pub entry: TimeEntry,
+    pub app_name: Option<String>,
+    pub app_category: Option<String>,
+    pub elapsed_seconds: i64, // now - start_time, measured here rather than on the client
+}
+
+// Most recently started open entry for the user, if any
+async fn get_active_entry_in_db(
+    db: &Database,
+    user_id: &str,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<Option<ActiveEntry>, String> {
+    let user_filter = format!("eq.{}", user_id);
+    let response = db
+        .execute_query_with_params(
+            "time_entries",
+            "GET",
+            &[
+                ("user_id", user_filter.as_str()),
+                ("end_time", "is.null"),
+                ("order", "start_time.desc"),
+                ("limit", "1"),
+            ],
+            Some("*,applications(name,category,icon_path)"),
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch active time entry: {}", e))?;
+    let entries: Vec<TimeEntryWithApp> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse active time entry: {}", e))?;
+
+    Ok(entries.into_iter().next().map(|with_app| ActiveEntry {
+        app_name: with_app.app_name().map(str::to_string),
+        app_category: with_app.app_category().map(str::to_string),
+        elapsed_seconds: (at - with_app.entry.start_time).num_seconds().max(0),
+        entry: with_app.entry,
+    }))
+}
+
 #[tauri::command]
-pub async fn get_time_entries_by_task(
+pub async fn get_active_entry(db: State<'_, Database>) -> Result<Option<ActiveEntry>, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    get_active_entry_in_db(&db, &user_id, now()).await
+}
+
+/// A time entry with its app and task names, ready for a recent-activity list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub app_name: Option<String>,
+    pub task_title: Option<String>,
+    pub start: String,       // Local time, RFC 3339 with offset
+    pub end: Option<String>, // None while the session is still running
+    pub duration_seconds: i64, // Elapsed so far when still running
+    pub is_active: bool,
+}
+
+#[derive(Deserialize)]
+struct EmbeddedTaskTitle {
+    title: String,
+}
+
+// time_entries row with `applications(name)` and `tasks(title)` embedded
+#[derive(Deserialize)]
+struct SessionRow {
+    #[serde(flatten)]
+    entry: TimeEntry,
+    #[serde(default)]
+    applications: Option<crate::database::EmbeddedApplication>,
+    #[serde(default)]
+    tasks: Option<EmbeddedTaskTitle>,
+}
+
+async fn get_recent_sessions_in_db(
+    db: &Database,
+    user_id: &str,
+    limit: u32,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<Session>, String> {
+    let user_filter = format!("eq.{}", user_id);
+    let limit = limit.clamp(1, DEFAULT_PAGE_SIZE).to_string();
+    let response = db
+        .execute_query_with_params(
+            "time_entries",
+            "GET",
+            &[
+                ("user_id", user_filter.as_str()),
+                ("order", "start_time.desc"),
+                ("limit", limit.as_str()),
+            ],
+            Some("*,applications(name,category,icon_path),tasks(title)"),
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch recent sessions: {}", e))?;
+    let rows: Vec<SessionRow> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse recent sessions: {}", e))?;
+
+    let local = |time: chrono::DateTime<chrono::Utc>| time.with_timezone(&chrono::Local).to_rfc3339();
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let entry = row.entry;
+            let duration_seconds = match entry.end_time {
+                Some(end) => entry.duration_seconds.unwrap_or((end - entry.start_time).num_seconds()),
+                None => (at - entry.start_time).num_seconds(),
+            }
+            .max(0);
+            Session {
+                id: entry.id,
+                app_name: row.applications.map(|app| app.name),
+                task_title: row.tasks.map(|task| task.title),
+                start: local(entry.start_time),
+                end: entry.end_time.map(local),
+                duration_seconds,
+                is_active: entry.end_time.is_none(),
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_recent_sessions(db: State<'_, Database>, limit: u32) -> Result<Vec<Session>, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    get_recent_sessions_in_db(&db, &user_id, limit, now()).await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    EntryStarted,
+    EntryEnded,
+    TaskCompleted,
+}
+
+/// One item in a workspace's activity feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub actor_id: String,
+    pub actor_name: String,
+    pub kind: ActivityKind,
+    pub subject: String, // App name (or task title) for entries, task title for completed tasks
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+// Turn entries and completed tasks into events, newest first, keeping `limit` of them
+fn build_activity_feed(
+    names: &HashMap<String, String>,
+    entries: Vec<SessionRow>,
+    done_tasks: Vec<Task>,
+    limit: usize,
+) -> Vec<ActivityEvent> {
+    let actor_name = |id: &str| names.get(id).cloned().unwrap_or_else(|| "Unknown member".to_string());
+    let mut events = Vec::new();
+
+    for row in entries {
+        let entry = row.entry;
+        let subject = row
+            .applications
+            .map(|app| app.name)
+            .or_else(|| row.tasks.map(|task| task.title))
+            .unwrap_or_else(|| "Untracked app".to_string());
+        let mut push = |kind, timestamp| {
+            events.push(ActivityEvent {
+                actor_id: entry.user_id.clone(),
+                actor_name: actor_name(&entry.user_id),
+                kind,
+                subject: subject.clone(),
+                timestamp,
+            })
+        };
+        push(ActivityKind::EntryStarted, entry.start_time);
+        if let Some(end_time) = entry.end_time {
+            push(ActivityKind::EntryEnded, end_time);
+        }
+    }
+
+    for task in done_tasks {
+        let (Some(assignee_id), Some(updated_at)) = (task.assignee_id, task.updated_at) else {
+            continue;
+        };
+        events.push(ActivityEvent {
+            actor_name: actor_name(&assignee_id),
+            actor_id: assignee_id,
+            kind: ActivityKind::TaskCompleted,
+            subject: task.title,
+            timestamp: updated_at,
+        });
+    }
+
+    events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    events.truncate(limit);
+    events
+}
+
+// Members, their latest entries and the workspace's completed tasks: three requests in all
+async fn get_workspace_activity_feed_in_db(
+    db: &Database,
+    workspace_id: &str,
+    limit: u32,
+) -> Result<Vec<ActivityEvent>, String> {
+    let limit = limit.clamp(1, DEFAULT_PAGE_SIZE);
+    let members = fetch_users_by_workspace(db, workspace_id).await?;
+    if members.is_empty() {
+        return Ok(Vec::new());
+    }
+    let names: HashMap<String, String> = members.into_iter().map(|user| (user.id, user.name)).collect();
+
+    let member_filter = format!("in.({})", names.keys().cloned().collect::<Vec<_>>().join(","));
+    let limit_param = limit.to_string();
+    // Recently updated entries cover both the ones just started and the ones just ended
+    let response = db
+        .execute_query_with_params(
+            "time_entries",
+            "GET",
+            &[
+                ("user_id", member_filter.as_str()),
+                ("order", "updated_at.desc"),
+                ("limit", limit_param.as_str()),
+            ],
+            Some("*,applications(name,category,icon_path),tasks(title)"),
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch workspace activity: {}", e))?;
+    let entries: Vec<SessionRow> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse workspace activity: {}", e))?;
+
+    let workspace_filter = format!("eq.{}", workspace_id);
+    let response = db
+        .execute_query_with_params(
+            "tasks",
+            "GET",
+            &[
+                ("workspace_id", workspace_filter.as_str()),
+                ("status", "eq.done"),
+                ("order", "updated_at.desc"),
+                ("limit", limit_param.as_str()),
+            ],
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch completed tasks: {}", e))?;
+    let done_tasks: Vec<Task> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse completed tasks: {}", e))?;
+
+    Ok(build_activity_feed(&names, entries, done_tasks, limit as usize))
+}
+
+#[tauri::command]
+pub async fn get_workspace_activity_feed(
     db: State<'_, Database>,
-    task_id: String,
+    workspace_id: String,
+    limit: u32,
+) -> Result<Vec<ActivityEvent>, String> {
+    get_workspace_activity_feed_in_db(&db, &workspace_id, limit).await
+}
+
+// Fetch a user's time entries that overlap [start, end), including entries still running,
+// so reports don't need to pull the entire history.
+async fn fetch_time_entries_in_range(
+    db: &Database,
+    user_id: &str,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<TimeEntry>, String> {
+    fetch_app_time_entries_in_range(db, user_id, None, start, end).await
+}
+
+// Like fetch_time_entries_in_range, narrowed to one app's entries when `app_id` is given
+async fn fetch_app_time_entries_in_range(
+    db: &Database,
+    user_id: &str,
+    app_id: Option<&str>,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
 ) -> Result<Vec<TimeEntry>, String> {
-    let url = format!("{}/rest/v1/time_entries?task_id=eq.{}&order=start_time.desc", db.base_url, task_id);
+    let mut url = format!(
+        "{}/rest/v1/time_entries?user_id=eq.{}&start_time=lt.{}&or=(end_time.is.null,end_time.gt.{})&order=start_time.asc",
+        db.base_url,
+        encode_filter_value(user_id),
+        end.format("%Y-%m-%dT%H:%M:%SZ"),
+        start.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    if let Some(app_id) = app_id {
+        url.push_str(&format!("&app_id=eq.{}", crate::database::encode_filter_value(app_id)));
+    }
+
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -1297,16 +3245,22 @@ pub async fn get_time_entries_by_task(
         .await
         .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
 
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch time entries: {} - {}", status, error_text));
+    }
+
     let entries: Vec<TimeEntry> = response.json().await.map_err(|e| format!("Failed to parse time entries: {}", e))?;
     Ok(entries)
 }
 
 #[tauri::command]
-pub async fn get_time_entries_by_app(
+pub async fn get_time_entries_by_task(
     db: State<'_, Database>,
-    app_id: String,
+    task_id: String,
 ) -> Result<Vec<TimeEntry>, String> {
-    let url = format!("{}/rest/v1/time_entries?app_id=eq.{}&order=start_time.desc", db.base_url, app_id);
+    let url = format!("{}/rest/v1/time_entries?task_id=eq.{}&order=start_time.desc", db.base_url, encode_filter_value(&task_id));
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -1319,6 +3273,27 @@ pub async fn get_time_entries_by_app(
     Ok(entries)
 }
 
+#[tauri::command]
+pub async fn get_time_entries_by_app(
+    db: State<'_, Database>,
+    app_id: String,
+) -> Result<Vec<TimeEntry>, String> {
+    let filter = format!("eq.{}", app_id);
+    let response = db
+        .execute_query_with_params(
+            "time_entries",
+            "GET",
+            &[("app_id", &filter), ("order", "start_time.desc")],
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+
+    let entries: Vec<TimeEntry> = serde_json::from_value(response).map_err(|e| format!("Failed to parse time entries: {}", e))?;
+    Ok(entries)
+}
+
 #[tauri::command]
 pub async fn update_time_entry(
     db: State<'_, Database>,
@@ -1326,80 +3301,694 @@ pub async fn update_time_entry(
     end_time: Option<String>,
     duration_seconds: Option<i64>,
     is_active: Option<bool>,
+    app_id: Option<String>,
+    task_id: Option<String>,
+) -> Result<TimeEntry, String> {
+    update_time_entry_in_db(&db, entry_id, end_time, duration_seconds, is_active, app_id, task_id).await
+}
+
+async fn fetch_time_entry(db: &Database, entry_id: &str) -> Result<Option<TimeEntry>, String> {
+    let url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, encode_filter_value(entry_id));
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch time entry: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch time entry: {} - {}", status, error_text));
+    }
+
+    let time_entries: Vec<TimeEntry> = response.json().await
+        .map_err(|e| format!("Failed to parse time entry: {}", e))?;
+    Ok(time_entries.into_iter().next())
+}
+
+// A task belongs to a user when it's assigned to them or sits in one of their workspaces
+async fn task_belongs_to_user(db: &Database, task: &Task, user_id: &str) -> Result<bool, String> {
+    if task.assignee_id.as_deref() == Some(user_id) {
+        return Ok(true);
+    }
+    let Some(workspace_id) = task.workspace_id.as_deref() else {
+        return Ok(false);
+    };
+    let memberships = fetch_memberships_for_user(db, user_id).await?;
+    Ok(memberships.iter().any(|record| record.workspace_id.as_deref() == Some(workspace_id)))
+}
+
+async fn update_time_entry_in_db(
+    db: &Database,
+    entry_id: String,
+    end_time: Option<String>,
+    duration_seconds: Option<i64>,
+    is_active: Option<bool>,
+    app_id: Option<String>,
+    task_id: Option<String>,
 ) -> Result<TimeEntry, String> {
     let mut update_data = json!({
         "updated_at": now().to_rfc3339()
     });
 
-    // If end_time is provided but duration_seconds is not, calculate it automatically
-    if let Some(end_time_str) = &end_time {
-        update_data["end_time"] = json!(end_time_str);
-        
-        if duration_seconds.is_none() {
-            // Get the current time entry to access the start_time
-            let get_url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, entry_id);
-            let get_response = db.client
-                .get(&get_url)
-                .header("apikey", &db.api_key)
-                .header("Authorization", format!("Bearer {}", db.api_key))
-                .send()
-                .await
-                .map_err(|e| format!("Failed to fetch time entry: {}", e))?;
+    // The stored entry is needed to derive a duration or to check who owns it
+    let needs_entry = (end_time.is_some() && duration_seconds.is_none()) || app_id.is_some() || task_id.is_some();
+    let existing_entry = if needs_entry {
+        fetch_time_entry(db, &entry_id).await?
+    } else {
+        None
+    };
+
+    // If end_time is provided but duration_seconds is not, calculate it automatically
+    if let Some(end_time_str) = &end_time {
+        update_data["end_time"] = json!(end_time_str);
+        
+        if duration_seconds.is_none() {
+            if let Some(time_entry) = &existing_entry {
+                // Parse the provided end_time
+                if let Ok(end_time_parsed) = chrono::DateTime::parse_from_rfc3339(end_time_str) {
+                    let end_time_utc = end_time_parsed.with_timezone(&chrono::Utc);
+                    let start_time = time_entry.start_time;
+                    
+                    // Calculate duration in seconds
+                    let calculated_duration = (end_time_utc - start_time).num_seconds();
+                    update_data["duration_seconds"] = json!(calculated_duration);
+                    log::debug!("Auto-calculated duration: {} seconds for time entry {}", calculated_duration, entry_id);
+                }
+            }
+        }
+    }
+    
+    if let Some(duration_seconds) = duration_seconds {
+        update_data["duration_seconds"] = json!(duration_seconds);
+    }
+    if let Some(is_active) = is_active {
+        update_data["is_active"] = json!(is_active);
+    }
+
+    // Moving the entry to another app or task: both must belong to the entry's owner
+    if app_id.is_some() || task_id.is_some() {
+        let owner_id = existing_entry
+            .as_ref()
+            .map(|entry| entry.user_id.clone())
+            .ok_or_else(|| "Time entry not found".to_string())?;
+
+        if let Some(app_id) = &app_id {
+            crate::platform::database_helpers::DatabaseHelpers::get_application(db, app_id)
+                .await?
+                .filter(|app| app.user_id.as_deref() == Some(owner_id.as_str()))
+                .ok_or_else(|| "Application not found".to_string())?;
+            update_data["app_id"] = json!(app_id);
+        }
+
+        // An empty task id detaches the entry from its task
+        match task_id.as_deref().map(str::trim) {
+            Some("") => update_data["task_id"] = serde_json::Value::Null,
+            Some(task_id) => {
+                let task = fetch_task_by_id(db, task_id)
+                    .await?
+                    .ok_or_else(|| "Task not found".to_string())?;
+                if !task_belongs_to_user(db, &task, &owner_id).await? {
+                    return Err("Task not found".to_string());
+                }
+                update_data["task_id"] = json!(task.id);
+            }
+            None => {}
+        }
+    }
+
+    let url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, encode_filter_value(&entry_id));
+    let response = db.client
+        .patch(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", "return=representation")
+        .json(&update_data)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to update time entry: {}", e))?;
+
+    // The response should be an array with the updated record
+    let updated_entries: Vec<TimeEntry> = response.json().await.map_err(|e| format!("Failed to parse updated time entry: {}", e))?;
+    
+    if let Some(updated_entry) = updated_entries.into_iter().next() {
+        Ok(updated_entry)
+    } else {
+        Err("No time entry was updated".to_string())
+    }
+}
+
+// Split a finished entry at `at`: the original ends there and a copy for the same app
+// and task covers the rest. The copy is written first so a failure never loses time
+async fn split_time_entry_in_db(
+    db: &Database,
+    entry_id: &str,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<(TimeEntry, TimeEntry), String> {
+    let id_filter = format!("eq.{}", entry_id);
+    let response = db
+        .execute_query_with_params("time_entries", "GET", &[("id", id_filter.as_str())], None, None)
+        .await
+        .map_err(|e| format!("Failed to fetch time entry: {}", e))?;
+    let row = response
+        .as_array()
+        .and_then(|rows| rows.first())
+        .cloned()
+        .ok_or_else(|| "Time entry not found".to_string())?;
+    let entry: TimeEntry = serde_json::from_value(row)
+        .map_err(|e| format!("Failed to parse time entry: {}", e))?;
+
+    let end_time = match entry.end_time {
+        Some(end_time) if !entry.is_active => end_time,
+        _ => return Err("Cannot split the active time entry".to_string()),
+    };
+    if at <= entry.start_time || at >= end_time {
+        return Err("Split time must fall between the entry's start and end".to_string());
+    }
+
+    let second_data = json!({
+        "id": generate_id(),
+        "user_id": entry.user_id,
+        "app_id": entry.app_id,
+        "task_id": entry.task_id,
+        "workspace_id": entry.workspace_id.clone(),
+        "start_time": at.to_rfc3339(),
+        "end_time": end_time.to_rfc3339(),
+        "duration_seconds": (end_time - at).num_seconds(),
+        "is_active": false,
+        "created_at": now().to_rfc3339(),
+        "updated_at": now().to_rfc3339()
+    });
+    let response = db
+        .upsert("time_entries", "id", second_data)
+        .await
+        .map_err(|e| format!("Failed to create time entry: {}", e))?;
+    let created: Vec<TimeEntry> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse created time entry: {}", e))?;
+    let second = created.into_iter().next().ok_or_else(|| "No time entry was created".to_string())?;
+
+    let shorten = db
+        .execute_query_with_params(
+            "time_entries",
+            "PATCH",
+            &[("id", id_filter.as_str())],
+            None,
+            Some(json!({
+                "end_time": at.to_rfc3339(),
+                "duration_seconds": (at - entry.start_time).num_seconds(),
+                "updated_at": now().to_rfc3339()
+            })),
+        )
+        .await;
+    let response = match shorten {
+        Ok(response) => response,
+        // The shortened original will sync later, so the second half must stay
+        Err(e @ DbError::Queued) => return Err(format!("Failed to update time entry: {}", e)),
+        Err(e) => {
+            // Don't leave the second half overlapping the untouched original
+            let second_filter = format!("eq.{}", second.id);
+            if let Err(cleanup_err) = db
+                .execute_query_with_params("time_entries", "DELETE", &[("id", second_filter.as_str())], None, None)
+                .await
+            {
+                log::error!("Failed to remove split entry {}: {}", second.id, cleanup_err);
+            }
+            return Err(format!("Failed to update time entry: {}", e));
+        }
+    };
+    let updated: Vec<TimeEntry> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse updated time entry: {}", e))?;
+    let first = updated.into_iter().next().ok_or_else(|| "No time entry was updated".to_string())?;
+
+    log::info!("Split time entry {} at {} into {}", first.id, at.to_rfc3339(), second.id);
+    Ok((first, second))
+}
+
+#[tauri::command]
+pub async fn split_time_entry(
+    db: State<'_, Database>,
+    entry_id: String,
+    at: String,
+) -> Result<(TimeEntry, TimeEntry), String> {
+    let at = chrono::DateTime::parse_from_rfc3339(&at)
+        .map_err(|e| format!("Invalid split time: {}", e))?
+        .with_timezone(&chrono::Utc);
+    split_time_entry_in_db(&db, &entry_id, at).await
+}
+
+// One merged run: the first entry is kept and extended, the rest are deleted
+struct EntryMerge {
+    keep_id: String,
+    start_time: chrono::DateTime<chrono::Utc>,
+    end_time: chrono::DateTime<chrono::Utc>,
+    absorbed_ids: Vec<String>,
+}
+
+// Group consecutive finished entries of the same app separated by at most max_gap_seconds.
+// Entries must be sorted by start_time.
+fn plan_entry_merges(entries: &[TimeEntry], max_gap_seconds: i64) -> Vec<EntryMerge> {
+    let mut merges: Vec<EntryMerge> = Vec::new();
+    let mut current: Option<(EntryMerge, String)> = None; // (run, app_id)
+
+    for entry in entries {
+        // The active entry and entries without an app are never merged
+        let (app_id, end_time) = match (&entry.app_id, entry.end_time) {
+            (Some(app_id), Some(end_time)) if !entry.is_active => (app_id.clone(), end_time),
+            _ => {
+                if let Some((run, _)) = current.take() {
+                    merges.push(run);
+                }
+                continue;
+            }
+        };
+
+        if let Some((run, run_app_id)) = current.as_mut() {
+            let gap = (entry.start_time - run.end_time).num_seconds();
+            if *run_app_id == app_id && gap <= max_gap_seconds {
+                run.end_time = run.end_time.max(end_time);
+                run.absorbed_ids.push(entry.id.clone());
+                continue;
+            }
+        }
+
+        if let Some((run, _)) = current.take() {
+            merges.push(run);
+        }
+        current = Some((
+            EntryMerge {
+                keep_id: entry.id.clone(),
+                start_time: entry.start_time,
+                end_time,
+                absorbed_ids: Vec::new(),
+            },
+            app_id,
+        ));
+    }
+
+    if let Some((run, _)) = current.take() {
+        merges.push(run);
+    }
+
+    merges.retain(|run| !run.absorbed_ids.is_empty());
+    merges
+}
+
+#[tauri::command]
+pub async fn merge_adjacent_entries(
+    db: State<'_, Database>,
+    user_id: Option<String>,
+    max_gap_seconds: i64,
+) -> Result<usize, String> {
+    if max_gap_seconds < 0 {
+        return Err("max_gap_seconds must not be negative".to_string());
+    }
+
+    let user_id = match user_id {
+        Some(id) => id,
+        None => crate::current_user::get_current_user_id_or_error()?,
+    };
+
+    let url = format!("{}/rest/v1/time_entries?user_id=eq.{}&order=start_time.asc", db.base_url, encode_filter_value(&user_id));
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to fetch time entries: {} - {}", status, error_text));
+    }
+
+    let entries: Vec<TimeEntry> = response.json().await.map_err(|e| format!("Failed to parse time entries: {}", e))?;
+
+    let mut removed = 0;
+    for merge in plan_entry_merges(&entries, max_gap_seconds) {
+        // Extend the kept entry first so no time is lost if the delete fails
+        let update_url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, encode_filter_value(&merge.keep_id));
+        let update_response = db.client
+            .patch(&update_url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .header("Content-Type", "application/json")
+            .json(&json!({
+                "end_time": merge.end_time.to_rfc3339(),
+                "duration_seconds": (merge.end_time - merge.start_time).num_seconds(),
+                "updated_at": now().to_rfc3339()
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to update time entry: {}", e))?;
+
+        if !update_response.status().is_success() {
+            return Err(format!("Failed to update time entry {}: {}", merge.keep_id, update_response.status()));
+        }
+
+        let delete_url = format!(
+            "{}/rest/v1/time_entries?id=in.({})",
+            db.base_url,
+            merge.absorbed_ids.iter().map(|id| encode_filter_value(id)).collect::<Vec<_>>().join(",")
+        );
+        let delete_response = db.client
+            .delete(&delete_url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to delete merged time entries: {}", e))?;
+
+        if !delete_response.status().is_success() {
+            return Err(format!("Failed to delete merged time entries: {}", delete_response.status()));
+        }
+
+        removed += merge.absorbed_ids.len();
+    }
+
+    log::info!("merge_adjacent_entries: Removed {} entries for user {}", removed, user_id);
+    Ok(removed)
+}
+
+// Delete a user's finished entries that started before the cutoff. The running
+// entry is never matched because it is still active and has no end time.
+async fn purge_time_entries_in_db(
+    db: &Database,
+    user_id: &str,
+    cutoff: chrono::DateTime<chrono::Utc>,
+    export_path: Option<&str>,
+) -> Result<usize, String> {
+    let filter = format!(
+        "user_id=eq.{}&start_time=lt.{}&is_active=eq.false&end_time=not.is.null",
+        encode_filter_value(user_id),
+        cutoff.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+
+    if let Some(export_path) = export_path {
+        let get_url = format!("{}/rest/v1/time_entries?{}&order=start_time.asc", db.base_url, filter);
+        let response = db.client
+            .get(&get_url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch time entries for export: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch time entries for export: {}", response.status()));
+        }
+
+        let entries: Vec<TimeEntry> = response.json().await
+            .map_err(|e| format!("Failed to parse time entries for export: {}", e))?;
+        let contents = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("Failed to serialize time entries: {}", e))?;
+        std::fs::write(export_path, contents)
+            .map_err(|e| format!("Failed to write export file: {}", e))?;
+        log::info!("purge_time_entries: Exported {} entries to {}", entries.len(), export_path);
+    }
+
+    let delete_url = format!("{}/rest/v1/time_entries?{}", db.base_url, filter);
+    let response = db.client
+        .delete(&delete_url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Prefer", "return=minimal, count=exact")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to purge time entries: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to purge time entries: {} - {}", status, error_text));
+    }
+
+    let count = response
+        .headers()
+        .get("content-range")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range_count)
+        .unwrap_or(0);
+
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn purge_time_entries_before(
+    db: State<'_, Database>,
+    cutoff: String,
+    export_path: Option<String>,
+) -> Result<usize, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let cutoff = chrono::DateTime::parse_from_rfc3339(&cutoff)
+        .map_err(|e| format!("Invalid cutoff: {}", e))?
+        .with_timezone(&chrono::Utc);
+    if cutoff >= now() {
+        return Err("Cutoff must be in the past".to_string());
+    }
+
+    let removed = purge_time_entries_in_db(&db, &user_id, cutoff, export_path.as_deref()).await?;
+    log::info!("purge_time_entries_before: Removed {} entries for user {}", removed, user_id);
+    Ok(removed)
+}
+
+// Entry ids per PATCH when backfilling, to keep the `id=in.(...)` filter a sane length
+const BACKFILL_BATCH_SIZE: usize = 100;
+
+/// Set workspace_id on the current user's time entries that were recorded without one,
+/// using the workspace they had most recently joined when each entry started.
+/// Entries from before the user joined any workspace are left alone. Returns how many were updated.
+#[tauri::command]
+pub async fn backfill_time_entry_workspace(db: State<'_, Database>) -> Result<usize, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    backfill_time_entry_workspace_in_db(&db, &user_id).await
+}
+
+async fn backfill_time_entry_workspace_in_db(db: &Database, user_id: &str) -> Result<usize, String> {
+    let mut memberships: Vec<(Option<chrono::DateTime<chrono::Utc>>, String)> = fetch_memberships_for_user(db, user_id)
+        .await?
+        .into_iter()
+        .filter_map(|record| Some((record.joined_at, record.workspace_id?)))
+        .collect();
+    if memberships.is_empty() {
+        return Ok(0);
+    }
+    // Memberships without a join date count as held from the start
+    memberships.sort_by_key(|(joined_at, _)| *joined_at);
+
+    let url = format!(
+        "{}/rest/v1/time_entries?user_id=eq.{}&workspace_id=is.null&select=id,start_time",
+        db.base_url,
+        encode_filter_value(user_id)
+    );
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch time entries: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch time entries: {}", response.status()));
+    }
+
+    #[derive(Deserialize)]
+    struct EntryStart {
+        id: String,
+        start_time: chrono::DateTime<chrono::Utc>,
+    }
+    let entries: Vec<EntryStart> = response.json().await.map_err(|e| format!("Failed to parse time entries: {}", e))?;
+
+    let mut ids_by_workspace: HashMap<String, Vec<String>> = HashMap::new();
+    for entry in entries {
+        let workspace = memberships
+            .iter()
+            .rev()
+            .find(|(joined_at, _)| !joined_at.is_some_and(|joined_at| joined_at > entry.start_time));
+        if let Some((_, workspace_id)) = workspace {
+            ids_by_workspace.entry(workspace_id.clone()).or_default().push(entry.id);
+        }
+    }
+
+    let mut updated = 0;
+    for (workspace_id, ids) in ids_by_workspace {
+        for batch in ids.chunks(BACKFILL_BATCH_SIZE) {
+            let patch_url = format!(
+                "{}/rest/v1/time_entries?id=in.({})&workspace_id=is.null",
+                db.base_url,
+                batch.iter().map(|id| encode_filter_value(id)).collect::<Vec<_>>().join(",")
+            );
+            let patch_response = db.client
+                .patch(&patch_url)
+                .header("apikey", &db.api_key)
+                .header("Authorization", format!("Bearer {}", db.api_key))
+                .header("Content-Type", "application/json")
+                .header("Prefer", "return=minimal")
+                .json(&json!({ "workspace_id": workspace_id }))
+                .send()
+                .await
+                .map_err(|e| format!("Failed to backfill time entries: {}", e))?;
+
+            if !patch_response.status().is_success() {
+                return Err(format!("Failed to backfill time entries: {}", patch_response.status()));
+            }
+            updated += batch.len();
+        }
+    }
+
+    log::info!("backfill_time_entry_workspace: Set workspace on {} entries for user {}", updated, user_id);
+    Ok(updated)
+}
+
+// Days of history to keep, from TIME_ENTRY_RETENTION_DAYS (unset or 0 disables auto-purge)
+fn retention_days_from_env() -> Option<i64> {
+    std::env::var("TIME_ENTRY_RETENTION_DAYS")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|days| *days > 0)
+}
+
+// ===== UTILITY COMMANDS =====
+
+/// What `force_sync_now` did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub flushed: usize, // Queued writes replayed
+    pub recovered: usize, // Orphaned running entries closed
+    pub errors: usize,
+}
+
+/// Flush queued writes, close orphaned entries and refresh the current activity in one go
+#[tauri::command]
+pub async fn force_sync_now(db: State<'_, Database>) -> Result<SyncReport, String> {
+    crate::current_user::get_current_user_id_or_error()?;
+
+    let mut report = SyncReport { flushed: 0, recovered: 0, errors: 0 };
+
+    // Replay what was queued offline first so recovery sees the latest rows
+    match crate::sync_queue::flush(&db).await {
+        Ok(flushed) => report.flushed = flushed,
+        Err(e) => {
+            log::warn!("force_sync_now: Queue flush stopped early: {}", e);
+            report.errors += 1;
+        }
+    }
+
+    let tracker = crate::tracking::get_tracker();
+    let running = match tracker {
+        Some(tracker) => tracker.running_entry_ids().await,
+        None => Vec::new(),
+    };
+    match crate::platform::database_helpers::DatabaseHelpers::end_orphaned_entries(&db, &running).await {
+        Ok((recovered, errors)) => {
+            report.recovered = recovered;
+            report.errors += errors;
+        }
+        Err(e) => {
+            log::warn!("force_sync_now: Orphan recovery failed: {}", e);
+            report.errors += 1;
+        }
+    }
+
+    if let Some(tracker) = tracker {
+        tracker.invalidate_activity_cache().await;
+        if let Err(e) = tracker.get_current_activity().await {
+            log::warn!("force_sync_now: Failed to refresh current activity: {}", e);
+            report.errors += 1;
+        }
+    }
+
+    log::info!("force_sync_now: {:?}", report);
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn test_database_connection(db: State<'_, Database>) -> Result<bool, String> {
+    db.test_connection().await.map_err(|e| e.to_string())
+}
+
+const CONNECTION_CHECK_INTERVAL_SECS: u64 = 15;
+
+static CONNECTION_WATCHER_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+// The running watcher, so logging out can stop it before the next login starts another
+static CONNECTION_WATCHER: once_cell::sync::Lazy<std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>> =
+    once_cell::sync::Lazy::new(|| std::sync::Mutex::new(None));
 
-            if get_response.status().is_success() {
-                let time_entries: Vec<TimeEntry> = get_response.json().await
-                    .map_err(|e| format!("Failed to parse time entry: {}", e))?;
-                
-                if let Some(time_entry) = time_entries.first() {
-                    // Parse the provided end_time
-                    if let Ok(end_time_parsed) = chrono::DateTime::parse_from_rfc3339(end_time_str) {
-                        let end_time_utc = end_time_parsed.with_timezone(&chrono::Utc);
-                        let start_time = time_entry.start_time;
-                        
-                        // Calculate duration in seconds
-                        let calculated_duration = (end_time_utc - start_time).num_seconds();
-                        update_data["duration_seconds"] = json!(calculated_duration);
-                        println!("Auto-calculated duration: {} seconds for time entry {}", calculated_duration, entry_id);
-                    }
+// Once the database is reachable again, replay queued writes and restart any
+// running entry the database lost track of while we were offline
+async fn resume_after_reconnect(db: &Database) {
+    // Until every queued write is in, an entry whose insert is still queued looks lost
+    if let Err(e) = crate::sync_queue::flush(db).await {
+        log::warn!("Reconnect: Queue flush stopped early, keeping running entries: {}", e);
+        return;
+    }
+
+    let Some(tracker) = crate::tracking::get_tracker() else { return };
+    match tracker.forget_lost_entries().await {
+        Ok(0) => {}
+        Ok(lost) => {
+            log::info!("Reconnect: {} running entries were lost, restarting them", lost);
+            if tracker.is_tracking().await {
+                if let Err(e) = tracker.update_activity().await {
+                    log::warn!("Reconnect: Failed to restart tracking: {}", e);
                 }
             }
         }
+        Err(e) => log::warn!("Reconnect: Failed to check running entries: {}", e),
     }
-    
-    if let Some(duration_seconds) = duration_seconds {
-        update_data["duration_seconds"] = json!(duration_seconds);
-    }
-    if let Some(is_active) = is_active {
-        update_data["is_active"] = json!(is_active);
+}
+
+// Check the connection periodically, emitting `connection-lost` and `connection-restored`
+// when it changes so the UI can show an offline banner
+fn start_connection_watcher(app_handle: tauri::AppHandle, db: Database) {
+    use tauri::Emitter;
+
+    if CONNECTION_WATCHER_STARTED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
     }
 
-    let url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, entry_id);
-    let response = db.client
-        .patch(&url)
-        .header("apikey", &db.api_key)
-        .header("Authorization", format!("Bearer {}", db.api_key))
-        .header("Content-Type", "application/json")
-        .header("Prefer", "return=representation")
-        .json(&update_data)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to update time entry: {}", e))?;
+    let handle = tokio::spawn(async move {
+        // Login only gets this far after a successful connection test
+        let mut online = true;
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(CONNECTION_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
 
-    // The response should be an array with the updated record
-    let updated_entries: Vec<TimeEntry> = response.json().await.map_err(|e| format!("Failed to parse updated time entry: {}", e))?;
-    
-    if let Some(updated_entry) = updated_entries.into_iter().next() {
-        Ok(updated_entry)
-    } else {
-        Err("No time entry was updated".to_string())
+            let reachable = matches!(db.test_connection().await, Ok(true));
+            if reachable == online {
+                continue;
+            }
+            online = reachable;
+
+            if online {
+                log::info!("Database connection restored");
+                resume_after_reconnect(&db).await;
+                let _ = app_handle.emit("connection-restored", ());
+            } else {
+                log::warn!("Database connection lost");
+                let _ = app_handle.emit("connection-lost", ());
+            }
+        }
+    });
+    if let Ok(mut watcher) = CONNECTION_WATCHER.lock() {
+        *watcher = Some(handle);
     }
 }
 
-// ===== UTILITY COMMANDS =====
-
-#[tauri::command]
-pub async fn test_database_connection(db: State<'_, Database>) -> Result<bool, String> {
-    db.test_connection().await.map_err(|e| e.to_string())
+// Stop the connection watcher so the next login starts one for its own database
+fn stop_connection_watcher() {
+    if let Ok(mut watcher) = CONNECTION_WATCHER.lock() {
+        if let Some(handle) = watcher.take() {
+            handle.abort();
+        }
+    }
+    CONNECTION_WATCHER_STARTED.store(false, std::sync::atomic::Ordering::SeqCst);
 }
 
 #[tauri::command]
@@ -1427,7 +4016,6 @@ pub async fn initialize_database_and_login(
     }
     
     log::info!("Database will be configured with URL: {}", supabase_config.url);
-    log::info!("Database will be configured with API key: {}...", &supabase_config.anon_key[..std::cmp::min(10, supabase_config.anon_key.len())]);
 
     // Initialize database
     let database = Database::new(supabase_config.url, supabase_config.anon_key)
@@ -1449,6 +4037,34 @@ pub async fn initialize_database_and_login(
     // Manage the database state
     app_handle.manage(database.clone());
 
+    // Replay any writes that were queued while offline
+    crate::sync_queue::start_background_flush(database.clone());
+
+    // Tell the UI when we go offline, and pick tracking back up when we return
+    start_connection_watcher(app_handle.clone(), database.clone());
+
+    // Remind about tasks that are about to come due
+    start_due_task_reminders(app_handle.clone(), database.clone());
+
+    // Warn when a running app goes over its daily limit
+    start_app_limit_monitor(app_handle.clone(), database.clone());
+
+    // Keep dashboard widgets fed with today's totals
+    start_usage_snapshots(app_handle.clone(), database.clone());
+
+    // Drop entries older than the retention window, if one is configured
+    if let Some(days) = retention_days_from_env() {
+        let db = database.clone();
+        let user_id = user_id.clone();
+        tokio::spawn(async move {
+            let cutoff = now() - chrono::Duration::days(days);
+            match purge_time_entries_in_db(&db, &user_id, cutoff, None).await {
+                Ok(removed) => log::info!("Auto-purge removed {} time entries older than {} days", removed, days),
+                Err(e) => log::warn!("Auto-purge failed: {}", e),
+            }
+        });
+    }
+
     // Initialize the activity tracker
     crate::tracking::init_tracker(database);
 
@@ -1459,6 +4075,17 @@ pub async fn initialize_database_and_login(
     Ok(true)
 }
 
+// Exact email match; the value is escaped so addresses like a+b@example.com work
+async fn fetch_users_by_email(db: &Database, email: &str) -> Result<Vec<User>, String> {
+    let filter = format!("eq.{}", email);
+    let response = db
+        .execute_query_with_params("users", "GET", &[("email", &filter)], None, None)
+        .await
+        .map_err(|e| format!("Failed to check existing users: {}", e))?;
+
+    serde_json::from_value(response).map_err(|e| format!("Failed to parse existing users: {}", e))
+}
+
 #[tauri::command]
 pub async fn sign_up_user(
     app_handle: tauri::AppHandle,
@@ -1466,18 +4093,7 @@ pub async fn sign_up_user(
     password: String,
     name: String,
 ) -> Result<bool, String> {
-    // Load Supabase configuration
-    let supabase_config = match crate::config::SupabaseConfig::from_env() {
-        Ok(config) => config,
-        Err(e) => {
-            log::warn!("Failed to load Supabase config from environment: {}", e);
-            return Err(format!("Failed to load database configuration: {}", e));
-        }
-    };
-
-    // Initialize database
-    let database = Database::new(supabase_config.url, supabase_config.anon_key)
-        .map_err(|e| format!("Failed to initialize database: {}", e))?;
+    let database = database_from_env()?;
 
     // Test database connection
     match database.test_connection().await {
@@ -1497,32 +4113,12 @@ pub async fn sign_up_user(
         return Err("Email and password are required".to_string());
     }
 
-    if password.len() < 6 {
-        return Err("Password must be at least 6 characters long".to_string());
-    }
+    check_password_length(&password)?;
 
-    // Email validation regex
-    let email_regex = regex::Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$")
-        .map_err(|_| "Invalid email format validation error".to_string())?;
-    
-    if !email_regex.is_match(&email) {
-        return Err("Invalid email format".to_string());
-    }
+    validate_email(&email)?;
 
     // Check if user already exists in our users table
-    let existing_users_url = format!("{}/rest/v1/users?email=eq.{}", database.base_url, email);
-    let existing_users_response = database.client
-        .get(&existing_users_url)
-        .header("apikey", &database.api_key)
-        .header("Authorization", format!("Bearer {}", database.api_key))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to check existing users: {}", e))?;
-
-    if existing_users_response.status().is_success() {
-        let existing_users: Vec<User> = existing_users_response.json().await
-            .map_err(|e| format!("Failed to parse existing users: {}", e))?;
-        
+    if let Ok(existing_users) = fetch_users_by_email(&database, &email).await {
         if !existing_users.is_empty() {
             return Err("A user with this email already exists".to_string());
         }
@@ -1556,8 +4152,8 @@ pub async fn sign_up_user(
     let auth_result: serde_json::Value = auth_response.json().await
         .map_err(|e| format!("Failed to parse auth response: {}", e))?;
 
-    // Log the auth response for debugging
-    log::info!("Auth response: {}", serde_json::to_string_pretty(&auth_result).unwrap_or_else(|_| "Could not serialize response".to_string()));
+    // Log the auth response for debugging, without any tokens it carries
+    log::info!("Auth response: {}", serde_json::to_string_pretty(&redact_secrets(&auth_result)).unwrap_or_else(|_| "Could not serialize response".to_string()));
 
     // Extract the user ID from the auth response - try multiple possible structures
     let user_id = auth_result
@@ -1586,7 +4182,7 @@ pub async fn sign_up_user(
         })
         .ok_or_else(|| {
             format!("Failed to extract user ID from auth response. Response structure: {}", 
-                   serde_json::to_string(&auth_result).unwrap_or_else(|_| "Could not serialize".to_string()))
+                   serde_json::to_string(&redact_secrets(&auth_result)).unwrap_or_else(|_| "Could not serialize".to_string()))
         })?;
 
     // Use provided name if present, otherwise fallback to email local part
@@ -1599,7 +4195,7 @@ pub async fn sign_up_user(
     // First try to update an existing users row for this id (in case Supabase
     // or another process already created a placeholder row). This avoids
     // duplicate-key conflicts when inserting.
-    let patch_url = format!("{}/rest/v1/users?id=eq.{}", database.base_url, user_id);
+    let patch_url = format!("{}/rest/v1/users?id=eq.{}", database.base_url, encode_filter_value(&user_id));
     let patch_payload = json!({
         "name": chosen_name,
         "updated_at": now().to_rfc3339(),
@@ -1651,6 +4247,176 @@ pub async fn sign_up_user(
     Ok(true)
 }
 
+// Shortest password the app accepts, matching the Auth server's default
+const MIN_PASSWORD_LENGTH: usize = 6;
+
+// Database for the Auth calls made before anyone is signed in
+fn database_from_env() -> Result<Database, String> {
+    let supabase_config = crate::config::SupabaseConfig::from_env().map_err(|e| {
+        log::warn!("Failed to load Supabase config from environment: {}", e);
+        format!("Failed to load database configuration: {}", e)
+    })?;
+    Database::new(supabase_config.url, supabase_config.anon_key)
+        .map_err(|e| format!("Failed to initialize database: {}", e))
+}
+
+fn check_password_length(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!("Password must be at least {} characters long", MIN_PASSWORD_LENGTH));
+    }
+    Ok(())
+}
+
+// Re-authenticate with the current password, then set the new one with the session it returns
+async fn change_password_in_db(db: &Database, email: &str, current_password: &str, new_password: &str) -> Result<(), String> {
+    if current_password.is_empty() {
+        return Err("Current password is required".to_string());
+    }
+    check_password_length(new_password).map_err(|e| format!("New password is too weak: {}", e))?;
+
+    let token_url = format!("{}/auth/v1/token?grant_type=password", db.base_url);
+    let token_response = db.client
+        .post(&token_url)
+        .header("apikey", &db.api_key)
+        .header("Content-Type", "application/json")
+        .json(&json!({ "email": email, "password": current_password }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to verify current password: {}", e))?;
+
+    // The Auth API answers a wrong password with 400 invalid_grant
+    if token_response.status() == StatusCode::BAD_REQUEST {
+        return Err("Current password is incorrect".to_string());
+    }
+    if !token_response.status().is_success() {
+        let status = token_response.status();
+        let error_text = token_response.text().await.unwrap_or_default();
+        return Err(format!("Failed to verify current password: {} - {}", status, error_text));
+    }
+
+    let session: serde_json::Value = token_response.json().await
+        .map_err(|e| format!("Failed to parse auth response: {}", e))?;
+    let access_token = session["access_token"]
+        .as_str()
+        .ok_or("Auth response did not include an access token")?;
+
+    set_password_with_session(db, access_token, new_password).await
+}
+
+// Set a new password for the user the access token belongs to
+async fn set_password_with_session(db: &Database, access_token: &str, new_password: &str) -> Result<(), String> {
+    let user_url = format!("{}/auth/v1/user", db.base_url);
+    let update_response = db.client
+        .put(&user_url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .header("Content-Type", "application/json")
+        .json(&json!({ "password": new_password }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to change password: {}", e))?;
+
+    // 422 is the server's own password policy rejecting it
+    if update_response.status() == StatusCode::UNPROCESSABLE_ENTITY {
+        let error_text = update_response.text().await.unwrap_or_default();
+        return Err(format!("New password is too weak: {}", error_text));
+    }
+    if !update_response.status().is_success() {
+        let status = update_response.status();
+        let error_text = update_response.text().await.unwrap_or_default();
+        return Err(format!("Failed to change password: {} - {}", status, error_text));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn change_my_password(
+    db: State<'_, Database>,
+    current_password: String,
+    new_password: String,
+) -> Result<(), String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let email = fetch_user_by_id(&db, &user_id)
+        .await?
+        .and_then(|user| user.email)
+        .ok_or("No email address is recorded for the current user")?;
+
+    change_password_in_db(&db, &email, &current_password, &new_password).await?;
+    log::info!("Password changed for user {}", user_id);
+    Ok(())
+}
+
+async fn request_password_reset_in_db(db: &Database, email: &str) -> Result<(), String> {
+    let email = email.trim();
+    validate_email(email)?;
+
+    let recover_url = format!("{}/auth/v1/recover", db.base_url);
+    let response = db.client
+        .post(&recover_url)
+        .header("apikey", &db.api_key)
+        .header("Content-Type", "application/json")
+        .json(&json!({ "email": email }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to request password reset: {}", e))?;
+
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err("Too many reset requests, please try again later".to_string());
+    }
+    // Otherwise answer the same way whether or not the address has an account
+    if !response.status().is_success() {
+        log::warn!("Password reset request returned {}", response.status());
+    }
+    Ok(())
+}
+
+/// Email a password reset link. Succeeds whether or not the address has an account.
+#[tauri::command]
+pub async fn request_password_reset(email: String) -> Result<(), String> {
+    let database = database_from_env()?;
+    request_password_reset_in_db(&database, &email).await
+}
+
+/// Finish a reset with the token from the emailed link and set the new password
+#[tauri::command]
+pub async fn confirm_password_reset(token: String, new_password: String) -> Result<(), String> {
+    if token.trim().is_empty() {
+        return Err("Reset token is required".to_string());
+    }
+    check_password_length(&new_password)?;
+
+    let database = database_from_env()?;
+    let verify_url = format!("{}/auth/v1/verify", database.base_url);
+    let response = database.client
+        .post(&verify_url)
+        .header("apikey", &database.api_key)
+        .header("Content-Type", "application/json")
+        .json(&json!({ "type": "recovery", "token_hash": token.trim() }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to verify reset token: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        log::warn!("Password reset token rejected: {}", status);
+        return Err("This reset link is invalid or has expired".to_string());
+    }
+
+    let session: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse auth response: {}", e))?;
+    let access_token = session["access_token"]
+        .as_str()
+        .ok_or("Auth response did not include an access token")?;
+
+    set_password_with_session(&database, access_token, &new_password).await
+}
+
+#[tauri::command]
+pub async fn get_pending_sync_count() -> Result<usize, String> {
+    Ok(crate::sync_queue::pending_count())
+}
+
 // ===== DEFAULT USER CONVENIENCE COMMANDS =====
 // Note: ensure_default_user_exists function removed to prevent automatic Dev User creation
 
@@ -1685,17 +4451,20 @@ pub async fn get_current_user_id() -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn get_my_applications(db: State<'_, Database>) -> Result<Vec<Application>, String> {
+pub async fn get_my_applications(
+    db: State<'_, Database>,
+    order_by: Option<String>,
+) -> Result<Vec<Application>, String> {
     let user_id = crate::current_user::get_current_user_id_or_error()?;
-    get_applications_by_user(db, user_id).await
+    get_applications_by_user(db, user_id, order_by).await
 }
 
 #[tauri::command]
-pub async fn get_my_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String> {
+pub async fn get_my_tasks(db: State<'_, Database>, include_archived: Option<bool>) -> Result<Vec<Task>, String> {
     // For now, get ALL tasks instead of filtering by assignee
     // This will help us test if the issue is with user assignment or task retrieval
-    let url = format!("{}/rest/v1/tasks", db.base_url);
-    println!("get_my_tasks: Getting ALL tasks from URL: {}", url);
+    let url = format!("{}/rest/v1/tasks?{}", db.base_url, archived_filter(include_archived));
+    log::debug!("get_my_tasks: Getting ALL tasks from URL: {}", url);
     
     let response = db.client
         .get(&url)
@@ -1705,23 +4474,92 @@ pub async fn get_my_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String>
         .await
         .map_err(|e| format!("Failed to get tasks: {}", e))?;
 
-    println!("get_my_tasks: Response status: {}", response.status());
+    log::debug!("get_my_tasks: Response status: {}", response.status());
 
     let tasks: Vec<Task> = response.json().await.map_err(|e| format!("Failed to parse tasks: {}", e))?;
-    println!("get_my_tasks: Found {} tasks total", tasks.len());
-    for task in &tasks {
-        println!("  Task: {} - {} - assignee: {:?} - status: {:?}", task.id, task.title, task.assignee_id, task.status);
-    }
+    log::debug!("get_my_tasks: Found {} tasks total", tasks.len());
     Ok(tasks)
 }
 
+/// A task with the name of the project it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskWithProject {
+    #[serde(flatten)]
+    pub task: Task, // workspace_id falls back to the project's when the task has none
+    pub project_name: Option<String>,
+    pub project_missing: bool, // project_id is set but the project no longer exists
+}
+
+#[derive(Deserialize)]
+struct EmbeddedProject {
+    name: String,
+    workspace_id: Option<String>,
+}
+
+// tasks row with `projects(name,workspace_id)` embedded
+#[derive(Deserialize)]
+struct TaskProjectRow {
+    #[serde(flatten)]
+    task: Task,
+    #[serde(default)]
+    projects: Option<EmbeddedProject>,
+}
+
+async fn get_tasks_with_projects_in_db(
+    db: &Database,
+    assignee_id: &str,
+    include_archived: bool,
+) -> Result<Vec<TaskWithProject>, String> {
+    let assignee_filter = format!("eq.{}", assignee_id);
+    let mut filters = vec![("assignee_id", assignee_filter.as_str()), ("order", "created_at.desc")];
+    if !include_archived {
+        filters.push(("archived_at", "is.null"));
+    }
+    let response = db
+        .execute_query_with_params(
+            "tasks",
+            "GET",
+            &filters,
+            Some("*,projects(name,workspace_id)"),
+            None,
+        )
+        .await
+        .map_err(|e| format!("Failed to get tasks: {}", e))?;
+    let rows: Vec<TaskProjectRow> = serde_json::from_value(response)
+        .map_err(|e| format!("Failed to parse tasks: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut task = row.task;
+            let project_missing = task.project_id.is_some() && row.projects.is_none();
+            let project_name = row.projects.map(|project| {
+                if task.workspace_id.is_none() {
+                    task.workspace_id = project.workspace_id;
+                }
+                project.name
+            });
+            TaskWithProject { task, project_name, project_missing }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_my_tasks_with_projects(
+    db: State<'_, Database>,
+    include_archived: Option<bool>,
+) -> Result<Vec<TaskWithProject>, String> {
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    get_tasks_with_projects_in_db(&db, &user_id, include_archived.unwrap_or(false)).await
+}
+
 #[tauri::command]
 pub async fn get_my_time_entries(
     db: State<'_, Database>,
     limit: Option<u32>,
 ) -> Result<Vec<TimeEntry>, String> {
     let user_id = crate::current_user::get_current_user_id_or_error()?;
-    get_time_entries_by_user(db, user_id, limit).await
+    get_time_entries_by_user(db, user_id, limit, None, None, None).await
 }
 
 #[tauri::command]
@@ -1741,12 +4579,28 @@ pub async fn create_my_application(
         return Err("Process name cannot be empty".to_string());
     }
     
-    println!("Creating application: {} ({})", name, process_name);
+    // An explicit category wins; otherwise store a guess the user can change later
+    let category = match category.filter(|c| !c.trim().is_empty()) {
+        Some(category) => category,
+        None => {
+            let mut suggested = crate::platform::categorize_app_name(&name);
+            if suggested == "Other" {
+                suggested = crate::platform::categorize_app_name(&process_name);
+            }
+            log::debug!("Suggested category '{}' for {}", suggested, name);
+            suggested
+        }
+    };
+
+    log::info!("Creating application: {} ({})", name, process_name);
     let user_id = crate::current_user::get_current_user_id_or_error()?;
-    create_application(db, name, process_name, user_id, icon_path, category, is_tracked).await
+    let app = create_application(db, name, process_name, user_id, icon_path, Some(category), is_tracked).await?;
+    refresh_tracked_apps().await;
+    Ok(app)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_my_application(
     db: State<'_, Database>,
     app_id: String,
@@ -1755,23 +4609,26 @@ pub async fn update_my_application(
     icon_path: Option<String>,
     category: Option<String>,
     is_tracked: Option<bool>,
+    expected_updated_at: Option<String>,
 ) -> Result<Application, String> {
-    println!("DEBUG: update_my_application called with app_id: {}, is_tracked: {:?}", app_id, is_tracked);
-    println!("DEBUG: All parameters - name: {:?}, process_name: {:?}, icon_path: {:?}, category: {:?}, is_tracked: {:?}", 
+    log::debug!("update_my_application called with app_id: {}, is_tracked: {:?}", app_id, is_tracked);
+    log::debug!("All parameters - name: {:?}, process_name: {:?}, icon_path: {:?}, category: {:?}, is_tracked: {:?}", 
              name, process_name, icon_path, category, is_tracked);
     
     // If is_tracked is being set to false, stop tracking for this app
     if let Some(false) = is_tracked {
         if let Some(tracker) = crate::tracking::get_tracker() {
             if let Err(e) = tracker.stop_tracking_for_app_by_id(&app_id).await {
-                println!("Warning: Failed to stop tracking for app {}: {}", app_id, e);
+                log::warn!("Failed to stop tracking for app {}: {}", app_id, e);
             } else {
-                println!("Stopped tracking for app {} because is_tracked was set to false", app_id);
+                log::info!("Stopped tracking for app {} because is_tracked was set to false", app_id);
             }
         }
     }
     
-    update_application(db, app_id, name, process_name, icon_path, category, is_tracked).await
+    let app = update_application(db, app_id, name, process_name, icon_path, category, is_tracked, expected_updated_at).await?;
+    refresh_tracked_apps().await;
+    Ok(app)
 }
 
 #[tauri::command]
@@ -1780,30 +4637,40 @@ pub async fn toggle_my_application_tracking(
     app_id: String,
     is_tracked: bool,
 ) -> Result<Application, String> {
-    println!("DEBUG: toggle_my_application_tracking called with app_id: {}, is_tracked: {}", app_id, is_tracked);
+    log::debug!("toggle_my_application_tracking called with app_id: {}, is_tracked: {}", app_id, is_tracked);
     
     // If is_tracked is being set to false, stop tracking for this app
     if !is_tracked {
         if let Some(tracker) = crate::tracking::get_tracker() {
             if let Err(e) = tracker.stop_tracking_for_app_by_id(&app_id).await {
-                println!("Warning: Failed to stop tracking for app {}: {}", app_id, e);
+                log::warn!("Failed to stop tracking for app {}: {}", app_id, e);
             } else {
-                println!("Stopped tracking for app {} because is_tracked was toggled to false", app_id);
+                log::info!("Stopped tracking for app {} because is_tracked was toggled to false", app_id);
             }
         }
     }
     
-    update_application(db, app_id, None, None, None, None, Some(is_tracked)).await
+    let app = update_application(db, app_id, None, None, None, None, Some(is_tracked), None).await?;
+    refresh_tracked_apps().await;
+    Ok(app)
+}
+
+// Have the tracker refetch the tracked-app list on its next tick instead of
+// waiting for its cache to expire
+async fn refresh_tracked_apps() {
+    if let Some(tracker) = crate::tracking::get_tracker() {
+        tracker.refresh_tracked_apps().await;
+    }
 }
 
 #[tauri::command]
 pub async fn delete_my_application(
     db: State<'_, Database>,
-    appId: String,
+    app_id: String,
 ) -> Result<(), String> {
-    println!("DEBUG: delete_my_application called with appId: {}", appId);
+    log::debug!("delete_my_application called with app_id: {}", app_id);
     
-    let url = format!("{}/rest/v1/applications?id=eq.{}", db.base_url, appId);
+    let url = format!("{}/rest/v1/applications?id=eq.{}", db.base_url, encode_filter_value(&app_id));
     let response = db.client
         .delete(&url)
         .header("apikey", &db.api_key)
@@ -1818,6 +4685,7 @@ pub async fn delete_my_application(
         return Err(format!("HTTP error {}: {}", status, error_text));
     }
 
+    refresh_tracked_apps().await;
     Ok(())
 }
 
@@ -1949,27 +4817,13 @@ async fn get_running_processes_fallback() -> Result<Vec<DetectedProcess>, String
     let mut seen_processes = std::collections::HashSet::new();
     let now = chrono::Utc::now().to_rfc3339();
 
-    // Background/system processes list (mostly Windows); macOS path uses NSWorkspace
-    let background_processes = [
-        "svchost.exe", "dwm.exe", "winlogon.exe", "csrss.exe", "smss.exe",
-        "wininit.exe", "services.exe", "lsass.exe", "conhost.exe",
-        "audiodg.exe", "dllhost.exe", "rundll32.exe", "taskhost.exe", "taskhostw.exe",
-        "sihost.exe", "ctfmon.exe", "WmiPrvSE.exe", "SearchIndexer.exe", "SearchProtocolHost.exe",
-        "SearchFilterHost.exe", "RuntimeBroker.exe", "Registry", "System", "Idle",
-        "Memory Compression", "Secure System", "System Interrupts", "spoolsv.exe",
-        "winlogon.exe", "csrss.exe", "smss.exe", "wininit.exe", "services.exe",
-        "lsass.exe", "audiodg.exe", "dllhost.exe", "rundll32.exe", "taskhost.exe",
-        "taskhostw.exe", "sihost.exe", "ctfmon.exe", "WmiPrvSE.exe", "SearchIndexer.exe",
-        "SearchProtocolHost.exe", "SearchFilterHost.exe", "RuntimeBroker.exe"
-    ];
-
     for (_pid, process) in system.processes() {
         let process_name = process.name();
         let exe_name = process.exe().and_then(|p| p.file_name()).unwrap_or_default();
 
         // Skip background/system processes
-        if background_processes.contains(&process_name) ||
-           background_processes.contains(&exe_name.to_string_lossy().as_ref()) ||
+        if crate::process_filter::is_ignored(process_name) ||
+           crate::process_filter::is_ignored(&exe_name.to_string_lossy()) ||
            process_name.len() < 3 ||
            process_name.starts_with('.') ||
            process_name.contains("Service") ||
@@ -1982,26 +4836,150 @@ async fn get_running_processes_fallback() -> Result<Vec<DetectedProcess>, String
             continue;
         }
 
-        if seen_processes.contains(process_name) { continue; }
-        seen_processes.insert(process_name.to_string());
+        if seen_processes.contains(process_name) { continue; }
+        seen_processes.insert(process_name.to_string());
+
+        let is_active = is_known_user_app(process_name) || is_likely_user_app(process_name, &process);
+
+        let detected_process = DetectedProcess {
+            name: crate::friendly_names::friendly_name(process_name),
+            process_name: process_name.to_string(),
+            window_title: None,
+            directory: process.exe().map(|p| p.to_string_lossy().to_string()),
+            is_active,
+            last_seen: now.clone(),
+        };
+
+        processes.push(detected_process);
+    }
+
+    processes.sort_by(|a, b| b.is_active.cmp(&a.is_active).then(a.name.cmp(&b.name)));
+    processes.truncate(30);
+    Ok(processes)
+}
+
+// Hide a noisy process from detection; returns the updated ignore list
+#[tauri::command]
+pub async fn add_ignored_process(name: String) -> Result<Vec<String>, String> {
+    crate::process_filter::add_ignored(&name)?;
+    Ok(crate::process_filter::ignored_processes())
+}
+
+// Show a previously ignored process again; returns the updated ignore list
+#[tauri::command]
+pub async fn remove_ignored_process(name: String) -> Result<Vec<String>, String> {
+    crate::process_filter::remove_ignored(&name)?;
+    Ok(crate::process_filter::ignored_processes())
+}
+
+// Effective process name -> display name mapping used for detected processes
+#[tauri::command]
+pub async fn get_friendly_names() -> Result<std::collections::BTreeMap<String, String>, String> {
+    Ok(crate::friendly_names::friendly_names())
+}
+
+// Override the display name for a process; an empty name reverts to the default
+#[tauri::command]
+pub async fn set_friendly_name(process_name: String, display_name: String) -> Result<std::collections::BTreeMap<String, String>, String> {
+    crate::friendly_names::set_friendly_name(&process_name, &display_name)?;
+    Ok(crate::friendly_names::friendly_names())
+}
+
+// The user's category rules, in the order they are checked
+#[tauri::command]
+pub async fn get_category_rules() -> Result<Vec<crate::category_rules::CategoryRule>, String> {
+    Ok(crate::category_rules::category_rules())
+}
+
+// Add a rule checked after the existing ones; returns the updated rules
+#[tauri::command]
+pub async fn add_category_rule(
+    pattern: String,
+    category: String,
+    is_regex: Option<bool>,
+) -> Result<Vec<crate::category_rules::CategoryRule>, String> {
+    crate::category_rules::add_rule(&pattern, &category, is_regex.unwrap_or(false))?;
+    Ok(crate::category_rules::category_rules())
+}
+
+// Remove the rule at a position in the list; returns the updated rules
+#[tauri::command]
+pub async fn remove_category_rule(index: usize) -> Result<Vec<crate::category_rules::CategoryRule>, String> {
+    crate::category_rules::remove_rule(index)?;
+    Ok(crate::category_rules::category_rules())
+}
+
+// Running apps the current user isn't tracking yet, foreground app first
+#[tauri::command]
+pub async fn suggest_untracked_apps(db: State<'_, Database>) -> Result<Vec<DetectedProcess>, String> {
+    use crate::platform::macos_tracker::normalize_name;
+
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let applications = get_applications_by_user(db, user_id, None).await?;
+    let known: std::collections::HashSet<String> = applications
+        .iter()
+        .flat_map(|app| [normalize_name(&app.process_name), normalize_name(&app.name)])
+        .collect();
+
+    let mut suggestions: Vec<DetectedProcess> = get_running_processes()
+        .await?
+        .into_iter()
+        .filter(|process| !known.contains(&normalize_name(&process.process_name)))
+        .collect();
 
-        let is_active = is_known_user_app(process_name) || is_likely_user_app(process_name, &process);
+    suggestions.sort_by(|a, b| b.is_active.cmp(&a.is_active));
+    Ok(suggestions)
+}
 
-        let detected_process = DetectedProcess {
-            name: get_friendly_name(process_name),
-            process_name: process_name.to_string(),
-            window_title: None,
-            directory: process.exe().map(|p| p.to_string_lossy().to_string()),
-            is_active,
-            last_seen: now.clone(),
-        };
+/// A tracked application and whether it is open, focused or being timed right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedAppState {
+    pub app: Application,
+    pub is_running: bool,
+    pub is_focused: bool,
+    pub is_active_entry: bool, // Has a running time entry
+}
 
-        processes.push(detected_process);
-    }
+fn tracked_app_states(
+    applications: Vec<Application>,
+    processes: &[DetectedProcess],
+    active_app_ids: &[String],
+) -> Vec<TrackedAppState> {
+    use crate::platform::macos_tracker::app_matches_process;
 
-    processes.sort_by(|a, b| b.is_active.cmp(&a.is_active).then(a.name.cmp(&b.name)));
-    processes.truncate(30);
-    Ok(processes)
+    applications
+        .into_iter()
+        .filter(|app| app.is_tracked)
+        .map(|app| {
+            let matching: Vec<&DetectedProcess> = processes
+                .iter()
+                .filter(|process| app_matches_process(&app, &process.name, &process.process_name))
+                .collect();
+            TrackedAppState {
+                is_running: !matching.is_empty(),
+                is_focused: matching.iter().any(|process| process.is_active),
+                is_active_entry: active_app_ids.contains(&app.id),
+                app,
+            }
+        })
+        .collect()
+}
+
+// The current user's tracked apps, each marked running, focused and/or being timed
+#[tauri::command]
+pub async fn get_tracked_app_states(db: State<'_, Database>) -> Result<Vec<TrackedAppState>, String> {
+    use crate::platform::database_helpers::DatabaseHelpers;
+
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+    let active_app_ids: Vec<String> = DatabaseHelpers::get_active_time_entries(&db)
+        .await?
+        .into_iter()
+        .filter_map(|entry| entry.app_id)
+        .collect();
+    let applications = get_applications_by_user(db, user_id, None).await?;
+    let processes = get_running_processes().await?;
+
+    Ok(tracked_app_states(applications, &processes, &active_app_ids))
 }
 
 // ===== TEAM KEY STORAGE (Prototype) =====
@@ -2020,7 +4998,7 @@ pub struct TeamKeyRecord {
 
 #[tauri::command]
 pub async fn get_team_key_record(db: State<'_, Database>, team_id: String) -> Result<Option<TeamKeyRecord>, String> {
-    let url = format!("{}/rest/v1/team_keys?team_id=eq.{}&order=created_at.desc&limit=1", db.base_url, team_id);
+    let url = format!("{}/rest/v1/team_keys?team_id=eq.{}&order=created_at.desc&limit=1", db.base_url, encode_filter_value(&team_id));
     let response = db.client
         .get(&url)
         .header("apikey", &db.api_key)
@@ -2169,124 +5147,29 @@ fn is_likely_user_app(process_name: &str, process: &sysinfo::Process) -> bool {
     criteria_met >= 3
 }
 
-fn get_friendly_name(process_name: &str) -> String {
-    let friendly_names: std::collections::HashMap<&str, &str> = [
-        ("Code.exe", "Visual Studio Code"),
-        ("chrome.exe", "Google Chrome"),
-        ("firefox.exe", "Mozilla Firefox"),
-        ("Discord.exe", "Discord"),
-        ("slack.exe", "Slack"),
-        ("notion.exe", "Notion"),
-        ("Figma.exe", "Figma"),
-        ("Photoshop.exe", "Adobe Photoshop"),
-        ("EXCEL.EXE", "Microsoft Excel"),
-        ("WINWORD.EXE", "Microsoft Word"),
-        ("POWERPNT.EXE", "Microsoft PowerPoint"),
-        ("Spotify.exe", "Spotify"),
-        ("steam.exe", "Steam"),
-        ("obs64.exe", "OBS Studio"),
-        ("Zoom.exe", "Zoom"),
-        ("Teams.exe", "Microsoft Teams"),
-        ("explorer.exe", "Windows Explorer"),
-        ("notepad.exe", "Notepad"),
-        ("calc.exe", "Calculator"),
-        ("mspaint.exe", "Paint"),
-        ("msedge.exe", "Microsoft Edge"),
-        ("brave.exe", "Brave Browser"),
-        ("opera.exe", "Opera Browser"),
-        ("thunderbird.exe", "Mozilla Thunderbird"),
-        ("OUTLOOK.EXE", "Microsoft Outlook"),
-        ("skype.exe", "Skype"),
-        ("telegram.exe", "Telegram"),
-        ("vlc.exe", "VLC Media Player"),
-        ("unity.exe", "Unity Editor"),
-        ("blender.exe", "Blender"),
-        ("autocad.exe", "AutoCAD"),
-        ("intellij64.exe", "IntelliJ IDEA"),
-        ("webstorm64.exe", "WebStorm"),
-        ("pycharm64.exe", "PyCharm"),
-        ("clion64.exe", "CLion"),
-        ("rider64.exe", "Rider"),
-        ("datagrip64.exe", "DataGrip"),
-        ("phpstorm64.exe", "PhpStorm"),
-        ("rubymine64.exe", "RubyMine"),
-        ("goland64.exe", "GoLand"),
-        ("rustrover64.exe", "RustRover"),
-        ("Cursor.exe", "Cursor"),
-        ("atom.exe", "Atom"),
-        ("sublime_text.exe", "Sublime Text"),
-        ("vim.exe", "Vim"),
-        ("emacs.exe", "Emacs"),
-        ("nvim.exe", "Neovim"),
-        ("WindowsTerminal.exe", "Windows Terminal"),
-        ("powershell.exe", "PowerShell"),
-        ("cmd.exe", "Command Prompt"),
-        ("bash.exe", "Bash"),
-        ("zsh.exe", "Zsh"),
-        ("fish.exe", "Fish"),
-        ("git.exe", "Git"),
-        ("docker.exe", "Docker"),
-        ("kubectl.exe", "Kubernetes"),
-        ("postman.exe", "Postman"),
-        ("insomnia.exe", "Insomnia"),
-        ("mongod.exe", "MongoDB"),
-        ("mysqld.exe", "MySQL"),
-        ("postgres.exe", "PostgreSQL"),
-        ("redis-server.exe", "Redis"),
-        ("elasticsearch.exe", "Elasticsearch"),
-        ("node.exe", "Node.js"),
-        ("npm.exe", "npm"),
-        ("yarn.exe", "Yarn"),
-        ("pnpm.exe", "pnpm"),
-        ("python.exe", "Python"),
-        ("java.exe", "Java"),
-        ("go.exe", "Go"),
-        ("cargo.exe", "Rust"),
-    ].iter().cloned().collect();
-    
-    friendly_names.get(process_name).map(|s| s.to_string())
-        .unwrap_or_else(|| {
-            // Convert process name to friendly format
-            process_name
-                .split('.')
-                .next()
-                .unwrap_or(process_name)
-                .split('_')
-                .map(|s| {
-                    let mut chars = s.chars();
-                    match chars.next() {
-                        None => String::new(),
-                        Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(" ")
-        })
-}
-
 #[tauri::command]
 pub async fn logout_user() -> Result<bool, String> {
-    println!("🚪 logout_user() called - starting cleanup process");
+    log::debug!("logout_user() called - starting cleanup process");
     
     // Stop tracking if tracker exists
     if let Some(tracker) = crate::tracking::get_tracker() {
-        println!("🎯 Tracker found, attempting to stop tracking...");
+        log::info!("Tracker found, attempting to stop tracking...");
         if let Err(e) = tracker.stop_tracking().await {
             log::error!("Error stopping tracking during logout: {}", e);
-            println!("❌ Error stopping tracking: {}", e);
         } else {
             log::info!("Activity tracking stopped during logout");
-            println!("✅ Activity tracking stopped successfully");
         }
     } else {
-        println!("⚠️ No tracker found - nothing to stop");
+        log::warn!("No tracker found - nothing to stop");
     }
 
+    stop_connection_watcher();
+
     // Clear runtime current user id
     crate::current_user::clear_current_user_id();
-    println!("🧹 Current user cleared from memory");
+    log::info!("Current user cleared from memory");
 
-    println!("🎉 logout_user() completed successfully");
+    log::info!("logout_user() completed successfully");
     Ok(true)
 }
 
@@ -2299,17 +5182,40 @@ pub async fn ai_chat(
     conversation_history: Vec<ai_assistant::ChatMessage>,
     workspace_id: Option<String>,
 ) -> Result<crate::ai::AIResponse, String> {
-    use crate::ai::{AIService, GeminiService, ChatMessage as AIChatMessage};
+    // Get productivity insights as context; continue without them if the fetch fails
+    let productivity_insights = get_productivity_insights_for_context(db.clone()).await.ok();
 
-    // Get productivity insights as context
-    let insights = match get_productivity_insights_for_context(db.clone()).await {
-        Ok(insights) => format_productivity_context(&insights),
-        Err(_) => String::new(), // Continue without context if fetch fails
-    };
+    let ai_service = crate::ai::create_ai_service().map_err(|e| {
+        log::error!("Failed to initialize AI service: {}", e);
+        e.into_user_message()
+    })?;
+
+    ai_chat_with_service(
+        &db,
+        ai_service.as_ref(),
+        message,
+        conversation_history,
+        workspace_id,
+        productivity_insights,
+    )
+    .await
+}
+
+// The body of ai_chat, with the AI service passed in so tests can use a mock
+async fn ai_chat_with_service(
+    db: &Database,
+    ai_service: &dyn crate::ai::AIService,
+    message: String,
+    conversation_history: Vec<ai_assistant::ChatMessage>,
+    workspace_id: Option<String>,
+    productivity_insights: Option<ProductivityInsights>,
+) -> Result<crate::ai::AIResponse, String> {
+    use crate::ai::ChatMessage as AIChatMessage;
 
-    // Initialize AI service (Gemini)
-    let ai_service = GeminiService::new()
-        .map_err(|e| format!("Failed to initialize AI service: {}", e))?;
+    let insights = match &productivity_insights {
+        Some(insights) => format_productivity_context(insights),
+        None => String::new(),
+    };
 
     // Build messages with system prompt
     let mut messages = vec![
@@ -2327,6 +5233,37 @@ pub async fn ai_chat(
         });
     }
 
+    // Map @mentions to real member ids; ask the user instead when one can't be pinned down
+    let mentions = match workspace_id.as_deref() {
+        Some(workspace_id) if !ai_assistant::use_mock_data() => {
+            match ai_assistant::resolve_mentions(db, workspace_id, &message).await {
+                Ok(ai_assistant::Mentions::Resolved(mentions)) => mentions,
+                Ok(ai_assistant::Mentions::NeedsClarification(question)) => {
+                    return Ok(crate::ai::AIResponse {
+                        content: question,
+                        usage: None,
+                        tools: None,
+                    });
+                }
+                Err(e) => {
+                    log::warn!("Could not resolve mentions, continuing without them: {}", e);
+                    Vec::new()
+                }
+            }
+        }
+        _ => Vec::new(),
+    };
+    if !mentions.is_empty() {
+        let lines: Vec<String> = mentions
+            .iter()
+            .map(|mention| format!("@{} is {} (member_id: {})", mention.token, mention.user.name, mention.user.id))
+            .collect();
+        messages.push(AIChatMessage {
+            role: "system".to_string(),
+            content: format!("Members mentioned in this message:\n{}", lines.join("\n")),
+        });
+    }
+
     // Convert conversation history to AI ChatMessage format
     let ai_conversation_history: Vec<AIChatMessage> = conversation_history
         .into_iter()
@@ -2349,19 +5286,26 @@ pub async fn ai_chat(
     let mut response = ai_service
         .chat(messages)
         .await
-        .map_err(|e| format!("AI service error: {}", e))?;
+        .map_err(|e| {
+            log::error!("AI service error: {}", e);
+            e.into_user_message()
+        })?;
 
     // If the AI called tools, execute them and replace the tool calls with structured data
     if let Some(ref tool_calls) = response.tools {
         let mut executed_tools = Vec::new();
 
         for tool_call in tool_calls {
-            // Use async execution with database access for real data
-            let executed_data = ai_assistant::execute_tool_async(
+            let mut arguments = tool_call.arguments.clone();
+            ai_assistant::apply_resolved_mentions(&mut arguments, &mentions);
+
+            // Real data from the database, or mock data when MACRO_USE_MOCK_DATA is set
+            let executed_data = ai_assistant::dispatch_tool(
                 &tool_call.name, 
-                &tool_call.arguments,
-                &db,
-                workspace_id.as_deref()
+                &arguments,
+                db,
+                workspace_id.as_deref(),
+                productivity_insights.as_ref(),
             ).await.unwrap_or_else(|| serde_json::json!({}));
 
             // Create a new tool call with the executed data
@@ -2381,7 +5325,7 @@ pub async fn ai_chat(
 
 async fn get_productivity_insights_for_context(db: State<'_, Database>) -> Result<ProductivityInsights, String> {
     // Use real database data instead of mock data
-    ai_assistant::get_productivity_insights(db).await
+    ai_assistant::get_productivity_insights(db, None).await
 }
 
 fn format_productivity_context(insights: &ProductivityInsights) -> String {
@@ -2455,3 +5399,1305 @@ fn format_productivity_context(insights: &ProductivityInsights) -> String {
 
     context
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // Serve a single canned HTTP response and return the server's base URL
+    async fn mock_server(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 8192];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn fetch_page_reads_total_from_content_range() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/projects"))
+            .and(header("Prefer", "count=exact"))
+            .and(query_param("limit", "2"))
+            .and(query_param("offset", "4"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("Content-Range", "4-5/4213")
+                    .set_body_json(json!([
+                        { "id": "project-5", "name": "E" },
+                        { "id": "project-6", "name": "F" }
+                    ])),
+            )
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let page: Page<Project> = fetch_page(&db, "projects", &[("order", "name.asc")], Some(2), Some(4))
+            .await
+            .unwrap();
+
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.total, 4213);
+    }
+
+    #[test]
+    fn parses_content_range_counts() {
+        assert_eq!(parse_content_range_count("0-4/5"), Some(5));
+        assert_eq!(parse_content_range_count("*/0"), Some(0));
+        assert_eq!(parse_content_range_count("0-2/*"), Some(3));
+        assert_eq!(parse_content_range_count("garbage"), None);
+    }
+
+    #[tokio::test]
+    async fn reassign_tasks_returns_updated_row_count() {
+        let base_url = mock_server(
+            "HTTP/1.1 204 No Content\r\nContent-Range: 0-2/3\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let db = Database::new(base_url, "test-key".to_string()).unwrap();
+
+        let count = reassign_tasks_in_db(&db, "user-a", "user-b", Some("project-1"))
+            .await
+            .unwrap();
+
+        assert_eq!(count, 3);
+    }
+
+    #[tokio::test]
+    async fn reassign_tasks_leaves_completed_tasks_alone() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/tasks"))
+            .and(query_param("assignee_id", "eq.user-a"))
+            .and(query_param("status", "neq.done"))
+            .respond_with(ResponseTemplate::new(204).insert_header("Content-Range", "0-1/2"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let count = reassign_tasks_in_db(&db, "user-a", "user-b", None).await.unwrap();
+
+        assert_eq!(count, 2);
+    }
+
+    fn task_row(id: &str, title: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "title": title,
+            "description": null,
+            "project_id": null,
+            "workspace_id": "ws-1",
+            "assignee_id": null,
+            "status": "in_progress",
+            "priority": "high",
+            "due_date": null,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z"
+        })
+    }
+
+    fn time_entry_row(id: &str, end_time: Option<&str>, duration_seconds: Option<i64>) -> serde_json::Value {
+        json!({
+            "id": id,
+            "user_id": "user-1",
+            "app_id": "app-1",
+            "task_id": null,
+            "start_time": "2024-01-01T09:00:00Z",
+            "end_time": end_time,
+            "duration_seconds": duration_seconds,
+            "is_active": end_time.is_none(),
+            "created_at": "2024-01-01T09:00:00Z",
+            "updated_at": "2024-01-01T09:00:00Z"
+        })
+    }
+
+    #[tokio::test]
+    async fn tasks_with_projects_tell_missing_projects_from_none() {
+        let server = MockServer::start().await;
+        let mut with_project = task_row("t1", "Write docs");
+        with_project["project_id"] = json!("p1");
+        with_project["workspace_id"] = serde_json::Value::Null;
+        with_project["projects"] = json!({ "name": "Website", "workspace_id": "ws-2" });
+        let mut deleted_project = task_row("t2", "Old work");
+        deleted_project["project_id"] = json!("p-gone");
+        deleted_project["projects"] = serde_json::Value::Null;
+        let mut no_project = task_row("t3", "Inbox");
+        no_project["projects"] = serde_json::Value::Null;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/tasks"))
+            .and(query_param("assignee_id", "eq.user-1"))
+            .and(query_param("select", "*,projects(name,workspace_id)"))
+            .and(query_param("archived_at", "is.null"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([with_project, deleted_project, no_project])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let tasks = get_tasks_with_projects_in_db(&db, "user-1", false).await.unwrap();
+
+        assert_eq!(tasks[0].project_name.as_deref(), Some("Website"));
+        assert_eq!(tasks[0].task.workspace_id.as_deref(), Some("ws-2"));
+        assert!(tasks[1].project_missing);
+        assert!(tasks[2].project_name.is_none() && !tasks[2].project_missing);
+    }
+
+    #[test]
+    fn tracked_app_states_match_bundle_ids_and_skip_untracked_apps() {
+        let app = |id: &str, name: &str, process_name: &str, is_tracked: bool| -> Application {
+            serde_json::from_value(json!({
+                "id": id, "name": name, "process_name": process_name, "icon_path": null, "category": null,
+                "is_tracked": is_tracked, "user_id": "user-1", "created_at": null, "updated_at": null, "last_used": null
+            }))
+            .unwrap()
+        };
+        let process = |name: &str, process_name: &str, is_active: bool| DetectedProcess {
+            name: name.to_string(),
+            process_name: process_name.to_string(),
+            window_title: None,
+            directory: None,
+            is_active,
+            last_seen: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let applications = vec![
+            app("a1", "Chrome", "chrome", true),
+            app("a2", "Slack", "Slack.exe", true),
+            app("a3", "Notion", "Notion", true),
+            app("a4", "Steam", "steam.exe", false),
+        ];
+        let processes = vec![
+            process("Google Chrome", "com.google.Chrome", true),
+            process("Slack", "Slack.exe", false),
+            process("Steam", "steam.exe", false),
+        ];
+
+        let states = tracked_app_states(applications, &processes, &["a2".to_string()]);
+
+        let summary: Vec<(&str, bool, bool, bool)> = states
+            .iter()
+            .map(|state| (state.app.id.as_str(), state.is_running, state.is_focused, state.is_active_entry))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("a1", true, true, false), ("a2", true, false, true), ("a3", false, false, false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_activity_feed_merges_entries_and_completed_tasks() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("workspace_members.workspace_id", "eq.ws-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "user-1", "name": "Ada", "email": null, "created_at": null, "updated_at": null, "image_url": null, "workspace_members": [] }
+            ])))
+            .mount(&server)
+            .await;
+        let mut entry = time_entry_row("entry-1", Some("2024-01-01T10:00:00Z"), Some(3600));
+        entry["applications"] = json!({ "name": "VS Code", "category": null, "icon_path": null });
+        entry["tasks"] = serde_json::Value::Null;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("user_id", "in.(user-1)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([entry])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let mut done = task_row("task-1", "Ship it");
+        done["status"] = json!("done");
+        done["assignee_id"] = json!("user-1");
+        done["updated_at"] = json!("2024-01-01T09:30:00Z");
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/tasks"))
+            .and(query_param("status", "eq.done"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([done])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let feed = get_workspace_activity_feed_in_db(&db, "ws-1", 2).await.unwrap();
+
+        let summary: Vec<(ActivityKind, &str, &str)> = feed
+            .iter()
+            .map(|event| (event.kind, event.actor_name.as_str(), event.subject.as_str()))
+            .collect();
+        // The entry started at 09:00 falls outside the limit of two
+        assert_eq!(
+            summary,
+            vec![(ActivityKind::EntryEnded, "Ada", "VS Code"), (ActivityKind::TaskCompleted, "Ada", "Ship it")]
+        );
+    }
+
+    #[tokio::test]
+    async fn archiving_a_task_sets_and_clears_archived_at() {
+        let server = MockServer::start().await;
+        let mut archived = task_row("task-1", "Old work");
+        archived["archived_at"] = json!("2024-01-02T00:00:00Z");
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/tasks"))
+            .and(query_param("id", "eq.task-1"))
+            .and(|request: &wiremock::Request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                body["archived_at"].is_string()
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([archived])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/tasks"))
+            .and(query_param("id", "eq.task-1"))
+            .and(body_partial_json(json!({ "archived_at": null })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([task_row("task-1", "Old work")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let task = set_task_archived_in_db(&db, "task-1", true).await.unwrap();
+        assert!(task.archived_at.is_some());
+
+        let task = set_task_archived_in_db(&db, "task-1", false).await.unwrap();
+        assert!(task.archived_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn split_time_entry_returns_both_halves() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                time_entry_row("entry-1", Some("2024-01-01T11:00:00Z"), Some(7200))
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/time_entries"))
+            .and(body_partial_json(json!({
+                "app_id": "app-1",
+                "start_time": "2024-01-01T10:00:00+00:00",
+                "end_time": "2024-01-01T11:00:00+00:00",
+                "duration_seconds": 3600,
+                "is_active": false
+            })))
+            .respond_with(|request: &wiremock::Request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                ResponseTemplate::new(201).set_body_json(json!([body]))
+            })
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .and(body_partial_json(json!({ "end_time": "2024-01-01T10:00:00+00:00", "duration_seconds": 3600 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                time_entry_row("entry-1", Some("2024-01-01T10:00:00Z"), Some(3600))
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let at = chrono::DateTime::parse_from_rfc3339("2024-01-01T10:00:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        let (first, second) = split_time_entry_in_db(&db, "entry-1", at).await.unwrap();
+
+        assert_eq!(first.end_time, Some(at));
+        assert_eq!(second.start_time, at);
+        assert_eq!(second.duration_seconds, Some(3600));
+        assert_ne!(second.id, first.id);
+    }
+
+    #[tokio::test]
+    async fn split_time_entry_rejects_the_active_entry() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([time_entry_row("entry-1", None, None)])))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let at = chrono::DateTime::parse_from_rfc3339("2024-01-01T10:00:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        let err = split_time_entry_in_db(&db, "entry-1", at).await.unwrap_err();
+
+        assert!(err.contains("active"));
+    }
+
+    #[tokio::test]
+    async fn password_reset_request_does_not_reveal_unknown_emails() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/recover"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({ "msg": "User not found" })))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        assert!(request_password_reset_in_db(&db, "nobody@example.com").await.is_ok());
+        assert_eq!(
+            request_password_reset_in_db(&db, "not-an-email").await.unwrap_err(),
+            "Invalid email format"
+        );
+    }
+
+    #[tokio::test]
+    async fn change_password_reports_a_wrong_current_password() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/token"))
+            .and(query_param("grant_type", "password"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "error": "invalid_grant" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/auth/v1/user"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let wrong = change_password_in_db(&db, "a@example.com", "not-it", "new-secret").await;
+        let weak = change_password_in_db(&db, "a@example.com", "old-secret", "abc").await;
+
+        assert_eq!(wrong.unwrap_err(), "Current password is incorrect");
+        assert!(weak.unwrap_err().starts_with("New password is too weak"));
+    }
+
+    #[tokio::test]
+    async fn optional_row_only_treats_missing_rows_as_none() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/projects"))
+            .and(query_param("id", "eq.missing"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/projects"))
+            .and(query_param("id", "eq.gone"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/projects"))
+            .and(query_param("id", "eq.broken"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("database is down"))
+            .mount(&server)
+            .await;
+        let client = reqwest::Client::new();
+        let fetch = |id: &str| client.get(format!("{}/rest/v1/projects?id=eq.{}", server.uri(), id)).send();
+
+        let missing: Option<Project> = optional_row(fetch("missing").await.unwrap(), "project").await.unwrap();
+        let gone: Option<Project> = optional_row(fetch("gone").await.unwrap(), "project").await.unwrap();
+        let broken = optional_row::<Project>(fetch("broken").await.unwrap(), "project").await;
+
+        assert!(missing.is_none());
+        assert!(gone.is_none());
+        assert!(broken.unwrap_err().contains("database is down"));
+    }
+
+    #[tokio::test]
+    async fn get_active_entry_measures_elapsed_time_from_start() {
+        let server = MockServer::start().await;
+        let mut row = time_entry_row("e1", None, None);
+        row["applications"] = json!({ "name": "Code", "category": "Development", "icon_path": null });
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("user_id", "eq.user-1"))
+            .and(query_param("end_time", "is.null"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([row])))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let at = chrono::DateTime::parse_from_rfc3339("2024-01-01T09:25:30Z").unwrap().with_timezone(&chrono::Utc);
+
+        let active = get_active_entry_in_db(&db, "user-1", at).await.unwrap().unwrap();
+
+        assert_eq!(active.entry.id, "e1");
+        assert_eq!(active.app_name.as_deref(), Some("Code"));
+        assert_eq!(active.app_category.as_deref(), Some("Development"));
+        assert_eq!(active.elapsed_seconds, 1530);
+    }
+
+    #[tokio::test]
+    async fn recent_sessions_resolve_names_and_mark_the_running_one() {
+        let server = MockServer::start().await;
+        let mut running = time_entry_row("e2", None, None);
+        running["start_time"] = json!("2024-01-01T10:00:00Z");
+        running["applications"] = json!({ "name": "Slack", "category": null, "icon_path": null });
+        let mut finished = time_entry_row("e1", Some("2024-01-01T09:45:00Z"), Some(2700));
+        finished["task_id"] = json!("task-1");
+        finished["applications"] = json!({ "name": "Code", "category": "Development", "icon_path": null });
+        finished["tasks"] = json!({ "title": "Write tests" });
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("user_id", "eq.user-1"))
+            .and(query_param("order", "start_time.desc"))
+            .and(query_param("limit", "2"))
+            .and(query_param("select", "*,applications(name,category,icon_path),tasks(title)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([running, finished])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let at = chrono::DateTime::parse_from_rfc3339("2024-01-01T10:05:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        let sessions = get_recent_sessions_in_db(&db, "user-1", 2, at).await.unwrap();
+
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions[0].is_active);
+        assert_eq!(sessions[0].end, None);
+        assert_eq!(sessions[0].duration_seconds, 300);
+        assert_eq!(sessions[1].app_name.as_deref(), Some("Code"));
+        assert_eq!(sessions[1].task_title.as_deref(), Some("Write tests"));
+        assert_eq!(sessions[1].duration_seconds, 2700);
+        let start = chrono::DateTime::parse_from_rfc3339(&sessions[1].start).unwrap();
+        assert_eq!(start.with_timezone(&chrono::Utc).to_rfc3339(), "2024-01-01T09:00:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn fetch_users_with_memberships_sends_select_and_filters() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("select", USER_SELECT_WITH_MEMBERS))
+            .and(query_param("id", "eq.user-1"))
+            .and(header("apikey", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "user-1",
+                "name": "Ada",
+                "email": "ada@example.com",
+                "created_at": null,
+                "updated_at": null,
+                "image_url": null,
+                "workspace_members": [{ "role": "manager", "workspace_id": "ws-1", "user_id": "user-1" }]
+            }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let users = fetch_users_with_memberships(&db, USER_SELECT_WITH_MEMBERS, &[("id", "eq.user-1".to_string())])
+            .await
+            .unwrap();
+
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].role.as_deref(), Some("manager"));
+        assert_eq!(users[0].workspace_id.as_deref(), Some("ws-1"));
+        assert_eq!(users[0].team_id.as_deref(), Some("ws-1"));
+    }
+
+    #[tokio::test]
+    async fn fetch_user_by_email_encodes_the_address() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("select", USER_SELECT_WITH_MEMBERS))
+            .and(query_param("email", "eq.ada+work@example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "user-1",
+                "name": "Ada",
+                "email": "ada+work@example.com",
+                "created_at": null,
+                "updated_at": null,
+                "image_url": null,
+                "workspace_members": [{ "role": "manager", "workspace_id": "ws-1", "user_id": "user-1" }]
+            }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let user = fetch_user_by_email(&db, " ada+work@example.com ").await.unwrap().unwrap();
+
+        assert_eq!(user.id, "user-1");
+        // The membership comes back with the user, not just the users row
+        assert_eq!(user.role.as_deref(), Some("manager"));
+        assert_eq!(user.workspace_id.as_deref(), Some("ws-1"));
+    }
+
+    #[tokio::test]
+    async fn get_user_avatar_returns_a_cached_data_uri() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("id", "eq.avatar-user"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "avatar-user",
+                "name": "Ada",
+                "email": "ada@example.com",
+                "created_at": null,
+                "updated_at": "2024-03-01T00:00:00Z",
+                "image_url": "/storage/v1/object/avatars/ada.png",
+                "workspace_members": []
+            }])))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/object/avatars/ada.png"))
+            .and(header("apikey", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(b"png".to_vec(), "image/png"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let first = get_user_avatar_in_db(&db, "avatar-user").await.unwrap();
+        let second = get_user_avatar_in_db(&db, "avatar-user").await.unwrap();
+
+        assert_eq!(first.as_deref(), Some("data:image/png;base64,cG5n"));
+        assert_eq!(second, first);
+    }
+
+    #[tokio::test]
+    async fn create_task_posts_payload_and_returns_first_row() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/tasks"))
+            .and(body_partial_json(json!({
+                "title": "Write tests",
+                "workspace_id": "ws-1",
+                "status": "in_progress",
+                "priority": "high"
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!([task_row("task-1", "Write tests")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspaces"))
+            .and(query_param("id", "in.(ws-1)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "id": "ws-1" }])))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let task = create_task_in_db(
+            &db,
+            "Write tests".to_string(),
+            Some("ws-1".to_string()),
+            None,
+            None,
+            Some("in_progress".to_string()),
+            Some("high".to_string()),
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(task.id, "task-1");
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert_eq!(task.priority, Some(TaskPriority::High));
+    }
+
+    #[tokio::test]
+    async fn create_task_rejects_unknown_status() {
+        let db = Database::new("http://127.0.0.1:9".to_string(), "test-key".to_string()).unwrap();
+
+        let err = create_task_in_db(&db, "Task".to_string(), None, None, None, Some("blocked".to_string()), None, None, true)
+            .await
+            .unwrap_err();
+
+        assert!(err.contains("'todo', 'in_progress', 'done'"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn create_task_rejects_a_nonexistent_assignee() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("id", "in.(user-1,user-typo)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "id": "user-1" }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/tasks"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = create_task_in_db(
+            &db,
+            "Task".to_string(),
+            None,
+            Some(vec!["user-1".to_string(), "user-typo".to_string()]),
+            None,
+            None,
+            None,
+            None,
+            true,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err, "Assigned users do not exist: user-typo");
+    }
+
+    #[tokio::test]
+    async fn update_time_entry_calculates_duration_from_start_time() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([time_entry_row("entry-1", None, None)])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .and(body_partial_json(json!({
+                "end_time": "2024-01-01T10:30:00Z",
+                "duration_seconds": 5400
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                time_entry_row("entry-1", Some("2024-01-01T10:30:00Z"), Some(5400))
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let entry = update_time_entry_in_db(&db, "entry-1".to_string(), Some("2024-01-01T10:30:00Z".to_string()), None, None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(entry.duration_seconds, Some(5400));
+    }
+
+    #[tokio::test]
+    async fn backfill_uses_the_workspace_held_when_the_entry_started() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspace_members"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "user_id": "user-1", "workspace_id": "ws-old", "role": "member", "joined_at": "2024-01-01T00:00:00Z" },
+                { "user_id": "user-1", "workspace_id": "ws-new", "role": "member", "joined_at": "2024-06-01T00:00:00Z" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("workspace_id", "is.null"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "before-joining", "start_time": "2023-12-01T09:00:00Z" },
+                { "id": "spring", "start_time": "2024-03-01T09:00:00Z" },
+                { "id": "summer", "start_time": "2024-07-01T09:00:00Z" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "in.(spring)"))
+            .and(body_partial_json(json!({ "workspace_id": "ws-old" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "in.(summer)"))
+            .and(body_partial_json(json!({ "workspace_id": "ws-new" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let updated = backfill_time_entry_workspace_in_db(&db, "user-1").await.unwrap();
+
+        assert_eq!(updated, 2);
+    }
+
+    #[tokio::test]
+    async fn update_time_entry_rejects_another_users_app() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                time_entry_row("entry-1", Some("2024-01-01T10:30:00Z"), Some(5400))
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/applications"))
+            .and(query_param("id", "eq.app-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "app-2",
+                "name": "Slack",
+                "process_name": "slack.exe",
+                "is_tracked": true,
+                "user_id": "user-2"
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = update_time_entry_in_db(&db, "entry-1".to_string(), None, None, None, Some("app-2".to_string()), None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, "Application not found");
+    }
+
+    #[tokio::test]
+    async fn a_failed_task_lookup_is_an_error_not_a_missing_task() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/tasks"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = fetch_task_by_id(&db, "task-1").await.unwrap_err();
+
+        assert!(err.starts_with("Failed to fetch task: 500"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn update_time_entry_fails_when_the_entry_cannot_be_read() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = update_time_entry_in_db(&db, "entry-1".to_string(), None, None, None, Some("app-2".to_string()), None)
+            .await
+            .unwrap_err();
+
+        assert!(err.starts_with("Failed to fetch time entry: 503"), "{}", err);
+    }
+
+    // An open invitation to ws-1 for ada@example.com, with `user-1` signed up as `email`
+    async fn invitation_server(email: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/invitations"))
+            .and(query_param("token", "eq.tok+en"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "inv-1",
+                "workspace_id": "ws-1",
+                "email": "ada@example.com",
+                "role": "member",
+                "token": "tok+en",
+                "expires_at": (chrono::Utc::now() + chrono::Duration::days(1)).to_rfc3339(),
+                "created_at": null
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "user-1",
+                "name": "Ada",
+                "email": email,
+                "created_at": null,
+                "updated_at": null,
+                "image_url": null,
+                "workspace_members": []
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/invitations"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn an_invitation_cannot_be_accepted_by_another_address() {
+        let server = invitation_server("mallory@example.com").await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = accept_invitation_in_db(&db, "user-1", " tok+en ").await.unwrap_err();
+
+        assert_eq!(err, "This invitation was sent to a different email address");
+    }
+
+    #[tokio::test]
+    async fn an_invitation_is_not_accepted_twice_into_one_workspace() {
+        let server = invitation_server("Ada@Example.com").await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspace_members"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([membership_row("member-1", "ws-1", "member")])))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = accept_invitation_in_db(&db, "user-1", "tok+en").await.unwrap_err();
+
+        assert_eq!(err, "You are already a member of this workspace");
+    }
+
+    fn membership_row(id: &str, workspace_id: &str, role: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "user_id": "user-1",
+            "workspace_id": workspace_id,
+            "role": role,
+            "joined_at": "2024-01-01T00:00:00Z"
+        })
+    }
+
+    async fn mount_memberships(server: &MockServer, rows: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspace_members"))
+            .and(query_param("user_id", "eq.user-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(rows))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn transfer_user_workspace_patches_only_the_source_membership() {
+        let server = MockServer::start().await;
+        mount_memberships(
+            &server,
+            json!([membership_row("m-1", "ws-a", "owner"), membership_row("m-2", "ws-b", "member")]),
+        )
+        .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/workspace_members"))
+            .and(query_param("user_id", "eq.user-1"))
+            .and(query_param("workspace_id", "eq.ws-b"))
+            .and(body_partial_json(json!({ "workspace_id": "ws-c", "role": "manager" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([membership_row("m-2", "ws-c", "manager")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let record = transfer_user_workspace_in_db(&db, "user-1", "ws-b", "ws-c", Some(UserRole::Manager), false)
+            .await
+            .unwrap();
+
+        assert_eq!(record.id.as_deref(), Some("m-2"));
+        assert_eq!(record.workspace_id.as_deref(), Some("ws-c"));
+
+        // joined_at is preserved unless a reset is requested
+        let requests = server.received_requests().await.unwrap();
+        let patch = requests.iter().find(|request| request.method.as_str() == "PATCH").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&patch.body).unwrap();
+        assert!(body.get("joined_at").is_none());
+    }
+
+    #[tokio::test]
+    async fn transfer_user_workspace_resets_joined_at_when_asked() {
+        let server = MockServer::start().await;
+        mount_memberships(&server, json!([membership_row("m-1", "ws-a", "member")])).await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/workspace_members"))
+            .and(query_param("workspace_id", "eq.ws-a"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([membership_row("m-1", "ws-b", "member")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        transfer_user_workspace_in_db(&db, "user-1", "ws-a", "ws-b", None, true)
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let patch = requests.iter().find(|request| request.method.as_str() == "PATCH").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&patch.body).unwrap();
+        assert!(body.get("joined_at").is_some());
+        assert!(body.get("role").is_none());
+    }
+
+    #[tokio::test]
+    async fn transfer_user_workspace_rejects_existing_destination_membership() {
+        let server = MockServer::start().await;
+        mount_memberships(
+            &server,
+            json!([membership_row("m-1", "ws-a", "member"), membership_row("m-2", "ws-b", "member")]),
+        )
+        .await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = transfer_user_workspace_in_db(&db, "user-1", "ws-a", "ws-b", None, false)
+            .await
+            .unwrap_err();
+
+        assert!(err.contains("already a member"));
+    }
+
+    #[tokio::test]
+    async fn transfer_user_workspace_requires_source_membership() {
+        let server = MockServer::start().await;
+        mount_memberships(&server, json!([membership_row("m-1", "ws-a", "member")])).await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = transfer_user_workspace_in_db(&db, "user-1", "ws-x", "ws-b", None, false)
+            .await
+            .unwrap_err();
+
+        assert!(err.contains("not a member of the source"));
+    }
+
+    #[test]
+    fn parses_the_row_create_team_writes() {
+        let row = new_team_row("Design", "user-1");
+
+        let team: Team = serde_json::from_value(row.clone()).unwrap();
+
+        assert_eq!(team.id, row["id"].as_str().unwrap());
+        assert_eq!(team.team_name, "Design");
+        assert!(team.created_at.is_some());
+        assert_eq!(team.created_by.as_deref(), Some("user-1"));
+    }
+
+    #[test]
+    fn team_name_survives_a_round_trip() {
+        let team: Team = serde_json::from_value(new_team_row("Design", "user-1")).unwrap();
+
+        let serialized = serde_json::to_value(&team).unwrap();
+        assert_eq!(serialized["team_name"], "Design");
+
+        // The frontend sends back what it received
+        let reparsed: Team = serde_json::from_value(serialized).unwrap();
+        assert_eq!(reparsed.team_name, "Design");
+    }
+
+    #[tokio::test]
+    async fn create_team_adds_the_creator_as_owner() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/workspaces"))
+            .and(body_partial_json(json!({ "name": "Design", "created_by": "user-1" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+                { "id": "ws-1", "name": "Design", "created_by": "user-1" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/workspace_members"))
+            .and(body_partial_json(json!({ "user_id": "user-1", "workspace_id": "ws-1", "role": "owner" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let team = create_team_in_db(&db, "Design", "user-1").await.unwrap();
+
+        assert_eq!(team.id, "ws-1");
+        assert_eq!(team.created_by.as_deref(), Some("user-1"));
+    }
+
+    #[tokio::test]
+    async fn a_created_team_is_listed_by_its_creator_even_without_membership() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/workspaces"))
+            .and(body_partial_json(json!({ "created_by": "user-1" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+                { "id": "ws-1", "name": "Design", "created_by": "user-1" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/workspace_members"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        // The membership read lags behind, so only the created_by branch can find the team
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspace_members"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspaces"))
+            .and(query_param("created_by", "eq.user-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "ws-1", "name": "Design", "created_by": "user-1" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let team = create_team_in_db(&db, "Design", "user-1").await.unwrap();
+        let workspaces = fetch_workspaces_for_user(&db, "user-1").await.unwrap();
+
+        assert_eq!(workspaces.iter().map(|w| w.id.as_str()).collect::<Vec<_>>(), vec![team.id.as_str()]);
+    }
+
+    #[tokio::test]
+    async fn create_team_deletes_the_workspace_when_membership_fails() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/workspaces"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+                { "id": "ws-1", "name": "Design", "created_by": "user-1" }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/workspace_members"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("denied"))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/rest/v1/workspaces"))
+            .and(query_param("id", "eq.ws-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = create_team_in_db(&db, "Design", "user-1").await.unwrap_err();
+
+        assert!(err.contains("Failed to add you to the new team"));
+    }
+
+    #[test]
+    fn parses_the_row_create_project_writes() {
+        let project: Project = serde_json::from_value(new_project_row("Website", "team-1", "user-1", None)).unwrap();
+
+        assert_eq!(project.name, "Website");
+        assert_eq!(project.workspace_id.as_deref(), Some("team-1"));
+        assert_eq!(project.manager_id.as_deref(), Some("user-1"));
+        assert!(project.description.is_none());
+    }
+
+    #[test]
+    fn parses_projects_that_use_team_id() {
+        let project: Project = serde_json::from_value(json!({
+            "id": "project-1",
+            "name": "Frontend App",
+            "team_id": "team-1"
+        }))
+        .unwrap();
+
+        assert_eq!(project.workspace_id.as_deref(), Some("team-1"));
+    }
+
+    #[test]
+    fn parses_time_entry_rows_without_defaulted_columns() {
+        let entry: TimeEntry = serde_json::from_value(json!({
+            "id": "entry-1",
+            "user_id": "user-1",
+            "start_time": "2024-01-01T09:00:00Z",
+            "end_time": null,
+            "created_at": null
+        }))
+        .unwrap();
+
+        assert!(!entry.is_active);
+        assert!(entry.app_id.is_none());
+        assert!(entry.created_at.is_none());
+        assert!(entry.updated_at.is_none());
+    }
+
+    #[test]
+    fn encodes_filter_values() {
+        assert_eq!(encode_filter_value("a+b@example.com"), "a%2Bb%40example.com");
+        assert_eq!(encode_filter_value("id&role=eq.owner"), "id%26role%3Deq.owner");
+    }
+
+    #[tokio::test]
+    async fn fetch_users_by_email_escapes_plus_sign() {
+        let server = MockServer::start().await;
+        // wiremock decodes the query, so this only matches if "+" was sent as %2B rather than a space
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("email", "eq.a+b@example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "user-1",
+                "name": "A B",
+                "email": "a+b@example.com",
+                "created_at": null,
+                "updated_at": null,
+                "image_url": null
+            }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let users = fetch_users_by_email(&db, "a+b@example.com").await.unwrap();
+
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].email.as_deref(), Some("a+b@example.com"));
+    }
+
+    #[tokio::test]
+    async fn ai_chat_executes_tool_calls_from_the_service() {
+        let db = Database::new("http://127.0.0.1:9".to_string(), "test-key".to_string()).unwrap();
+        let service = crate::ai::MockAIService::new("Here is your team.").with_tools(vec![crate::ai::ToolCall {
+            name: "show_team_overview".to_string(),
+            arguments: json!({}),
+        }]);
+
+        let response = ai_chat_with_service(&db, &service, "How is my team doing?".to_string(), vec![], None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Here is your team.");
+        let tools = response.tools.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "show_team_overview");
+        assert!(tools[0].arguments.get("team_summary").is_some());
+
+        // The user's message is the last thing sent to the service
+        let sent = service.received_messages();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].last().unwrap().content, "How is my team doing?");
+    }
+
+    #[tokio::test]
+    async fn ai_chat_asks_which_member_an_ambiguous_mention_means() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/users"))
+            .and(query_param("workspace_members.workspace_id", "eq.ws-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "u1", "name": "Sam Lee", "email": "sam.lee@example.com", "created_at": null, "updated_at": null, "image_url": null, "workspace_members": [] },
+                { "id": "u2", "name": "Sam Ortiz", "email": "sortiz@example.com", "created_at": null, "updated_at": null, "image_url": null, "workspace_members": [] }
+            ])))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let service = crate::ai::MockAIService::new("unused");
+
+        let response = ai_chat_with_service(&db, &service, "How is @sam doing?".to_string(), vec![], Some("ws-1".to_string()), None)
+            .await
+            .unwrap();
+
+        assert!(response.content.contains("Sam Lee (sam.lee@example.com)"), "{}", response.content);
+        assert!(response.content.contains("Sam Ortiz"), "{}", response.content);
+        assert!(service.received_messages().is_empty());
+    }
+
+    #[test]
+    fn minutes_used_by_app_clips_to_the_day() {
+        let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).unwrap().with_timezone(&chrono::Utc);
+        let day_start = parse("2024-01-02T00:00:00Z");
+        let now = parse("2024-01-02T10:00:00Z");
+        let mut overnight: TimeEntry = serde_json::from_value(time_entry_row("e1", Some("2024-01-02T00:30:00Z"), Some(5400))).unwrap();
+        overnight.start_time = parse("2024-01-01T23:00:00Z");
+        let mut running: TimeEntry = serde_json::from_value(time_entry_row("e2", None, None)).unwrap();
+        running.start_time = parse("2024-01-02T09:15:00Z");
+
+        let used = minutes_used_by_app(&[overnight, running], day_start, now);
+
+        assert_eq!(used.get("app-1"), Some(&75));
+    }
+
+    // A finished entry for `app_id` from `start_minute` to `end_minute` past nine
+    fn finished_entry(id: &str, app_id: &str, start_minute: i64, end_minute: i64) -> TimeEntry {
+        let nine = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 3, 3, 9, 0, 0).unwrap();
+        TimeEntry {
+            id: id.to_string(),
+            user_id: "user-1".to_string(),
+            app_id: Some(app_id.to_string()),
+            task_id: None,
+            workspace_id: None,
+            start_time: nine + chrono::Duration::minutes(start_minute),
+            end_time: Some(nine + chrono::Duration::minutes(end_minute)),
+            duration_seconds: Some((end_minute - start_minute) * 60),
+            is_active: false,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn adjacent_entries_of_one_app_merge_into_the_first() {
+        let entries = vec![
+            finished_entry("e1", "app-code", 0, 10),
+            finished_entry("e2", "app-code", 11, 20),
+            finished_entry("e3", "app-code", 20, 30),
+        ];
+
+        let merges = plan_entry_merges(&entries, 60);
+
+        assert_eq!(merges.len(), 1);
+        assert_eq!(merges[0].keep_id, "e1");
+        assert_eq!(merges[0].absorbed_ids, vec!["e2".to_string(), "e3".to_string()]);
+        assert_eq!(merges[0].start_time, entries[0].start_time);
+        assert_eq!(merges[0].end_time, entries[2].end_time.unwrap());
+
+        // A gap wider than allowed keeps them apart
+        assert!(plan_entry_merges(&entries[..2], 59).is_empty());
+    }
+
+    #[test]
+    fn overlapping_entries_keep_the_latest_end() {
+        let entries = vec![
+            finished_entry("e1", "app-code", 0, 30),
+            finished_entry("e2", "app-code", 5, 20),
+        ];
+
+        let merges = plan_entry_merges(&entries, 0);
+
+        assert_eq!(merges.len(), 1);
+        assert_eq!(merges[0].absorbed_ids, vec!["e2".to_string()]);
+        assert_eq!(merges[0].end_time, entries[0].end_time.unwrap());
+    }
+
+    #[test]
+    fn entries_of_different_apps_or_still_running_are_not_merged() {
+        let mut running = finished_entry("e4", "app-code", 31, 40);
+        running.is_active = true;
+        running.end_time = None;
+        let entries = vec![
+            finished_entry("e1", "app-code", 0, 10),
+            finished_entry("e2", "app-slack", 10, 20),
+            finished_entry("e3", "app-code", 20, 30),
+            running,
+        ];
+
+        assert!(plan_entry_merges(&entries, 60).is_empty());
+    }
+}
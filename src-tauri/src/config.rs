@@ -11,12 +11,14 @@ pub struct SupabaseConfig {
 impl SupabaseConfig {
     pub fn from_env() -> Result<Self> {
         // Try to load URL
-        let url = env::var("SUPABASE_URL")
+        let (url_var, raw_url) = env::var("SUPABASE_URL")
+            .map(|url| ("SUPABASE_URL", url))
             .or_else(|_| {
                 log::info!("SUPABASE_URL not found, trying VITE_SUPABASE_URL");
-                env::var("VITE_SUPABASE_URL")
+                env::var("VITE_SUPABASE_URL").map(|url| ("VITE_SUPABASE_URL", url))
             })
             .map_err(|_| anyhow::anyhow!("SUPABASE_URL environment variable not found"))?;
+        let url = normalize_supabase_url(&raw_url, url_var)?;
 
         // Try to load API key
         let anon_key = env::var("SUPABASE_ANON_KEY")
@@ -31,7 +33,7 @@ impl SupabaseConfig {
             .map_err(|_| anyhow::anyhow!("SUPABASE_ANON_KEY environment variable not found"))?;
 
         log::info!("Loaded Supabase URL: {}", url);
-        log::info!("Loaded API key: {}...", &anon_key[..std::cmp::min(10, anon_key.len())]);
+        log::info!("API key present: {}", !anon_key.is_empty());
 
         Ok(Self { url, anon_key })
     }
@@ -40,3 +42,79 @@ impl SupabaseConfig {
         Self { url, anon_key }
     }
 }
+
+/// Validate a Supabase base URL and strip any trailing slash so that
+/// `format!("{}/rest/v1/...")` never produces double slashes.
+/// `source` is the env var the value came from, used in error messages.
+pub fn normalize_supabase_url(raw: &str, source: &str) -> Result<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow::anyhow!("{} is empty", source));
+    }
+
+    let parsed = reqwest::Url::parse(trimmed).map_err(|e| {
+        anyhow::anyhow!(
+            "{} is not a valid URL ('{}'): {}. Expected something like https://your-project-id.supabase.co",
+            source, trimmed, e
+        )
+    })?;
+
+    match parsed.scheme() {
+        "http" | "https" => {}
+        other => {
+            return Err(anyhow::anyhow!(
+                "{} must start with http:// or https:// (found scheme '{}' in '{}')",
+                source, other, trimmed
+            ))
+        }
+    }
+
+    if !parsed.host_str().is_some_and(|host| !host.is_empty()) {
+        return Err(anyhow::anyhow!("{} has no host ('{}')", source, trimmed));
+    }
+
+    if parsed.path() != "/" && !parsed.path().is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} must not include a path ('{}'); use only the project URL",
+            source, trimmed
+        ));
+    }
+
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(anyhow::anyhow!(
+            "{} must not include a query string or fragment ('{}')",
+            source, trimmed
+        ));
+    }
+
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_trailing_slash() {
+        let url = normalize_supabase_url("https://x.supabase.co/", "SUPABASE_URL").unwrap();
+        assert_eq!(url, "https://x.supabase.co");
+    }
+
+    #[test]
+    fn rejects_missing_scheme() {
+        let err = normalize_supabase_url("x.supabase.co", "SUPABASE_URL").unwrap_err();
+        assert!(err.to_string().contains("SUPABASE_URL"));
+    }
+
+    #[test]
+    fn accepts_valid_url() {
+        let url = normalize_supabase_url("https://x.supabase.co", "VITE_SUPABASE_URL").unwrap();
+        assert_eq!(url, "https://x.supabase.co");
+    }
+
+    #[test]
+    fn rejects_path_and_query() {
+        assert!(normalize_supabase_url("https://x.supabase.co/rest/v1", "SUPABASE_URL").is_err());
+        assert!(normalize_supabase_url("https://x.supabase.co?a=b", "SUPABASE_URL").is_err());
+    }
+}
@@ -1,8 +1,91 @@
 ﻿use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use reqwest::Client;
 
+const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Errors from talking to Supabase that callers may want to handle specifically
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("HTTP error {status}: {body}")]
+    Http { status: reqwest::StatusCode, body: String },
+    #[error("{0}")]
+    Other(String),
+    // The write couldn't be sent but is in the sync queue, to be replayed once back online
+    #[error("Saved offline; it will sync once the connection is back")]
+    Queued,
+}
+
+impl DbError {
+    /// Whether retrying the same request later may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DbError::Timeout(_) | DbError::Network(_))
+    }
+}
+
+impl From<reqwest::Error> for DbError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            DbError::Timeout(e.to_string())
+        } else if e.is_builder() || e.is_body() || e.is_decode() || e.is_redirect() {
+            // The request itself is at fault, so sending it again won't help
+            DbError::Other(e.to_string())
+        } else {
+            DbError::Network(e.to_string())
+        }
+    }
+}
+
+// Read a timeout in seconds from the environment, falling back to the default
+fn timeout_from_env(var: &str, default_secs: u64) -> Duration {
+    let secs = std::env::var(var)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(default_secs);
+    Duration::from_secs(secs)
+}
+
+// Proxy for all requests, taken from HTTPS_PROXY or HTTP_PROXY (either case)
+fn proxy_from_env() -> Result<Option<reqwest::Proxy>> {
+    for var in ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"] {
+        if let Ok(value) = std::env::var(var) {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let proxy = reqwest::Proxy::all(value)
+                .map_err(|e| anyhow::anyhow!("Invalid proxy URL in {}: {}", var, e))?;
+            log::info!("Using HTTP proxy from {}", var);
+            return Ok(Some(proxy));
+        }
+    }
+    Ok(None)
+}
+
+// Extra trusted root certificate (PEM) for self-hosted Supabase behind a private CA
+fn ca_certificate_from_env() -> Result<Option<reqwest::Certificate>> {
+    let path = match std::env::var("SUPABASE_CA_CERT") {
+        Ok(path) if !path.trim().is_empty() => path.trim().to_string(),
+        _ => return Ok(None),
+    };
+
+    let pem = std::fs::read(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read SUPABASE_CA_CERT file '{}': {}", path, e))?;
+    let certificate = reqwest::Certificate::from_pem(&pem)
+        .map_err(|e| anyhow::anyhow!("Failed to parse SUPABASE_CA_CERT file '{}' as a PEM certificate: {}", path, e))?;
+
+    log::info!("Using custom CA certificate from {}", path);
+    Ok(Some(certificate))
+}
+
 #[derive(Clone)]
 pub struct Database {
     pub client: Arc<Client>,
@@ -12,7 +95,19 @@ pub struct Database {
 
 impl Database {
     pub fn new(url: String, key: String) -> Result<Self> {
-        let client = Client::new();
+        // Without timeouts a hung connection would block a tracking tick indefinitely
+        let mut builder = Client::builder()
+            .timeout(timeout_from_env("HTTP_TIMEOUT_SECS", DEFAULT_HTTP_TIMEOUT_SECS))
+            .connect_timeout(timeout_from_env("HTTP_CONNECT_TIMEOUT_SECS", DEFAULT_HTTP_CONNECT_TIMEOUT_SECS));
+
+        if let Some(proxy) = proxy_from_env()? {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(certificate) = ca_certificate_from_env()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        let client = builder.build()?;
         Ok(Self {
             client: Arc::new(client),
             base_url: url,
@@ -24,10 +119,7 @@ impl Database {
         // Test the connection by making a simple request
         let url = format!("{}/rest/v1/", self.base_url);
         log::info!("Testing connection to: {}", url);
-        log::info!(
-            "Using API key: {}...",
-            &self.api_key[..std::cmp::min(10, self.api_key.len())]
-        );
+        log::info!("API key present: {}", !self.api_key.is_empty());
 
         let response = self
             .client
@@ -69,28 +161,92 @@ impl Database {
         table: &str,
         method: &str,
         data: Option<serde_json::Value>,
-    ) -> Result<serde_json::Value> {
+    ) -> Result<serde_json::Value, DbError> {
         let url = format!("{}/rest/v1/{}", self.base_url, table);
+        self.send_query(table, &url, method, data, "return=representation").await
+    }
 
+    /// Insert a row, merging into the existing one if `on_conflict` already matches.
+    /// Rows keyed by a client-generated id can then be retried without duplicating.
+    pub async fn upsert(
+        &self,
+        table: &str,
+        on_conflict: &str,
+        data: serde_json::Value,
+    ) -> Result<serde_json::Value, DbError> {
+        let target = format!("{}?on_conflict={}", table, on_conflict);
+        let url = format!("{}/rest/v1/{}", self.base_url, target);
+        self.send_query(&target, &url, "POST", Some(data), "resolution=merge-duplicates,return=representation").await
+    }
+
+    /// Like `execute_query`, but builds the query string from PostgREST filter
+    /// pairs (e.g. `("project_id", "eq.123")`) so values are properly escaped.
+    pub async fn execute_query_with_params(
+        &self,
+        table: &str,
+        method: &str,
+        filters: &[(&str, &str)],
+        select: Option<&str>,
+        data: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, DbError> {
+        let mut url = url::Url::parse(&format!("{}/rest/v1/{}", self.base_url, table))
+            .map_err(|e| DbError::Other(format!("Invalid URL for {}: {}", table, e)))?;
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(select) = select {
+                query.append_pair("select", select);
+            }
+            for (key, value) in filters {
+                query.append_pair(key, value);
+            }
+        }
+        // Keep the filters with the table name so a queued write replays against the same rows
+        let target = match url.query() {
+            Some(query) => format!("{}?{}", table, query),
+            None => table.to_string(),
+        };
+        self.send_query(&target, url.as_str(), method, data, "return=representation").await
+    }
+
+    async fn send_query(
+        &self,
+        table: &str,
+        url: &str,
+        method: &str,
+        data: Option<serde_json::Value>,
+        prefer: &str,
+    ) -> Result<serde_json::Value, DbError> {
         let mut request = match method {
-            "GET" => self.client.get(&url),
-            "POST" => self.client.post(&url),
-            "PATCH" => self.client.patch(&url),
-            "DELETE" => self.client.delete(&url),
-            _ => return Err(anyhow::anyhow!("Unsupported HTTP method: {}", method)),
+            "GET" => self.client.get(url),
+            "POST" => self.client.post(url),
+            "PATCH" => self.client.patch(url),
+            "DELETE" => self.client.delete(url),
+            _ => return Err(DbError::Other(format!("Unsupported HTTP method: {}", method))),
         };
 
         request = request
             .header("apikey", &self.api_key)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
-            .header("Prefer", "return=representation");
+            .header("Prefer", prefer);
 
-        if let Some(data) = data {
-            request = request.json(&data);
+        if let Some(data) = &data {
+            request = request.json(data);
         }
 
-        let response = request.send().await?;
+        let response = match request.send().await.map_err(DbError::from) {
+            Ok(response) => response,
+            Err(e) if method != "GET" && e.is_transient() => {
+                // Network failure: keep the write for later instead of dropping it
+                log::warn!("{} to {} failed, queueing for sync: {}", method, table, e);
+                if let Err(queue_err) = crate::sync_queue::enqueue(method, table, data) {
+                    log::error!("Failed to queue {} to {}: {}", method, table, queue_err);
+                    return Err(e);
+                }
+                return Err(DbError::Queued);
+            }
+            Err(e) => return Err(e),
+        };
 
         if !response.status().is_success() {
             let status = response.status();
@@ -98,14 +254,22 @@ impl Database {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(anyhow::anyhow!("HTTP error {}: {}", status, error_text));
+            return Err(DbError::Http { status, body: error_text });
         }
 
-        let json_response: serde_json::Value = response.json().await?;
-        Ok(json_response)
+        response
+            .json()
+            .await
+            .map_err(|e| DbError::Other(format!("Failed to parse response from {}: {}", table, e)))
     }
 }
 
+/// Percent-encode a value interpolated into a PostgREST filter by hand,
+/// so characters like `+`, `&` or `,` don't change the query's meaning.
+pub fn encode_filter_value(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
 // Data models based on your schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -137,11 +301,28 @@ pub struct WorkspaceMemberRecord {
     pub joined_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitation {
+    pub id: String,
+    pub workspace_id: String,
+    pub email: String,
+    pub role: String,
+    pub token: String,
+    #[serde(default)]
+    pub invited_by: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>, // Set once the invitation is accepted
+    #[serde(default)]
+    pub used_by: Option<String>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Team {
     pub id: String,
-    #[serde(alias = "name")]
-    pub team_name: String,
+    #[serde(alias = "name", default)]
+    pub team_name: String, // Stored as `name` in the workspaces table
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_by: Option<String>,
@@ -152,7 +333,8 @@ pub struct Team {
 pub struct Project {
     pub id: String,
     pub name: String,
-    pub workspace_id: Option<String>, // Changed from team_id to workspace_id
+    #[serde(alias = "team_id")]
+    pub workspace_id: Option<String>, // Stored as workspace_id; older payloads call it team_id
     pub manager_id: Option<String>,
     pub description: Option<String>,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
@@ -172,9 +354,31 @@ pub struct Task {
     pub due_date: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>, // Set when archived; list getters skip these
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    Owner,
+    Manager,
+    Member,
+}
+
+impl UserRole {
+    pub const ALL: [UserRole; 3] = [UserRole::Owner, UserRole::Manager, UserRole::Member];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Owner => "owner",
+            UserRole::Manager => "manager",
+            UserRole::Member => "member",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskStatus {
     #[serde(rename = "todo")]
     Todo,
@@ -184,7 +388,19 @@ pub enum TaskStatus {
     Done,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl TaskStatus {
+    pub const ALL: [TaskStatus; 3] = [TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::Done];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskPriority {
     Low,
@@ -192,6 +408,49 @@ pub enum TaskPriority {
     High,
 }
 
+impl TaskPriority {
+    pub const ALL: [TaskPriority; 3] = [TaskPriority::Low, TaskPriority::Medium, TaskPriority::High];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+        }
+    }
+}
+
+// Display and FromStr use the same strings as the database, and parse errors
+// list every valid value so validation can't drift from storage.
+macro_rules! impl_str_enum {
+    ($ty:ident, $label:literal) => {
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = String;
+
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                $ty::ALL
+                    .iter()
+                    .find(|variant| variant.as_str() == s)
+                    .copied()
+                    .ok_or_else(|| {
+                        let valid: Vec<String> = $ty::ALL.iter().map(|v| format!("'{}'", v.as_str())).collect();
+                        format!("Invalid {} '{}'. Must be one of: {}", $label, s, valid.join(", "))
+                    })
+            }
+        }
+    };
+}
+
+impl_str_enum!(UserRole, "role");
+impl_str_enum!(TaskStatus, "status");
+impl_str_enum!(TaskPriority, "priority");
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Application {
     pub id: String, // UUID as string
@@ -199,6 +458,7 @@ pub struct Application {
     pub process_name: String,
     pub icon_path: Option<String>,
     pub category: Option<String>,
+    #[serde(default)]
     pub is_tracked: bool, // Boolean field with default false
     pub user_id: Option<String>, // Optional to match database schema
     pub created_at: Option<chrono::DateTime<chrono::Utc>>, // Database default now()
@@ -212,12 +472,44 @@ pub struct TimeEntry {
     pub user_id: String,
     pub app_id: Option<String>,
     pub task_id: Option<String>,
+    #[serde(default)]
+    pub workspace_id: Option<String>, // Workspace the user was in when the entry started
     pub start_time: chrono::DateTime<chrono::Utc>,
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
     pub duration_seconds: Option<i64>,
+    #[serde(default)]
     pub is_active: bool,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub updated_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>, // Database default now()
+    #[serde(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>, // Database default now()
+}
+
+/// Application fields embedded in a time entry query via PostgREST resource embedding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedApplication {
+    pub name: String,
+    pub category: Option<String>,
+    pub icon_path: Option<String>,
+}
+
+/// Time entry fetched with `select=*,applications(name,category,icon_path)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeEntryWithApp {
+    #[serde(flatten)]
+    pub entry: TimeEntry,
+    #[serde(default, rename = "applications")]
+    pub application: Option<EmbeddedApplication>, // None when the entry has no app
+}
+
+impl TimeEntryWithApp {
+    pub fn app_name(&self) -> Option<&str> {
+        self.application.as_ref().map(|app| app.name.as_str())
+    }
+
+    pub fn app_category(&self) -> Option<&str> {
+        self.application.as_ref().and_then(|app| app.category.as_deref())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -229,3 +521,260 @@ pub struct Workspace {
     pub created_by: Option<String>,
     pub description: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    #[test]
+    fn clones_share_one_http_client() {
+        let db = Database::new("http://localhost".to_string(), "test-key".to_string()).unwrap();
+
+        let clone = db.clone();
+
+        // Same Arc, so every clone draws from the same connection pool
+        assert!(Arc::ptr_eq(&db.client, &clone.client));
+    }
+
+    #[tokio::test]
+    async fn a_slow_read_fails_as_a_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/tasks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])).set_delay(Duration::from_secs(2)))
+            .mount(&server)
+            .await;
+        let db = Database {
+            client: Arc::new(Client::builder().timeout(Duration::from_millis(100)).build().unwrap()),
+            base_url: server.uri(),
+            api_key: "test-key".to_string(),
+        };
+
+        let err = db.execute_query("tasks", "GET", None).await.unwrap_err();
+
+        assert!(matches!(err, DbError::Timeout(_)), "{:?}", err);
+        assert!(err.is_transient());
+    }
+
+    #[tokio::test]
+    async fn a_rejected_request_keeps_its_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/tasks"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("denied"))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let err = db.execute_query("tasks", "GET", None).await.unwrap_err();
+
+        assert!(matches!(err, DbError::Http { status, .. } if status == reqwest::StatusCode::FORBIDDEN));
+        assert!(!err.is_transient());
+    }
+
+    #[tokio::test]
+    async fn a_write_that_cannot_be_sent_is_queued_not_answered() {
+        let dir = std::env::temp_dir().join(format!("macro-db-queue-{}", uuid::Uuid::new_v4()));
+        crate::sync_queue::set_queue_dir(dir.clone());
+        // Nothing listens on port 1, so the request fails like it does offline
+        let db = Database::new("http://127.0.0.1:1".to_string(), "test-key".to_string()).unwrap();
+
+        let err = db
+            .execute_query("time_entries?id=eq.entry-queued", "PATCH", Some(json!({ "is_active": false })))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, DbError::Queued), "{:?}", err);
+        let queued = std::fs::read_to_string(dir.join("pending_writes.ndjson")).unwrap();
+        assert!(queued.contains("entry-queued"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    // Stand-in for the time_entries table: rejects a repeated id unless the
+    // request asks PostgREST to merge on the id conflict
+    async fn mount_time_entries(server: &MockServer) -> Arc<Mutex<HashMap<String, serde_json::Value>>> {
+        let rows = Arc::new(Mutex::new(HashMap::new()));
+        let table = Arc::clone(&rows);
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(move |request: &Request| {
+                let row: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let merges = request
+                    .headers
+                    .get("Prefer")
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| value.contains("resolution=merge-duplicates"))
+                    && request.url.query_pairs().any(|(key, value)| key == "on_conflict" && value == "id");
+
+                let mut rows = table.lock().unwrap();
+                let id = row["id"].as_str().unwrap().to_string();
+                if rows.contains_key(&id) && !merges {
+                    return ResponseTemplate::new(409);
+                }
+                rows.insert(id, row.clone());
+                ResponseTemplate::new(201).set_body_json(json!([row]))
+            })
+            .mount(server)
+            .await;
+        rows
+    }
+
+    #[tokio::test]
+    async fn retried_upsert_with_the_same_id_keeps_one_row() {
+        let server = MockServer::start().await;
+        let rows = mount_time_entries(&server).await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let entry = json!({ "id": "entry-1", "user_id": "user-1", "start_time": "2024-01-01T09:00:00Z" });
+
+        // The first response is "lost", so the caller sends the same insert again
+        db.upsert("time_entries", "id", entry.clone()).await.unwrap();
+        let retried = db.upsert("time_entries", "id", entry).await.unwrap();
+
+        assert_eq!(retried[0]["id"], "entry-1");
+        assert_eq!(rows.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn plain_insert_with_a_repeated_id_is_rejected() {
+        let server = MockServer::start().await;
+        let rows = mount_time_entries(&server).await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let entry = json!({ "id": "entry-1", "user_id": "user-1", "start_time": "2024-01-01T09:00:00Z" });
+
+        db.execute_query("time_entries", "POST", Some(entry.clone())).await.unwrap();
+
+        assert!(db.execute_query("time_entries", "POST", Some(entry)).await.is_err());
+        assert_eq!(rows.lock().unwrap().len(), 1);
+    }
+
+    // Deserialize a row shaped like its Supabase table, then check the model
+    // serializes back to exactly those columns. `renamed` maps a column to the
+    // field name the model uses instead, and `derived` lists fields that are
+    // filled in by the app rather than stored, so any other drift fails here
+    fn assert_columns<T>(row: serde_json::Value, renamed: &[(&str, &str)], derived: &[&str])
+    where
+        T: Serialize + serde::de::DeserializeOwned,
+    {
+        let model: T = serde_json::from_value(row.clone()).expect("row should deserialize");
+        let serialized = serde_json::to_value(&model).unwrap();
+
+        let mut expected: Vec<String> = row
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|column| {
+                renamed
+                    .iter()
+                    .find(|(from, _)| from == column)
+                    .map_or(column.clone(), |(_, to)| to.to_string())
+            })
+            .chain(derived.iter().map(|field| field.to_string()))
+            .collect();
+        expected.sort();
+        let mut actual: Vec<String> = serialized.as_object().unwrap().keys().cloned().collect();
+        actual.sort();
+        assert_eq!(actual, expected);
+
+        // What the model writes has to read back too
+        serde_json::from_value::<T>(serialized).expect("serialized model should deserialize");
+    }
+
+    const AT: &str = "2025-01-01T09:00:00Z";
+
+    #[test]
+    fn user_columns() {
+        assert_columns::<User>(
+            json!({ "id": "u1", "name": "Ada", "email": "ada@example.com", "created_at": AT, "updated_at": AT, "image_url": null }),
+            &[],
+            // Filled in from workspace_members; team_id mirrors workspace_id for the frontend
+            &["role", "workspace_id", "team_id"],
+        );
+    }
+
+    #[test]
+    fn workspace_member_columns() {
+        assert_columns::<WorkspaceMemberRecord>(
+            json!({ "id": "m1", "user_id": "u1", "workspace_id": "ws-1", "role": "member", "joined_at": AT }),
+            &[],
+            &[],
+        );
+    }
+
+    #[test]
+    fn invitation_columns() {
+        assert_columns::<Invitation>(
+            json!({
+                "id": "i1", "workspace_id": "ws-1", "email": "ada@example.com", "role": "member", "token": "t",
+                "invited_by": "u1", "expires_at": AT, "used_at": null, "used_by": null, "created_at": AT
+            }),
+            &[],
+            &[],
+        );
+    }
+
+    #[test]
+    fn workspace_and_team_columns() {
+        let row = json!({ "id": "ws-1", "name": "Core", "created_at": AT, "updated_at": AT, "created_by": "u1", "description": null });
+        assert_columns::<Workspace>(row.clone(), &[], &[]);
+        // Team is the frontend's view of a workspace and calls `name` team_name
+        assert_columns::<Team>(row, &[("name", "team_name")], &[]);
+    }
+
+    #[test]
+    fn project_columns() {
+        assert_columns::<Project>(
+            json!({ "id": "p1", "name": "App", "workspace_id": "ws-1", "manager_id": "u1", "description": null, "created_at": AT, "updated_at": AT }),
+            &[],
+            &[],
+        );
+
+        // Older payloads still call the workspace team_id
+        let legacy: Project = serde_json::from_value(json!({ "id": "p1", "name": "App", "team_id": "ws-1", "manager_id": null, "description": null, "created_at": null, "updated_at": null })).unwrap();
+        assert_eq!(legacy.workspace_id.as_deref(), Some("ws-1"));
+    }
+
+    #[test]
+    fn task_columns() {
+        let row = json!({
+            "id": "t1", "title": "Ship", "description": null, "project_id": null, "workspace_id": "ws-1", "assignee_id": "u1",
+            "status": "in_progress", "priority": "high", "due_date": AT, "created_at": AT, "updated_at": AT, "archived_at": null
+        });
+        assert_columns::<Task>(row.clone(), &[], &[]);
+
+        let task: Task = serde_json::from_value(row).unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert_eq!(task.priority, Some(TaskPriority::High));
+        assert_eq!(serde_json::to_value(UserRole::Manager).unwrap(), "manager");
+    }
+
+    #[test]
+    fn application_columns() {
+        assert_columns::<Application>(
+            json!({
+                "id": "a1", "name": "Code", "process_name": "Code.exe", "icon_path": null, "category": "Development",
+                "is_tracked": true, "user_id": "u1", "created_at": AT, "updated_at": AT, "last_used": null
+            }),
+            &[],
+            &[],
+        );
+    }
+
+    #[test]
+    fn time_entry_columns() {
+        let row = json!({
+            "id": "e1", "user_id": "u1", "app_id": "a1", "task_id": null, "workspace_id": "ws-1", "start_time": AT, "end_time": null,
+            "duration_seconds": null, "is_active": true, "created_at": AT, "updated_at": AT
+        });
+        assert_columns::<TimeEntry>(row.clone(), &[], &[]);
+
+        // The embedded application comes back under the table name
+        let mut with_app = row;
+        with_app["applications"] = json!({ "name": "Code", "category": null, "icon_path": null });
+        assert_columns::<TimeEntryWithApp>(with_app, &[], &[]);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::settings_file::SettingsFile;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+static OVERRIDE_FILE: SettingsFile = SettingsFile::new("friendly_names.json", "friendly names");
+
+// Built-in process name -> display name pairs
+static BUILT_IN: Lazy<BTreeMap<String, String>> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../resources/friendly_names.json"))
+        .expect("friendly_names.json must be a JSON object of process name to display name")
+});
+
+// Effective mapping keyed by lowercased process name, rebuilt whenever the override changes
+static NAMES: Lazy<Arc<Mutex<HashMap<String, String>>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(build_names(&BTreeMap::new())))
+});
+
+fn build_names(overrides: &BTreeMap<String, String>) -> HashMap<String, String> {
+    BUILT_IN
+        .iter()
+        .chain(overrides.iter())
+        .map(|(process, display)| (process.to_lowercase(), display.clone()))
+        .collect()
+}
+
+fn reload(overrides: &BTreeMap<String, String>) {
+    if let Ok(mut names) = NAMES.lock() {
+        *names = build_names(overrides);
+    }
+}
+
+/// Set the directory the override file lives in (the app data dir) and load it
+pub fn set_override_dir(dir: PathBuf) {
+    OVERRIDE_FILE.set_dir(dir);
+    reload(&OVERRIDE_FILE.read());
+}
+
+/// Display name for a process, falling back to a title-cased process name
+pub fn friendly_name(process_name: &str) -> String {
+    let mapped = NAMES
+        .lock()
+        .ok()
+        .and_then(|names| names.get(&process_name.to_lowercase()).cloned());
+    mapped.unwrap_or_else(|| title_case(process_name))
+}
+
+/// Effective mapping (built-in plus user overrides), sorted by process name
+pub fn friendly_names() -> BTreeMap<String, String> {
+    let mut names = BUILT_IN.clone();
+    for (process, display) in OVERRIDE_FILE.read::<BTreeMap<String, String>>() {
+        // Let the user's spelling of the process name replace a built-in key
+        names.retain(|existing, _| !existing.eq_ignore_ascii_case(&process));
+        names.insert(process, display);
+    }
+    names
+}
+
+/// Save a display name for a process; an empty display name removes the user's entry
+pub fn set_friendly_name(process_name: &str, display_name: &str) -> Result<(), String> {
+    let process_name = process_name.trim();
+    if process_name.is_empty() {
+        return Err("Process name is required".to_string());
+    }
+
+    let display_name = display_name.trim();
+    OVERRIDE_FILE.update(
+        |overrides: &mut BTreeMap<String, String>| {
+            overrides.retain(|existing, _| !existing.eq_ignore_ascii_case(process_name));
+            if !display_name.is_empty() {
+                overrides.insert(process_name.to_string(), display_name.to_string());
+            }
+            Ok(())
+        },
+        reload,
+    )
+}
+
+// Convert a process name to a friendly format, e.g. "my_tool.exe" -> "My Tool"
+fn title_case(process_name: &str) -> String {
+    process_name
+        .split('.')
+        .next()
+        .unwrap_or(process_name)
+        .split('_')
+        .map(|s| {
+            let mut chars = s.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_processes_get_a_title_cased_name() {
+        assert_eq!(title_case("my_tool.exe"), "My Tool");
+        assert_eq!(title_case("SLACK"), "Slack");
+    }
+
+    #[test]
+    fn overrides_replace_built_in_names_whatever_their_case() {
+        let built_in = BUILT_IN.keys().next().unwrap().clone();
+        let names = build_names(&BTreeMap::from([(built_in.to_uppercase(), "Renamed".to_string())]));
+
+        assert_eq!(names[&built_in.to_lowercase()], "Renamed");
+    }
+
+    #[test]
+    fn saving_a_name_creates_the_app_data_dir() {
+        // Not created yet, like the app data dir on first launch
+        let dir = std::env::temp_dir()
+            .join(format!("macro-friendly-names-{}", uuid::Uuid::new_v4()))
+            .join("app-data");
+        set_override_dir(dir.clone());
+
+        set_friendly_name("macro_test_tool.exe", "Test Tool").unwrap();
+
+        assert!(dir.join("friendly_names.json").exists());
+        assert_eq!(friendly_name("MACRO_TEST_TOOL.EXE"), "Test Tool");
+        let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+    }
+}
@@ -6,11 +6,68 @@ mod default_user;
 mod tracking;
 mod platform;
 mod ai;
+mod sync_queue;
+mod process_filter;
+mod friendly_names;
+mod category_rules;
+mod settings_file;
+mod tracking_settings;
+mod validation;
 
 use commands::*;
-use tracking::{start_activity_tracking, stop_activity_tracking, update_activity, get_current_activity, get_active_applications_count, stop_tracking_for_app, stop_tracking_for_app_by_id, get_detected_os};
+use tracking::{start_activity_tracking, stop_activity_tracking, update_activity, get_current_activity, get_active_applications_count, stop_tracking_for_app, stop_tracking_for_app_by_id, set_active_task, get_tracking_state, start_focus_session, end_focus_session, get_detected_os, get_idle_time_seconds, clear_all_tracking, get_tracking_settings, set_max_session_length, set_min_entry_duration, set_usage_snapshot_interval, set_tracking_mode, get_tracking_diagnostics};
+use tracking::pomodoro::start_pomodoro;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{Listener, Manager};
 
+// Set once shutdown cleanup has started, so the window-close and signal paths don't both run it
+static SHUTDOWN_CLEANUP_STARTED: AtomicBool = AtomicBool::new(false);
+
+// Log out and stop tracking so the active time entry gets its end time
+async fn shutdown_cleanup(source: &str) {
+    if SHUTDOWN_CLEANUP_STARTED.swap(true, Ordering::SeqCst) {
+        log::debug!("Shutdown cleanup already ran, skipping ({})", source);
+        return;
+    }
+
+    log::info!("Executing logout cleanup ({})...", source);
+    match crate::commands::logout_user().await {
+        Ok(_) => log::info!("Logout cleanup completed ({})", source),
+        Err(e) => log::error!("Error during logout cleanup ({}): {}", source, e),
+    }
+}
+
+// Window event callbacks are synchronous, so run the cleanup on its own runtime
+fn shutdown_cleanup_blocking(source: &str) {
+    match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt.block_on(shutdown_cleanup(source)),
+        Err(e) => log::error!("Failed to create runtime for cleanup ({}): {}", source, e),
+    }
+}
+
+// Resolves on Ctrl+C, or SIGTERM on Unix
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => log::warn!("Could not listen for SIGTERM: {}", e),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        log::warn!("Could not listen for Ctrl+C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Load environment variables from .env file
@@ -18,121 +75,155 @@ pub fn run() {
     
     tauri::Builder::default()
         .setup(|app| {
-            // Initialize logging
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
+            // Initialize logging; release builds keep warnings and errors for field diagnostics
+            let log_level = if cfg!(debug_assertions) {
+                log::LevelFilter::Info
+            } else {
+                log::LevelFilter::Warn
+            };
+            app.handle().plugin(
+                tauri_plugin_log::Builder::default()
+                    .level(log_level)
+                    .build(),
+            )?;
+
+            // Offline writes and the user's process overrides live in the app data dir
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    crate::sync_queue::set_queue_dir(dir.clone());
+                    crate::process_filter::set_override_dir(dir.clone());
+                    crate::friendly_names::set_override_dir(dir.clone());
+                    crate::category_rules::set_rules_dir(dir.clone());
+                    crate::tracking_settings::set_settings_dir(dir);
+                }
+                Err(e) => log::error!("Could not resolve app data dir: {}", e),
+            }
+
+            if use_mock_data() {
+                log::warn!("MACRO_USE_MOCK_DATA is set - insights and AI tools are serving mock data");
+            } else {
+                log::info!("Insights and AI tools are using live database data");
             }
 
-            println!("🚀 App setup starting - registering window close handlers...");
+            log::info!("App setup starting - registering window close handlers...");
 
             // Try multiple approaches to catch window close events
             
             // Approach 1: tauri://close-requested event
             app.handle().listen("tauri://close-requested", move |_event| {
-                println!("🔴 METHOD 1: tauri://close-requested event triggered!");
-                
-                // Create a new runtime for this context
-                match tokio::runtime::Runtime::new() {
-                    Ok(rt) => {
-                        rt.block_on(async {
-                            println!("🔄 Executing logout cleanup...");
-                            match crate::commands::logout_user().await {
-                                Ok(_) => println!("✅ Logout cleanup completed on app close"),
-                                Err(e) => eprintln!("❌ Error during logout cleanup on app close: {}", e),
-                            }
-                        });
-                    }
-                    Err(e) => eprintln!("❌ Failed to create runtime for cleanup: {}", e),
-                }
-                
-                println!("🏁 Window close cleanup finished");
+                log::info!("METHOD 1: tauri://close-requested event triggered!");
+                shutdown_cleanup_blocking("close-requested event");
+                log::info!("Window close cleanup finished");
             });
 
             // Approach 2: Try the window-specific event
             let main_window = app.get_webview_window("main");
             if let Some(window) = main_window {
-                println!("🪟 Found main window, setting up window-specific close handler...");
+                log::debug!("Found main window, setting up window-specific close handler...");
                 
                 window.on_window_event(move |event| {
                     match event {
                         tauri::WindowEvent::CloseRequested { .. } => {
-                            println!("🔴 METHOD 2: WindowEvent::CloseRequested triggered!");
-                            
-                            // Create a new runtime for this context
-                            match tokio::runtime::Runtime::new() {
-                                Ok(rt) => {
-                                    rt.block_on(async {
-                                        println!("🔄 Executing logout cleanup via window event...");
-                                        match crate::commands::logout_user().await {
-                                            Ok(_) => println!("✅ Window event logout cleanup completed"),
-                                            Err(e) => eprintln!("❌ Error during window event logout cleanup: {}", e),
-                                        }
-                                    });
-                                }
-                                Err(e) => eprintln!("❌ Failed to create runtime for window event cleanup: {}", e),
-                            }
-                            
-                            println!("🏁 Window event cleanup finished");
+                            log::info!("METHOD 2: WindowEvent::CloseRequested triggered!");
+                            shutdown_cleanup_blocking("window event");
+                            log::info!("Window event cleanup finished");
                         }
                         _ => {}
                     }
                 });
             } else {
-                println!("⚠️ Could not find main window for event handler");
+                log::warn!("Could not find main window for event handler");
             }
 
-            println!("✅ App setup completed with window close handlers registered");
+            // Approach 3: Ctrl+C / SIGTERM never reach the window, so end the session from here
+            let signal_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                wait_for_shutdown_signal().await;
+                log::info!("Shutdown signal received");
+                shutdown_cleanup("shutdown signal").await;
+                signal_handle.exit(0);
+            });
+
+            log::info!("App setup completed with window close handlers registered");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // User commands
             create_user,
             get_user,
+            get_user_by_email,
+            get_user_avatar,
             get_users_by_team,
             get_all_users,
+            get_all_users_paged,
             update_user,
+            transfer_user_workspace,
             delete_user,
             // Team commands
             create_team,
             get_team,
             get_all_teams,
+            update_team,
             get_my_workspaces,
+            get_my_workspaces_with_stats,
+            get_workspace_activity_feed,
             get_all_workspace_members,
             delete_team,
+            // Invitation commands
+            create_invitation,
+            accept_invitation,
             // Project commands
             create_project,
             get_projects_by_team,
             get_project,
             get_all_projects,
+            get_all_projects_paged,
+            get_project_progress,
+            get_all_project_progress,
             // Task commands
             create_task,
             get_tasks_by_project,
             get_tasks_by_workspace,
             get_tasks_by_assignee,
+            get_task,
+            search_tasks,
+            reassign_tasks,
+            get_upcoming_tasks,
+            set_app_limit,
+            add_process_alias,
+            get_app_limit_status,
             get_all_tasks,
             get_all_assignees,
             get_task_assignees,
             update_task,
+            archive_task,
+            unarchive_task,
             delete_task,
             // Application commands
             create_application,
             get_applications_by_user,
+            get_applications_by_ids,
             update_application,
             // Time entry commands
             create_time_entry,
             get_time_entries_by_user,
+            get_time_entries_by_user_paged,
+            get_time_entries_by_user_with_apps,
+            get_active_entry,
+            get_recent_sessions,
             get_time_entries_by_task,
             get_time_entries_by_app,
             update_time_entry,
+            split_time_entry,
+            merge_adjacent_entries,
+            purge_time_entries_before,
+            backfill_time_entry_workspace,
             // Default user convenience commands
             get_current_user,
             get_current_user_id,
             get_my_applications,
             get_my_tasks,
+            get_my_tasks_with_projects,
             get_my_time_entries,
             create_my_application,
             update_my_application,
@@ -141,6 +232,15 @@ pub fn run() {
             create_my_time_entry,
             // Process detection commands
             get_running_processes,
+            suggest_untracked_apps,
+            get_tracked_app_states,
+            add_ignored_process,
+            remove_ignored_process,
+            get_friendly_names,
+            set_friendly_name,
+            get_category_rules,
+            add_category_rule,
+            remove_category_rule,
             // Activity tracking commands
             start_activity_tracking,
             stop_activity_tracking,
@@ -149,17 +249,43 @@ pub fn run() {
             get_active_applications_count,
             stop_tracking_for_app,
             stop_tracking_for_app_by_id,
+            set_active_task,
+            get_tracking_state,
+            start_focus_session,
+            end_focus_session,
+            start_pomodoro,
             get_detected_os,
+            get_idle_time_seconds,
+            clear_all_tracking,
+            get_tracking_settings,
+            set_max_session_length,
+            set_min_entry_duration,
+            set_usage_snapshot_interval,
+            set_tracking_mode,
+            get_tracking_diagnostics,
             // Utility commands
             test_database_connection,
+            force_sync_now,
             initialize_database_and_login,
             sign_up_user,
+            change_my_password,
+            request_password_reset,
+            confirm_password_reset,
             logout_user,
+            get_pending_sync_count,
             // E2EE team key helpers (prototype)
             get_team_key_record,
             upsert_team_key_record,
             // AI Assistant commands
             get_productivity_insights,
+            get_weekly_summary,
+            get_productivity_score,
+            get_daily_hours,
+            get_app_daily_usage,
+            get_category_usage,
+            get_my_applications_with_usage,
+            get_most_used_apps,
+            export_report_json,
             ai_chat,
         ])
         .run(tauri::generate_context!())
@@ -1,8 +1,12 @@
-use crate::database::{Database, TimeEntry, Application};
+use crate::database::{encode_filter_value, Database, DbError, TimeEntry, Application};
 // Use the currently logged-in user id managed by runtime state, not a hardcoded default
 use crate::current_user::get_current_user_id_or_error;
 use serde_json::json;
 
+// How long an open entry can go without a heartbeat before it counts as orphaned;
+// several heartbeat intervals, so a tracker that is merely slow isn't cut off
+const ORPHANED_AFTER_SECS: i64 = 5 * 60;
+
 /// Database helper methods for platform trackers
 pub struct DatabaseHelpers;
 
@@ -35,8 +39,40 @@ impl DatabaseHelpers {
         }
     }
 
-    /// Start a new time entry for an application
-    pub async fn start_time_entry(db: &Database, app: &Application) -> Result<String, String> {
+    /// End the current user's open entries that nothing is keeping alive: those not in `running`
+    /// whose heartbeat has gone stale, e.g. left behind by a crash. Entries with a recent heartbeat
+    /// belong to a tracker on another device and are left alone. Each is ended at its last
+    /// heartbeat so the downtime isn't counted. Returns how many were ended and how many failed
+    pub async fn end_orphaned_entries(db: &Database, running: &[String]) -> Result<(usize, usize), String> {
+        let now = chrono::Utc::now();
+        let mut ended = 0;
+        let mut failed = 0;
+        for entry in Self::get_active_time_entries(db).await? {
+            if running.contains(&entry.id) {
+                continue;
+            }
+            let last_seen = entry.updated_at.unwrap_or(entry.start_time).max(entry.start_time);
+            if (now - last_seen).num_seconds() < ORPHANED_AFTER_SECS {
+                continue;
+            }
+
+            match Self::close_time_entry(db, &entry.id, Some(entry.start_time), last_seen).await {
+                Ok(()) => {
+                    ended += 1;
+                    log::info!("Closed orphaned entry {} for app_id {:?} at {}", entry.id, entry.app_id, last_seen);
+                }
+                Err(e) => {
+                    failed += 1;
+                    log::warn!("Failed to close orphaned entry {}: {}", entry.id, e);
+                }
+            }
+        }
+        Ok((ended, failed))
+    }
+
+    /// Start a new time entry for an application, linked to the active task if one is set.
+    /// Returns the running entry, which may be an existing one that gets reused.
+    pub async fn start_time_entry(db: &Database, app: &Application, task_id: Option<&str>) -> Result<TimeEntry, String> {
         if db.base_url.is_empty() {
             return Err("Database base_url is empty. Check your environment variables (SUPABASE_URL or VITE_SUPABASE_URL)".to_string());
         }
@@ -50,25 +86,31 @@ impl DatabaseHelpers {
             .header("apikey", &db.api_key)
             .header("Authorization", format!("Bearer {}", db.api_key))
             .send()
-            .await
-            .map_err(|e| format!("Failed to check existing time entries: {}", e))?;
+            .await;
 
-        if existing_response.status().is_success() {
+        // If the check can't reach the server, fall through and let the insert be queued
+        if let Err(e) = &existing_response {
+            log::warn!("Failed to check existing time entries: {}", e);
+        }
+
+        if let Ok(existing_response) = existing_response.and_then(|r| r.error_for_status()) {
             let existing_entries: Vec<TimeEntry> = existing_response.json().await
                 .map_err(|e| format!("Failed to parse existing time entries: {}", e))?;
             
-            if let Some(existing_entry) = existing_entries.first() {
-                println!("Found existing active time entry for {} (id: {}), reusing it", app.name, existing_entry.id);
-                return Ok(existing_entry.id.clone());
+            if let Some(existing_entry) = existing_entries.into_iter().next() {
+                log::debug!("Found existing active time entry for {} (id: {}), reusing it", app.name, existing_entry.id);
+                return Ok(existing_entry);
             }
         }
 
         // No existing active entry found, create a new one
+        let workspace_id = Self::current_workspace_id(db, &user_id).await;
         let time_entry_data = json!({
             "id": uuid::Uuid::new_v4().to_string(),
             "user_id": user_id,
             "app_id": app.id,
-            "task_id": null,
+            "task_id": task_id,
+            "workspace_id": workspace_id,
             "start_time": chrono::Utc::now().to_rfc3339(),
             "end_time": null,
             "duration_seconds": null,
@@ -77,84 +119,358 @@ impl DatabaseHelpers {
             "updated_at": chrono::Utc::now().to_rfc3339()
         });
 
-        let response = db
-            .execute_query("time_entries", "POST", Some(time_entry_data))
-            .await
-            .map_err(|e| format!("Failed to create time entry: {}", e))?;
+        let response = match db.upsert("time_entries", "id", time_entry_data.clone()).await {
+            // Offline: the insert waits in the sync queue, so carry on with the row as sent
+            Err(DbError::Queued) => json!([time_entry_data]),
+            response => response.map_err(|e| format!("Failed to create time entry: {}", e))?,
+        };
 
         // The response should be an array with the created record
         let created_entries: Vec<TimeEntry> = serde_json::from_value(response)
             .map_err(|e| format!("Failed to parse created time entry: {}", e))?;
 
-        if let Some(created_entry) = created_entries.first() {
-            println!("Created new time entry for {} (id: {})", app.name, created_entry.id);
-            Ok(created_entry.id.clone())
+        if let Some(created_entry) = created_entries.into_iter().next() {
+            log::info!("Created new time entry for {} (id: {})", app.name, created_entry.id);
+            Self::mark_application_used(db, &app.id, created_entry.start_time);
+            Ok(created_entry)
         } else {
             Err("No time entry was created".to_string())
         }
     }
 
-    /// End a time entry
-    pub async fn end_time_entry(db: &Database, entry_id: String) -> Result<(), String> {
+    /// Workspace new time entries are recorded under: the one the user joined most recently,
+    /// the same rule the workspace backfill applies to older entries. Memberships without a
+    /// join date count as the oldest. None if they have none or it can't be read.
+    pub async fn current_workspace_id(db: &Database, user_id: &str) -> Option<String> {
+        let url = format!(
+            "{}/rest/v1/workspace_members?user_id=eq.{}&workspace_id=not.is.null&select=workspace_id&order=joined_at.desc.nullslast&limit=1",
+            db.base_url,
+            encode_filter_value(user_id)
+        );
+        let response = db.client
+            .get(&url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await;
+
+        let rows: Vec<serde_json::Value> = match response {
+            Ok(response) if response.status().is_success() => response.json().await.unwrap_or_default(),
+            Ok(response) => {
+                log::debug!("Could not look up workspace for {}: {}", user_id, response.status());
+                return None;
+            }
+            Err(e) => {
+                log::debug!("Could not look up workspace for {}: {}", user_id, e);
+                return None;
+            }
+        };
+        rows.first()
+            .and_then(|row| row["workspace_id"].as_str())
+            .map(str::to_string)
+    }
+
+    /// End a time entry. `started_at` is the start time the caller already knows, used to
+    /// work out the duration when the entry can't be fetched because the network is down
+    pub async fn end_time_entry(db: &Database, entry_id: String, started_at: Option<chrono::DateTime<chrono::Utc>>) -> Result<(), String> {
+        let start_time = Self::entry_start_time(db, &entry_id, started_at).await?;
+        Self::close_time_entry(db, &entry_id, start_time, chrono::Utc::now()).await?;
+
+        log::info!("Successfully ended time entry {}", entry_id);
+        Ok(())
+    }
+
+    /// End an entry the tracker opened, deleting it instead if it ran for less than the
+    /// minimum entry duration. Manual entries never come through here, so they are always kept
+    pub async fn end_tracked_time_entry(db: &Database, entry_id: String, started_at: Option<chrono::DateTime<chrono::Utc>>) -> Result<(), String> {
+        let start_time = Self::entry_start_time(db, &entry_id, started_at).await?;
+        let end_time = chrono::Utc::now();
+
+        if let (Some(start_time), Some(min_seconds)) = (start_time, crate::tracking_settings::min_entry_seconds()) {
+            let seconds = (end_time - start_time).num_seconds();
+            if seconds < min_seconds as i64 {
+                match db.execute_query(&format!("time_entries?id=eq.{}", entry_id), "DELETE", None).await {
+                    Ok(_) | Err(DbError::Queued) => {}
+                    Err(e) => return Err(format!("Failed to discard short time entry: {}", e)),
+                }
+                log::debug!("Discarded time entry {} after {}s (minimum {}s)", entry_id, seconds, min_seconds);
+                return Ok(());
+            }
+        }
+
+        Self::close_time_entry(db, &entry_id, start_time, end_time).await?;
+        log::info!("Successfully ended time entry {}", entry_id);
+        Ok(())
+    }
+
+    // Start time of an entry, from the server or, when offline, the queue or the caller's
+    // own record; None only if none of them has it
+    async fn entry_start_time(
+        db: &Database,
+        entry_id: &str,
+        started_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
         if db.base_url.is_empty() {
             return Err("Database base_url is empty. Check your environment variables (SUPABASE_URL or VITE_SUPABASE_URL)".to_string());
         }
         
         // First, get the current time entry to access the start_time
         let get_url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, entry_id);
-        let get_response = db.client
+        let start_time = match db.client
             .get(&get_url)
             .header("apikey", &db.api_key)
             .header("Authorization", format!("Bearer {}", db.api_key))
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch time entry: {}", e))?;
+        {
+            Ok(get_response) => {
+                if !get_response.status().is_success() {
+                    let status = get_response.status();
+                    let error_text = get_response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    return Err(format!("HTTP error {}: {}", status, error_text));
+                }
 
-        if !get_response.status().is_success() {
-            let status = get_response.status();
-            let error_text = get_response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("HTTP error {}: {}", status, error_text));
-        }
+                let time_entries: Vec<TimeEntry> = get_response.json().await
+                    .map_err(|e| format!("Failed to parse time entry: {}", e))?;
 
-        let time_entries: Vec<TimeEntry> = get_response.json().await
-            .map_err(|e| format!("Failed to parse time entry: {}", e))?;
-        
-        let time_entry = time_entries.first()
-            .ok_or("Time entry not found")?;
+                let time_entry = time_entries.first()
+                    .ok_or("Time entry not found")?;
+                Some(time_entry.start_time)
+            }
+            Err(e) => {
+                // Offline: the entry may only exist in the sync queue so far
+                log::warn!("Failed to fetch time entry {}, using queued copy: {}", entry_id, e);
+                crate::sync_queue::pending_insert("time_entries", entry_id)
+                    .and_then(|payload| serde_json::from_value::<TimeEntry>(payload).ok())
+                    .map(|entry| entry.start_time)
+                    .or(started_at)
+            }
+        };
+        Ok(start_time)
+    }
 
-        let end_time = chrono::Utc::now();
-        let start_time = time_entry.start_time;
-        
-        // Calculate duration in seconds
-        let duration_seconds = (end_time - start_time).num_seconds();
-        
-        let update_data = json!({
+    // Write the end time, and the duration when the start is known
+    async fn close_time_entry(
+        db: &Database,
+        entry_id: &str,
+        start_time: Option<chrono::DateTime<chrono::Utc>>,
+        end_time: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), String> {
+        let mut update_data = json!({
             "end_time": end_time.to_rfc3339(),
-            "duration_seconds": duration_seconds,
             "is_active": false,
             "updated_at": chrono::Utc::now().to_rfc3339()
         });
 
+        // Calculate duration in seconds
+        if let Some(start_time) = start_time {
+            update_data["duration_seconds"] = json!((end_time - start_time).num_seconds());
+        }
+
+        // Routed through execute_query so the update is queued if the network is down
+        match db.execute_query(&format!("time_entries?id=eq.{}", entry_id), "PATCH", Some(update_data)).await {
+            Ok(_) | Err(DbError::Queued) => Ok(()),
+            Err(e) => Err(format!("Failed to end time entry: {}", e)),
+        }
+    }
+
+    /// Close a running entry at `at` and open a new one for the same app and task starting
+    /// at that same instant, so a long session is split without losing any time between them.
+    /// Ok(None) means the entry was closed but its continuation couldn't be created
+    pub async fn roll_over_time_entry(db: &Database, entry_id: &str, at: chrono::DateTime<chrono::Utc>) -> Result<Option<TimeEntry>, String> {
         let url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, entry_id);
+        let response = db.client
+            .get(&url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch time entry: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch time entry: {}", response.status()));
+        }
+
+        let entries: Vec<TimeEntry> = response.json().await
+            .map_err(|e| format!("Failed to parse time entry: {}", e))?;
+        let entry = entries.into_iter().next().ok_or("Time entry not found")?;
+
+        Self::close_time_entry(db, &entry.id, Some(entry.start_time), at).await?;
+
+        let workspace_id = Self::current_workspace_id(db, &entry.user_id).await;
+        let next_entry = json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "user_id": entry.user_id,
+            "app_id": entry.app_id,
+            "task_id": entry.task_id,
+            "workspace_id": workspace_id,
+            "start_time": at.to_rfc3339(),
+            "end_time": null,
+            "duration_seconds": null,
+            "is_active": true,
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "updated_at": chrono::Utc::now().to_rfc3339()
+        });
+
+        let created = match db.upsert("time_entries", "id", next_entry.clone()).await {
+            Err(DbError::Queued) => Ok(json!([next_entry])),
+            response => response,
+        };
+        let created = created
+            .map_err(|e| format!("Failed to create time entry: {}", e))
+            .and_then(|response| {
+                serde_json::from_value::<Vec<TimeEntry>>(response)
+                    .map_err(|e| format!("Failed to parse created time entry: {}", e))
+            })
+            .and_then(|created| created.into_iter().next().ok_or_else(|| "No time entry was created".to_string()));
+
+        match created {
+            Ok(created) => {
+                log::info!("Split long session: ended {} and continued as {}", entry.id, created.id);
+                Ok(Some(created))
+            }
+            Err(e) => {
+                log::warn!("Ended long session {} but could not continue it: {}", entry.id, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record when an app was last used without holding up the caller.
+    /// last_used only moves forward, so back-dated manual entries don't rewind it.
+    pub fn mark_application_used(db: &Database, app_id: &str, used_at: chrono::DateTime<chrono::Utc>) {
+        let db = db.clone();
+        let app_id = app_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = Self::set_application_last_used(&db, &app_id, used_at).await {
+                log::debug!("Could not update last_used for app {}: {}", app_id, e);
+            }
+        });
+    }
+
+    async fn set_application_last_used(db: &Database, app_id: &str, used_at: chrono::DateTime<chrono::Utc>) -> Result<(), String> {
+        if db.base_url.is_empty() {
+            return Ok(());
+        }
+
+        let used_at = used_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let url = format!(
+            "{}/rest/v1/applications?id=eq.{}&or=(last_used.is.null,last_used.lt.{})",
+            db.base_url, app_id, used_at
+        );
         let response = db.client
             .patch(&url)
             .header("apikey", &db.api_key)
             .header("Authorization", format!("Bearer {}", db.api_key))
             .header("Content-Type", "application/json")
-            .header("Prefer", "return=representation")
-            .json(&update_data)
+            .header("Prefer", "return=minimal")
+            .json(&json!({ "last_used": used_at }))
             .send()
             .await
-            .map_err(|e| format!("Failed to update time entry: {}", e))?;
+            .map_err(|e| format!("Failed to update last_used: {}", e))?;
 
-        if response.status().is_success() {
-            println!("Successfully ended time entry {}", entry_id);
-            Ok(())
-        } else {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            Err(format!("Failed to end time entry: HTTP {} - {}", status, error_text))
+        if !response.status().is_success() {
+            return Err(format!("Failed to update last_used: {}", response.status()));
+        }
+        Ok(())
+    }
+
+    /// Look up a single application by id
+    pub async fn get_application(db: &Database, app_id: &str) -> Result<Option<Application>, String> {
+        let url = format!("{}/rest/v1/applications?id=eq.{}", db.base_url, app_id);
+        let response = db.client
+            .get(&url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch application: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch application: {}", response.status()));
         }
+
+        let apps: Vec<Application> = response.json().await
+            .map_err(|e| format!("Failed to parse application: {}", e))?;
+        Ok(apps.into_iter().next())
+    }
+
+    /// Look up many applications in one request. Duplicate ids are fetched once and
+    /// ids with no matching application are left out
+    pub async fn get_applications_by_ids(db: &Database, ids: &[String]) -> Result<Vec<Application>, String> {
+        let mut unique: Vec<&str> = Vec::new();
+        for id in ids {
+            if !unique.contains(&id.as_str()) {
+                unique.push(id);
+            }
+        }
+        if unique.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let id_filter = format!("in.({})", unique.join(","));
+        let response = db
+            .execute_query_with_params("applications", "GET", &[("id", id_filter.as_str())], None, None)
+            .await
+            .map_err(|e| format!("Failed to fetch applications: {}", e))?;
+        serde_json::from_value(response).map_err(|e| format!("Failed to parse applications: {}", e))
+    }
+
+    /// Mark running entries as still alive by bumping their updated_at.
+    /// Sent directly rather than queued, since a missed heartbeat is harmless.
+    pub async fn touch_time_entries(db: &Database, entry_ids: &[String]) -> Result<(), String> {
+        if entry_ids.is_empty() || db.base_url.is_empty() {
+            return Ok(());
+        }
+
+        let url = format!(
+            "{}/rest/v1/time_entries?id=in.({})&is_active=eq.true",
+            db.base_url,
+            entry_ids.join(",")
+        );
+        let response = db.client
+            .patch(&url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .header("Content-Type", "application/json")
+            .header("Prefer", "return=minimal")
+            .json(&json!({ "updated_at": chrono::Utc::now().to_rfc3339() }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to refresh time entries: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to refresh time entries: {}", response.status()));
+        }
+
+        log::debug!("Heartbeat refreshed {} active time entries", entry_ids.len());
+        Ok(())
+    }
+
+    /// Which of the given entries are still open in the database
+    pub async fn still_active_entry_ids(db: &Database, entry_ids: &[String]) -> Result<Vec<String>, String> {
+        if entry_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids = format!("in.({})", entry_ids.join(","));
+        let response = db
+            .execute_query_with_params(
+                "time_entries",
+                "GET",
+                &[("id", ids.as_str()), ("is_active", "eq.true")],
+                Some("id"),
+                None,
+            )
+            .await
+            .map_err(|e| format!("Failed to check active time entries: {}", e))?;
+
+        let rows: Vec<serde_json::Value> = serde_json::from_value(response)
+            .map_err(|e| format!("Failed to parse active time entries: {}", e))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| row["id"].as_str().map(str::to_string))
+            .collect())
     }
 
     /// Get tracked applications for the current user
@@ -182,13 +498,251 @@ impl DatabaseHelpers {
         if response.status().is_success() {
             let apps: Vec<Application> = response.json().await
                 .map_err(|e| format!("Failed to parse applications: {}", e))?;
-            Ok(apps)
+            Ok(Self::apply_process_aliases(db, &user_id, apps).await)
         } else {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             Err(format!("HTTP error {}: {}", status, error_text))
         }
     }
+
+    /// Replace each app's process name with the alias stored for this platform, if any,
+    /// so an app set up on another OS matches by its local identifier
+    pub async fn apply_process_aliases(db: &Database, user_id: &str, mut apps: Vec<Application>) -> Vec<Application> {
+        if apps.is_empty() {
+            return apps;
+        }
+
+        let url = format!(
+            "{}/rest/v1/process_aliases?user_id=eq.{}&platform=eq.{}&select=app_id,process_name",
+            db.base_url,
+            encode_filter_value(user_id),
+            super::detect_os().as_str()
+        );
+        let response = db.client
+            .get(&url)
+            .header("apikey", &db.api_key)
+            .header("Authorization", format!("Bearer {}", db.api_key))
+            .send()
+            .await;
+
+        // Aliases are optional, so a failed lookup just leaves the stored process names
+        let aliases: Vec<serde_json::Value> = match response {
+            Ok(response) if response.status().is_success() => response.json().await.unwrap_or_default(),
+            Ok(response) => {
+                log::debug!("Could not fetch process aliases: {}", response.status());
+                return apps;
+            }
+            Err(e) => {
+                log::debug!("Could not fetch process aliases: {}", e);
+                return apps;
+            }
+        };
+
+        for alias in aliases {
+            let (Some(app_id), Some(process_name)) = (alias["app_id"].as_str(), alias["process_name"].as_str()) else {
+                continue;
+            };
+            if let Some(app) = apps.iter_mut().find(|app| app.id == app_id) {
+                log::debug!("Using alias '{}' for {} on this platform", process_name, app.name);
+                app.process_name = process_name.to_string();
+            }
+        }
+        apps
+    }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    fn open_entry(id: &str, started_ago: chrono::Duration, heartbeat_ago: chrono::Duration) -> serde_json::Value {
+        let now = chrono::Utc::now();
+        json!({
+            "id": id,
+            "user_id": "user-1",
+            "app_id": "app-1",
+            "start_time": (now - started_ago).to_rfc3339(),
+            "is_active": true,
+            "updated_at": (now - heartbeat_ago).to_rfc3339()
+        })
+    }
+
+    #[tokio::test]
+    async fn orphaned_entries_end_at_their_last_heartbeat() {
+        let server = MockServer::start().await;
+        let stale = open_entry("entry-stale", chrono::Duration::hours(5), chrono::Duration::hours(3));
+        let last_heartbeat = stale["updated_at"].as_str().unwrap().to_string();
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("end_time", "is.null"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                open_entry("entry-running", chrono::Duration::hours(1), chrono::Duration::hours(1)),
+                stale,
+                // Still heartbeating, so another device is tracking it
+                open_entry("entry-elsewhere", chrono::Duration::hours(1), chrono::Duration::seconds(30))
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-stale"))
+            .and(move |request: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                body["end_time"].as_str().and_then(|end| chrono::DateTime::parse_from_rfc3339(end).ok())
+                    == chrono::DateTime::parse_from_rfc3339(&last_heartbeat).ok()
+                    && body["duration_seconds"] == 2 * 3600
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        crate::current_user::set_current_user_id("user-1".to_string());
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let (ended, failed) = DatabaseHelpers::end_orphaned_entries(&db, &["entry-running".to_string()]).await.unwrap();
+
+        assert_eq!((ended, failed), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn new_entries_go_to_the_most_recently_joined_workspace() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/workspace_members"))
+            .and(query_param("user_id", "eq.user+1"))
+            .and(query_param("order", "joined_at.desc.nullslast"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "workspace_id": "ws-new" }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let workspace_id = DatabaseHelpers::current_workspace_id(&db, "user+1").await;
+
+        assert_eq!(workspace_id.as_deref(), Some("ws-new"));
+    }
+
+    #[tokio::test]
+    async fn an_entry_ended_offline_keeps_the_start_time_the_tracker_knows() {
+        // Nothing listens on port 1, so the lookup fails like it does offline
+        let db = Database::new("http://127.0.0.1:1".to_string(), "test-key".to_string()).unwrap();
+        let started_at = chrono::Utc::now() - chrono::Duration::minutes(30);
+
+        let start_time = DatabaseHelpers::entry_start_time(&db, "entry-1", Some(started_at)).await.unwrap();
+
+        assert_eq!(start_time, Some(started_at));
+    }
+
+    // Serves `entry-1`, open for nine hours, and accepts closing it
+    async fn long_session_server(create_status: u16) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                open_entry("entry-1", chrono::Duration::hours(9), chrono::Duration::minutes(1))
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(move |request: &Request| {
+                let row: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                ResponseTemplate::new(create_status).set_body_json(json!([row]))
+            })
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn a_rolled_over_entry_continues_from_the_same_instant() {
+        let server = long_session_server(201).await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let at = chrono::Utc::now();
+
+        let next = DatabaseHelpers::roll_over_time_entry(&db, "entry-1", at).await.unwrap().unwrap();
+
+        assert_ne!(next.id, "entry-1");
+        assert_eq!(next.app_id.as_deref(), Some("app-1"));
+        assert_eq!(next.start_time.timestamp(), at.timestamp());
+        assert!(next.is_active);
+    }
+
+    #[tokio::test]
+    async fn a_roll_over_that_cannot_continue_still_closes_the_entry() {
+        let server = long_session_server(500).await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+
+        let next = DatabaseHelpers::roll_over_time_entry(&db, "entry-1", chrono::Utc::now()).await.unwrap();
+
+        // The PATCH expectation checks the close went through
+        assert!(next.is_none());
+    }
+
+    #[tokio::test]
+    async fn process_aliases_replace_the_stored_process_name() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/process_aliases"))
+            .and(query_param("user_id", "eq.user+1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "app_id": "app-code", "process_name": "com.microsoft.VSCode" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let app = |id: &str, process_name: &str| -> Application {
+            serde_json::from_value(json!({
+                "id": id, "user_id": "user+1", "name": id, "process_name": process_name, "is_tracked": true
+            }))
+            .unwrap()
+        };
+
+        let apps = DatabaseHelpers::apply_process_aliases(&db, "user+1", vec![app("app-code", "Code.exe"), app("app-slack", "slack.exe")]).await;
+
+        assert_eq!(apps[0].process_name, "com.microsoft.VSCode");
+        assert_eq!(apps[1].process_name, "slack.exe");
+    }
+
+    #[tokio::test]
+    async fn applications_are_fetched_by_id_in_one_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/applications"))
+            .and(query_param("id", "in.(app-1,app-2)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "id": "app-1", "name": "Code", "process_name": "Code.exe" },
+                { "id": "app-2", "name": "Slack", "process_name": "slack.exe" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let ids = vec!["app-1".to_string(), "app-2".to_string(), "app-1".to_string()];
+
+        let apps = DatabaseHelpers::get_applications_by_ids(&db, &ids).await.unwrap();
+        let none = DatabaseHelpers::get_applications_by_ids(&db, &[]).await.unwrap();
+
+        assert_eq!(apps.iter().map(|app| app.name.as_str()).collect::<Vec<_>>(), vec!["Code", "Slack"]);
+        assert!(none.is_empty());
+    }
+}
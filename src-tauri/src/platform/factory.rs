@@ -11,19 +11,19 @@ impl TrackerFactory {
     pub fn create_tracker(db: Database) -> PlatformTracker {
         match crate::platform::detect_os() {
             OperatingSystem::Windows => {
-                println!("Creating Windows tracker");
+                log::debug!("Creating Windows tracker");
                 PlatformTracker::Windows(WindowsTracker::new(db))
             },
             OperatingSystem::MacOS => {
-                println!("Creating macOS tracker");
+                log::debug!("Creating macOS tracker");
                 PlatformTracker::MacOS(MacOSTracker::new(db))
             },
             OperatingSystem::Linux => {
-                println!("Creating Windows tracker for Linux (fallback)");
+                log::debug!("Creating Windows tracker for Linux (fallback)");
                 PlatformTracker::Windows(WindowsTracker::new(db))
             },
             OperatingSystem::Unknown => {
-                println!("Unknown OS, using Windows tracker as fallback");
+                log::debug!("Unknown OS, using Windows tracker as fallback");
                 PlatformTracker::Windows(WindowsTracker::new(db))
             },
         }
@@ -33,19 +33,19 @@ impl TrackerFactory {
     pub fn create_tracker_for_os(db: Database, os: OperatingSystem) -> PlatformTracker {
         match os {
             OperatingSystem::Windows => {
-                println!("Creating Windows tracker");
+                log::debug!("Creating Windows tracker");
                 PlatformTracker::Windows(WindowsTracker::new(db))
             },
             OperatingSystem::MacOS => {
-                println!("Creating macOS tracker");
+                log::debug!("Creating macOS tracker");
                 PlatformTracker::MacOS(MacOSTracker::new(db))
             },
             OperatingSystem::Linux => {
-                println!("Creating Windows tracker for Linux (fallback)");
+                log::debug!("Creating Windows tracker for Linux (fallback)");
                 PlatformTracker::Windows(WindowsTracker::new(db))
             },
             OperatingSystem::Unknown => {
-                println!("Unknown OS, using Windows tracker as fallback");
+                log::debug!("Unknown OS, using Windows tracker as fallback");
                 PlatformTracker::Windows(WindowsTracker::new(db))
             },
         }
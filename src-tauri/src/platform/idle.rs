@@ -0,0 +1,172 @@
+//! Time since the user last pressed a key or moved the mouse, read from the OS.
+
+/// Seconds since the last keyboard or mouse input, or 0 when the OS can't tell us
+pub fn idle_time_seconds() -> u64 {
+    system_idle_seconds().unwrap_or(0)
+}
+
+#[cfg(target_os = "windows")]
+fn system_idle_seconds() -> Option<u64> {
+    use winapi::um::sysinfoapi::GetTickCount;
+    use winapi::um::winuser::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+
+    // Both are tick counts that wrap every ~49 days, so wrapping_sub keeps the difference right
+    let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(u64::from(idle_ms) / 1000)
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventSourceSecondsSinceLastEventType(source_state_id: i32, event_type: u32) -> f64;
+}
+
+#[cfg(target_os = "macos")]
+fn system_idle_seconds() -> Option<u64> {
+    const COMBINED_SESSION_STATE: i32 = 0; // kCGEventSourceStateCombinedSessionState
+    const ANY_INPUT_EVENT: u32 = u32::MAX; // kCGAnyInputEventType
+
+    let seconds = unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT) };
+    (seconds.is_finite() && seconds >= 0.0).then(|| seconds as u64)
+}
+
+#[cfg(target_os = "linux")]
+fn system_idle_seconds() -> Option<u64> {
+    linux::idle_seconds()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn system_idle_seconds() -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use once_cell::sync::Lazy;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use zbus::blocking::{Connection, Proxy};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub(super) enum IdleSource {
+        XScreenSaver,
+        ScreenSaver, // org.freedesktop.ScreenSaver on the session bus
+        Logind,      // IdleHint on the logind session
+    }
+
+    static SESSION_BUS: Lazy<Option<Connection>> = Lazy::new(|| Connection::session().ok());
+    static SYSTEM_BUS: Lazy<Option<Connection>> = Lazy::new(|| Connection::system().ok());
+
+    // Picked on first use; a source that can't answer then is not retried
+    static SOURCE: Lazy<Option<IdleSource>> = Lazy::new(|| {
+        let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+        let source = candidate_sources(wayland).into_iter().find(|source| source.read().is_some());
+        match source {
+            Some(source) => log::info!("Idle detection is using {:?}", source),
+            None => log::warn!("No idle source available, idle time will always read as 0"),
+        }
+        source
+    });
+
+    /// Sources to try in order. Under Wayland, XScreenSaver only sees input to
+    /// XWayland clients, so the compositor's D-Bus services go first
+    pub(super) fn candidate_sources(wayland: bool) -> [IdleSource; 3] {
+        if wayland {
+            [IdleSource::ScreenSaver, IdleSource::Logind, IdleSource::XScreenSaver]
+        } else {
+            [IdleSource::XScreenSaver, IdleSource::ScreenSaver, IdleSource::Logind]
+        }
+    }
+
+    pub(super) fn idle_seconds() -> Option<u64> {
+        (*SOURCE)?.read()
+    }
+
+    impl IdleSource {
+        fn read(self) -> Option<u64> {
+            match self {
+                IdleSource::XScreenSaver => xscreensaver_idle_seconds(),
+                IdleSource::ScreenSaver => screensaver_idle_seconds(),
+                IdleSource::Logind => logind_idle_seconds(),
+            }
+        }
+    }
+
+    fn xscreensaver_idle_seconds() -> Option<u64> {
+        // Loaded at runtime so machines without libXss still start
+        let xlib = x11_dl::xlib::Xlib::open().ok()?;
+        let xss = x11_dl::xss::Xss::open().ok()?;
+
+        unsafe {
+            let display = (xlib.XOpenDisplay)(std::ptr::null());
+            if display.is_null() {
+                return None;
+            }
+
+            let info = (xss.XScreenSaverAllocInfo)();
+            let idle_ms = if !info.is_null()
+                && (xss.XScreenSaverQueryInfo)(display, (xlib.XDefaultRootWindow)(display), info) != 0
+            {
+                Some((*info).idle as u64)
+            } else {
+                None
+            };
+
+            if !info.is_null() {
+                (xlib.XFree)(info.cast());
+            }
+            (xlib.XCloseDisplay)(display);
+            idle_ms.map(|ms| ms / 1000)
+        }
+    }
+
+    fn screensaver_idle_seconds() -> Option<u64> {
+        let bus = SESSION_BUS.as_ref()?;
+        let proxy = Proxy::new(
+            bus,
+            "org.freedesktop.ScreenSaver",
+            "/org/freedesktop/ScreenSaver",
+            "org.freedesktop.ScreenSaver",
+        )
+        .ok()?;
+        let idle_ms: u32 = proxy.call("GetSessionIdleTime", &()).ok()?;
+        Some(u64::from(idle_ms) / 1000)
+    }
+
+    fn logind_idle_seconds() -> Option<u64> {
+        let bus = SYSTEM_BUS.as_ref()?;
+        let proxy = Proxy::new(
+            bus,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1/session/auto",
+            "org.freedesktop.login1.Session",
+        )
+        .ok()?;
+        if !proxy.get_property::<bool>("IdleHint").ok()? {
+            return Some(0);
+        }
+
+        // Wall-clock microseconds since the epoch at which the session went idle
+        let idle_since_us: u64 = proxy.get_property("IdleSinceHint").ok()?;
+        let now_us = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_micros() as u64;
+        Some(now_us.saturating_sub(idle_since_us) / 1_000_000)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::linux::{candidate_sources, IdleSource};
+
+    #[test]
+    fn wayland_prefers_dbus_over_xscreensaver() {
+        assert_eq!(candidate_sources(true)[0], IdleSource::ScreenSaver);
+        assert_eq!(candidate_sources(false)[0], IdleSource::XScreenSaver);
+    }
+}
@@ -0,0 +1,242 @@
+//! Focused-application detection on Linux.
+//!
+//! Wayland compositors don't let clients read the active window globally, so
+//! each desktop is asked through its own D-Bus interface. X11 sessions use the
+//! EWMH `_NET_ACTIVE_WINDOW` property. When neither works we fall back to the
+//! process that used the most CPU over a short sample.
+
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const CPU_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+const KWIN_SCRIPT_MARKER: &str = "macro-focused-app";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionType {
+    Wayland,
+    X11,
+    Unknown,
+}
+
+/// Session type reported by the login manager in XDG_SESSION_TYPE
+pub fn session_type() -> SessionType {
+    match std::env::var("XDG_SESSION_TYPE").map(|s| s.to_lowercase()).as_deref() {
+        Ok("wayland") => SessionType::Wayland,
+        Ok("x11") => SessionType::X11,
+        _ if std::env::var_os("WAYLAND_DISPLAY").is_some() => SessionType::Wayland,
+        _ if std::env::var_os("DISPLAY").is_some() => SessionType::X11,
+        _ => SessionType::Unknown,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FocusMethod {
+    Gnome,
+    KWin,
+    X11,
+    MostActiveProcess,
+}
+
+// Picked on the first poll so later polls don't retry interfaces this desktop lacks
+static FOCUS_METHOD: OnceLock<FocusMethod> = OnceLock::new();
+
+fn detect_focus_method() -> FocusMethod {
+    match session_type() {
+        SessionType::Wayland if gnome_windows().is_some() => FocusMethod::Gnome,
+        SessionType::Wayland if kwin_available() => FocusMethod::KWin,
+        SessionType::X11 => FocusMethod::X11,
+        _ => FocusMethod::MostActiveProcess,
+    }
+}
+
+/// Name of the focused application, using the strategy detected for this session.
+/// Blocks while external tools run, so call it from a blocking task.
+pub fn focused_app() -> Option<String> {
+    // The outer None means the desktop couldn't be asked; Some(None) means it answered
+    // that nothing is focused, which shouldn't be replaced by a guess
+    let detected = match *FOCUS_METHOD.get_or_init(detect_focus_method) {
+        FocusMethod::Gnome => gnome_windows().map(|output| parse_gnome_windows(&output)),
+        FocusMethod::KWin => kwin_focused_app(),
+        FocusMethod::X11 => x11_focused_app(),
+        FocusMethod::MostActiveProcess => None,
+    };
+    detected.unwrap_or_else(most_active_process)
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Turn a desktop app id like "org.gnome.Terminal.desktop" into a process-like name
+fn clean_app_id(app_id: &str) -> Option<String> {
+    let app_id = app_id.trim().trim_end_matches(".desktop");
+    if app_id.is_empty() {
+        None
+    } else {
+        Some(app_id.to_string())
+    }
+}
+
+// GNOME Shell: org.gnome.Shell.Introspect.GetWindows returns every window with
+// its app id and focus state. Newer GNOME releases restrict this to allowlisted
+// callers, in which case the call fails and the next strategy is tried.
+fn gnome_windows() -> Option<String> {
+    run(
+        "gdbus",
+        &[
+            "call", "--session",
+            "--dest", "org.gnome.Shell",
+            "--object-path", "/org/gnome/Shell/Introspect",
+            "--method", "org.gnome.Shell.Introspect.GetWindows",
+        ],
+    )
+}
+
+fn parse_gnome_windows(output: &str) -> Option<String> {
+    let app_id = regex::Regex::new(r"'app-id': <'([^']*)'>").ok()?;
+    // Each window's properties follow a "uint64 <id>:" key
+    output
+        .split("uint64 ")
+        .find(|window| window.contains("'has-focus': <true>"))
+        .and_then(|window| app_id.captures(window))
+        .and_then(|captures| clean_app_id(&captures[1]))
+}
+
+fn kwin_available() -> bool {
+    run(
+        "dbus-send",
+        &["--session", "--dest=org.kde.KWin", "--print-reply", "/Scripting", "org.freedesktop.DBus.Peer.Ping"],
+    )
+    .is_some()
+}
+
+// KWin: load a one-shot script that prints the active window's resource class,
+// then read the line back from the user journal where KWin logs script output.
+// The marker carries a fresh nonce so an earlier poll's line is never read back.
+fn kwin_focused_app() -> Option<Option<String>> {
+    let marker = format!("{}-{}:", KWIN_SCRIPT_MARKER, uuid::Uuid::new_v4());
+    let script_path = std::env::temp_dir().join("macro-kwin-focus.js");
+    let script = format!(
+        "var w = workspace.activeWindow || workspace.activeClient; \
+         print('{}' + (w ? w.resourceClass : ''));",
+        marker
+    );
+    fs::write(&script_path, script).ok()?;
+    let script_path = script_path.to_string_lossy().into_owned();
+    let plugin_name = "macro-focus";
+
+    // Unload any copy left over from a previous poll before loading again
+    let _ = run(
+        "dbus-send",
+        &["--session", "--dest=org.kde.KWin", "--print-reply", "/Scripting",
+          "org.kde.kwin.Scripting.unloadScript", &format!("string:{}", plugin_name)],
+    );
+    let reply = run(
+        "dbus-send",
+        &["--session", "--dest=org.kde.KWin", "--print-reply", "/Scripting",
+          "org.kde.kwin.Scripting.loadScript", &format!("string:{}", script_path), &format!("string:{}", plugin_name)],
+    )?;
+    let script_id = reply.split_whitespace().last()?.to_string();
+    run(
+        "dbus-send",
+        &["--session", "--dest=org.kde.KWin", "--print-reply",
+          &format!("/Scripting/Script{}", script_id), "org.kde.kwin.Script.run"],
+    )?;
+
+    let journal = run("journalctl", &["--user", "--since", "-5s", "-o", "cat", "--no-pager"])?;
+    let resource_class = journal
+        .lines()
+        .rev()
+        .find_map(|line| line.split(marker.as_str()).nth(1))?;
+    Some(clean_app_id(resource_class))
+}
+
+// X11: read _NET_ACTIVE_WINDOW from the root window, then that window's PID
+fn x11_focused_app() -> Option<Option<String>> {
+    let active = run("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window_id = active.split_whitespace().last()?.trim_end_matches(',').to_string();
+    if window_id == "0x0" {
+        return Some(None);
+    }
+    let pid_line = run("xprop", &["-id", &window_id, "_NET_WM_PID"])?;
+    // Windows that don't set _NET_WM_PID can't be named, but something is still focused
+    let pid = pid_line.split_whitespace().last().and_then(|pid| pid.parse::<u32>().ok());
+    Some(pid.and_then(process_name))
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+// utime + stime (fields 14 and 15) from /proc/<pid>/stat, in clock ticks
+fn cpu_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name is parenthesised and may contain spaces, so split after it
+    let after_name = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = after_name.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+fn sample_cpu_ticks() -> HashMap<u32, u64> {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return HashMap::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| cpu_ticks(pid).map(|ticks| (pid, ticks)))
+        .collect()
+}
+
+// Last resort: the non-system process that used the most CPU over a short sample
+fn most_active_process() -> Option<String> {
+    let before = sample_cpu_ticks();
+    std::thread::sleep(CPU_SAMPLE_INTERVAL);
+    let after = sample_cpu_ticks();
+
+    let mut busiest: Vec<(u64, u32)> = after
+        .iter()
+        .filter_map(|(pid, ticks)| before.get(pid).map(|previous| (ticks.saturating_sub(*previous), *pid)))
+        .filter(|(delta, _)| *delta > 0)
+        .collect();
+    busiest.sort_by(|a, b| b.0.cmp(&a.0));
+
+    busiest
+        .into_iter()
+        .filter_map(|(_, pid)| process_name(pid))
+        .find(|name| !crate::process_filter::is_ignored(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_focused_gnome_window_gives_the_app_id() {
+        let output = "([(uint64 2185010386, {'app-id': <'org.gnome.Nautilus.desktop'>, 'has-focus': <false>, 'title': <'Home'>}), \
+                      (uint64 2185010390, {'app-id': <'org.gnome.Terminal.desktop'>, 'has-focus': <true>, 'title': <'Terminal'>})],)";
+
+        assert_eq!(parse_gnome_windows(output).as_deref(), Some("org.gnome.Terminal"));
+    }
+
+    #[test]
+    fn no_focused_gnome_window_gives_nothing() {
+        let output = "([(uint64 2185010386, {'app-id': <'org.gnome.Nautilus.desktop'>, 'has-focus': <false>}), \
+                      (uint64 2185010390, {'app-id': <''>, 'has-focus': <true>})],)";
+
+        assert_eq!(parse_gnome_windows(output), None);
+        assert_eq!(parse_gnome_windows(""), None);
+    }
+}
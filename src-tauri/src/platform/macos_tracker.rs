@@ -1,9 +1,9 @@
-use crate::database::Database;
-use crate::platform::{BaseTracker, database_helpers::DatabaseHelpers};
+use crate::database::{Application, Database};
+use crate::platform::{BaseTracker, FocusDiagnosis, database_helpers::DatabaseHelpers};
 use crate::tracking::CurrentActivity;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::time::interval;
 
 // Ensure AppKit is linked for NSWorkspace usage on macOS
 #[cfg(target_os = "macos")]
@@ -27,18 +27,6 @@ impl MacOSTracker {
         }
     }
 
-    async fn cleanup_existing_active_entries(&self) -> Result<(), String> {
-        // Get all active time entries and end them
-        let active_entries = DatabaseHelpers::get_active_time_entries(&self.base.db).await?;
-        
-        for entry in active_entries {
-            let _ = DatabaseHelpers::end_time_entry(&self.base.db, entry.id).await;
-            println!("Cleaned up existing active entry for app_id: {:?}", entry.app_id);
-        }
-        
-        Ok(())
-    }
-
     #[cfg(target_os = "macos")]
     async fn get_frontmost_application(&self) -> Result<Option<(String, String)>, String> {
         // Use NSWorkspace.shared.frontmostApplication for accurate foreground app detection
@@ -115,24 +103,82 @@ impl MacOSTracker {
         Ok(None)
     }
 
-    async fn categorize_app(&self, app_name: &str) -> String {
-        let name_lower = app_name.to_lowercase();
-        
-        if name_lower.contains("chrome") || name_lower.contains("firefox") || name_lower.contains("safari") {
-            "Browser".to_string()
-        } else if name_lower.contains("code") || name_lower.contains("xcode") || name_lower.contains("terminal") {
-            "Development".to_string()
-        } else if name_lower.contains("pages") || name_lower.contains("numbers") || name_lower.contains("keynote") {
-            "Productivity".to_string()
-        } else if name_lower.contains("steam") || name_lower.contains("game") {
-            "Gaming".to_string()
-        } else if name_lower.contains("discord") || name_lower.contains("slack") || name_lower.contains("messages") {
-            "Communication".to_string()
-        } else {
-            "Other".to_string()
+    // Apps with an ordinary, non-minimized window on the active Space, as (name, bundle id)
+    #[cfg(target_os = "macos")]
+    async fn get_visible_applications(&self) -> Vec<(String, String)> {
+        unsafe {
+            use core_foundation::base::{CFType, TCFType};
+            use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+            use core_foundation::number::CFNumber;
+            use core_foundation::string::CFString;
+            use core_graphics::window::{
+                copy_window_info, kCGNullWindowID, kCGWindowLayer, kCGWindowListExcludeDesktopElements,
+                kCGWindowListOptionOnScreenOnly, kCGWindowOwnerName, kCGWindowOwnerPID,
+            };
+            use objc::{class, msg_send, sel, sel_impl};
+            use objc::runtime::Object;
+            use std::ffi::CStr;
+            use std::os::raw::c_char;
+
+            // On-screen only leaves out minimized windows and those on other Spaces
+            let Some(windows) = copy_window_info(
+                kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements,
+                kCGNullWindowID,
+            ) else {
+                return Vec::new();
+            };
+
+            let layer_key = CFString::wrap_under_get_rule(kCGWindowLayer);
+            let owner_name_key = CFString::wrap_under_get_rule(kCGWindowOwnerName);
+            let owner_pid_key = CFString::wrap_under_get_rule(kCGWindowOwnerPID);
+
+            let mut apps: Vec<(String, String)> = Vec::new();
+            for window in windows.iter() {
+                let info: CFDictionary<CFString, CFType> = CFDictionary::wrap_under_get_rule(*window as CFDictionaryRef);
+
+                // Layer 0 holds normal app windows; the menu bar, Dock and overlays sit above it
+                let layer = info.find(&layer_key).and_then(|value| value.downcast::<CFNumber>()).and_then(|n| n.to_i32());
+                if layer != Some(0) {
+                    continue;
+                }
+
+                let Some(name) = info.find(&owner_name_key).and_then(|value| value.downcast::<CFString>()).map(|s| s.to_string()) else {
+                    continue;
+                };
+                let Some(pid) = info.find(&owner_pid_key).and_then(|value| value.downcast::<CFNumber>()).and_then(|n| n.to_i32()) else {
+                    continue;
+                };
+                if apps.iter().any(|(existing, _)| *existing == name) {
+                    continue;
+                }
+
+                let running: *mut Object = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+                let mut bundle = String::from("Unknown");
+                if !running.is_null() {
+                    let bundle_nsstring: *mut Object = msg_send![running, bundleIdentifier];
+                    if !bundle_nsstring.is_null() {
+                        let bundle_ptr: *const c_char = msg_send![bundle_nsstring, UTF8String];
+                        if !bundle_ptr.is_null() {
+                            bundle = CStr::from_ptr(bundle_ptr).to_string_lossy().into_owned();
+                        }
+                    }
+                }
+
+                apps.push((name, bundle));
+            }
+            apps
         }
     }
 
+    #[cfg(not(target_os = "macos"))]
+    async fn get_visible_applications(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    async fn categorize_app(&self, app_name: &str) -> String {
+        crate::platform::categorize_app_name(app_name)
+    }
+
     async fn check_for_idle(&self) -> bool {
         let now = Instant::now();
         let time_since_last_activity = now.duration_since(self.last_activity_time);
@@ -175,69 +221,43 @@ impl MacOSTracker {
         };
         
         if already_tracking {
-            println!("macOS tracking is already running, skipping start");
+            log::info!("macOS tracking is already running, skipping start");
             return Ok(());
         }
         
         // Clean up existing active entries
-        self.cleanup_existing_active_entries().await?;
+        self.base.cleanup_existing_active_entries().await?;
         
         let mut state = self.base.state.lock().await;
         state.is_tracking = true;
         state.last_activity_time = Instant::now();
         drop(state);
 
-        // Start the tracking loop
-        let state_clone = Arc::clone(&self.base.state);
-        let db_clone = self.base.db.clone();
-        
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(5)); // Check every 5 seconds
-            loop {
-                interval.tick().await;
-                
-                let should_continue = {
-                    let state = state_clone.lock().await;
-                    state.is_tracking
-                };
-                
-                if !should_continue {
-                    break;
-                }
-                
-                // Update activity tracking
-                let tracker = MacOSTracker {
-                    base: BaseTracker {
-                        state: Arc::clone(&state_clone),
-                        db: db_clone.clone(),
-                    },
-                    idle_threshold: Duration::from_secs(300),
-                    last_activity_time: Instant::now(),
-                    idle_start_time: None,
-                };
-                
+        // Start the tracking loop, checking every 5 seconds; the ticks share one tracker
+        let tracker = Arc::new(MacOSTracker {
+            base: self.base.clone(),
+            idle_threshold: Duration::from_secs(300),
+            last_activity_time: Instant::now(),
+            idle_start_time: None,
+        });
+        self.base.spawn_loop(Duration::from_secs(5), move || {
+            let tracker = Arc::clone(&tracker);
+            async move {
                 if let Err(e) = tracker.update_activity().await {
-                    eprintln!("Error updating macOS activity: {}", e);
+                    log::error!("Error updating macOS activity: {}", e);
                 }
+
+                tracker.base.heartbeat_if_due().await;
+                tracker.base.split_long_sessions().await;
             }
-        });
+        }).await;
 
         Ok(())
     }
 
     pub async fn stop_tracking(&self) -> Result<(), String> {
-        let mut state = self.base.state.lock().await;
-        state.is_tracking = false;
-        
-        // End all active time entries
-        let entry_ids_to_end: Vec<String> = state.active_apps.values().cloned().collect();
-        drop(state);
-        
-        for entry_id in entry_ids_to_end {
-            let _ = DatabaseHelpers::end_time_entry(&self.base.db, entry_id).await;
-        }
-        
-        println!("Stopping macOS tracking");
+        self.base.stop_tracking().await;
+        log::info!("Stopping macOS tracking");
 
         Ok(())
     }
@@ -250,106 +270,35 @@ impl MacOSTracker {
                 return Ok(());
             }
             
-            let app_category = self.categorize_app(&app_name).await;
-            let start_time = chrono::Utc::now();
-            
             // Check for idle state
             if self.check_for_idle().await {
                 // Handle idle state
                 return Ok(());
             }
-            
-            let mut state = self.base.state.lock().await;
-            state.last_activity_time = Instant::now();
-            
-            // Get tracked applications from database
-            let tracked_apps = DatabaseHelpers::get_tracked_applications(&self.base.db).await?;
-            
-            // Debug: log current app and tracked apps
-            println!("🔍 Current app: '{}' (bundle: {})", app_name, bundle_id);
-            println!("🔍 Tracked apps count: {}", tracked_apps.len());
-            for app in &tracked_apps {
-                println!("  - {} (process_name: {})", app.name, app.process_name);
-            }
-            
-            // Check if the current app is in the tracked list
-            // On macOS, process_name is typically the bundle identifier, so match against bundle_id
-            // Also try matching by name as a fallback (helps with cross-platform apps)
-            let app_is_tracked = tracked_apps.iter().any(|app| {
-                // Primary match: bundle identifier to process_name (both are bundle IDs on macOS)
-                let bundle_match = names_match(&app.process_name, &bundle_id);
-                // Secondary match: app name to localized name (both are display names)
-                let name_match = names_match(&app.name, &app_name);
-                // Tertiary match: process_name to app_name (for edge cases where process_name might be name)
-                let fallback_match = names_match(&app.process_name, &app_name);
-                // Cross-platform match: try lenient name matching (helps with Windows->macOS migration)
-                let cross_platform_match = app_name_likely_matches(&app.name, &app.process_name, &app_name, &bundle_id);
-                
-                let matches = bundle_match || name_match || fallback_match || cross_platform_match;
-                if matches {
-                    println!("✅ Matched app '{}' (process_name: {}) - bundle: {}, name: {}, fallback: {}, cross-platform: {}", 
-                             app.name, app.process_name, bundle_match, name_match, fallback_match, cross_platform_match);
-                }
-                
-                matches
-            });
-            
-            // If app is not tracked, stop all active tracking
-            if !app_is_tracked && !state.active_apps.is_empty() {
-                println!("Current app '{}' (bundle: {}) is not in tracked list, stopping all active tracking", app_name, bundle_id);
-                
-                // End all active time entries
-                let entry_ids_to_end: Vec<String> = state.active_apps.values().cloned().collect();
-                state.active_apps.clear();
-                
-                for entry_id in &entry_ids_to_end {
-                    let _ = DatabaseHelpers::end_time_entry(&self.base.db, entry_id.clone()).await;
-                    println!("Ended time entry: {}", entry_id);
-                }
-            }
-            
-            // Only start/continue tracking if the current app is in the tracked list
-            if app_is_tracked {
-                // Find the tracked app that matches the current app
-                // Try bundle ID first (most reliable), then name, then cross-platform matching
-                let tracked_app = tracked_apps.iter().find(|app| {
-                    names_match(&app.process_name, &bundle_id) || 
-                    names_match(&app.name, &app_name) ||
-                    app_name_likely_matches(&app.name, &app.process_name, &app_name, &bundle_id)
-                });
-                
-                if let Some(tracked_app) = tracked_app {
-                    // Check if we're already tracking this app
-                    if let Some(_entry_id) = state.active_apps.get(&app_name) {
-                        // Continue existing entry
-                        // No need to do anything, entry continues
-                    } else {
-                        // Start new entry
-                        match DatabaseHelpers::start_time_entry(&self.base.db, tracked_app).await {
-                            Ok(entry_id) => {
-                                state.active_apps.insert(app_name.clone(), entry_id.clone());
-                                println!("Started tracking for {} (entry_id: {})", tracked_app.name, entry_id);
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to start time entry for {}: {}", tracked_app.name, e);
-                            }
-                        }
+
+            log::debug!("Current app: '{}' (bundle: {})", app_name, bundle_id);
+
+            // Entries are keyed by the localized app name; remember each app's bundle id
+            // so tracked apps can be matched on either
+            let mut bundles = HashMap::from([(app_name.clone(), bundle_id)]);
+
+            // In visible mode, other apps with a window on screen count as in use too
+            let mut visible_apps = Vec::new();
+            if crate::tracking_settings::tracking_mode() == crate::tracking_settings::TrackingMode::Visible {
+                for (name, bundle) in self.get_visible_applications().await {
+                    if name != app_name && !self.is_app_excluded(&bundle).await {
+                        visible_apps.push(name.clone());
+                        bundles.insert(name, bundle);
                     }
                 }
             }
-            
-            // Update cache - show current app even if not tracked
-            let app_name_clone = app_name.clone();
-            state.cached_current_activity = Some(CurrentActivity {
-                app_name,
-                app_category,
-                start_time,
-                duration_minutes: 0,
-                duration_hours: 0,
-                is_active: state.active_apps.contains_key(&app_name_clone), // Only active if being tracked
-                active_apps_count: state.active_apps.len(),
-            });
-            state.cache_last_updated = Instant::now();
+
+            self.base
+                .apply_foreground(Some(app_name), &visible_apps, |app, name| {
+                    let bundle = bundles.get(name).map(String::as_str).unwrap_or(name);
+                    is_running_app(app, name, bundle)
+                })
+                .await?;
         }
         
         Ok(())
@@ -364,7 +313,7 @@ impl MacOSTracker {
             }
             
             let app_category = self.categorize_app(&app_name).await;
-            let start_time = chrono::Utc::now();
+            let now = chrono::Utc::now();
             
             // Check if this app is being tracked in the database
             // Match against both app_name and bundle_id since active_apps might use either as key
@@ -372,17 +321,26 @@ impl MacOSTracker {
             let is_being_tracked = state.active_apps.keys().any(|k| {
                 names_match(k, &app_name) || names_match(k, &bundle_id)
             });
+            // Report how long the running entry has been open, not just this instant
+            let start_time = state
+                .tracked_since(|k| names_match(k, &app_name) || names_match(k, &bundle_id))
+                .unwrap_or(now);
             let active_apps_count = state.active_apps.len();
+            let focus_app_name = state.focus_app.as_ref().map(|app| app.name.clone());
+            let on_focus_app = state.focus_app.as_ref().map(|app| is_running_app(app, &app_name, &bundle_id));
             drop(state);
+            let elapsed = now - start_time;
             
             Ok(Some(CurrentActivity {
                 app_name,
                 app_category,
                 start_time,
-                duration_minutes: 0,
-                duration_hours: 0,
+                duration_minutes: elapsed.num_minutes(),
+                duration_hours: elapsed.num_hours(),
                 is_active: is_being_tracked,
                 active_apps_count,
+                focus_app_name,
+                on_focus_app,
             }))
         } else {
             Ok(None)
@@ -395,41 +353,138 @@ impl MacOSTracker {
     }
 
     pub async fn stop_tracking_for_app(&self, process_name: &str) -> Result<(), String> {
-        let mut state = self.base.state.lock().await;
-        
-        if let Some(_entry_id) = state.active_apps.remove(process_name) {
-            // For now, we'll just remove from tracking without database operations
-            println!("Stopped tracking for app: {}", process_name);
+        // active_apps is keyed by the localized app name on macOS, so match loosely
+        let ended = self.base.end_entries_matching(|key| names_match(key, process_name)).await?;
+        if ended > 0 {
+            log::info!("Stopped tracking for app: {}", process_name);
         }
-        
         Ok(())
     }
 
     pub async fn stop_tracking_for_app_by_id(&self, app_id: &str) -> Result<(), String> {
-        // For now, we'll skip database operations
-        println!("Stopped tracking for app ID: {}", app_id);
+        let app = match DatabaseHelpers::get_application(&self.base.db, app_id).await? {
+            Some(app) => app,
+            None => {
+                log::warn!("Could not find application with ID: {}", app_id);
+                return Ok(());
+            }
+        };
+
+        let ended = self.base
+            .end_entries_matching(|key| names_match(key, &app.name) || names_match(key, &app.process_name))
+            .await?;
+        if ended > 0 {
+            log::info!("Stopped tracking for app: {}", app.name);
+        }
         Ok(())
     }
 
+    pub async fn set_active_task(&self, task_id: Option<String>) {
+        let mut state = self.base.state.lock().await;
+        state.active_task_id = task_id;
+    }
+
+    pub async fn set_focus_app(&self, app: Option<Application>) -> Result<(), String> {
+        // active_apps is keyed by the localized app name on macOS
+        self.base.set_focus_app(app, |focus_app, key| is_running_app(focus_app, key, key)).await
+    }
+
+    pub async fn active_task_id(&self) -> Option<String> {
+        let state = self.base.state.lock().await;
+        state.active_task_id.clone()
+    }
+
     pub async fn is_tracking(&self) -> bool {
         let state = self.base.state.lock().await;
         state.is_tracking
     }
+
+    pub async fn forget_running_entries(&self) -> Vec<String> {
+        self.base.forget_running_entries().await
+    }
+
+    pub async fn running_entry_ids(&self) -> Vec<String> {
+        self.base.running_entry_ids().await
+    }
+
+    pub async fn forget_lost_entries(&self) -> Result<usize, String> {
+        self.base.forget_lost_entries().await
+    }
+
+    pub async fn invalidate_activity_cache(&self) {
+        self.base.invalidate_activity_cache().await
+    }
+
+    pub async fn refresh_tracked_apps(&self) {
+        self.base.refresh_tracked_apps().await
+    }
+
+    pub async fn diagnose_focus(&self, tracked_apps: &[Application]) -> Result<FocusDiagnosis, String> {
+        let Some((app_name, bundle_id)) = self.get_frontmost_application().await? else {
+            return Ok(FocusDiagnosis { focused_process: None, matched_app: None });
+        };
+        // Same matching update_activity uses to pick the tracked app
+        let matched_app = tracked_apps
+            .iter()
+            .find(|app| is_running_app(app, &app_name, &bundle_id))
+            .map(|app| app.name.clone());
+        Ok(FocusDiagnosis {
+            focused_process: Some(format!("{} ({})", app_name, bundle_id)),
+            matched_app,
+        })
+    }
 }
 
 // Helpers
-fn normalize_name(name: &str) -> String {
+// Whether an app on screen is the tracked app; process_name may hold the display name
+// rather than the bundle id, so that is tried too
+fn is_running_app(app: &Application, app_name: &str, bundle_id: &str) -> bool {
+    app_matches_process(app, app_name, bundle_id) || names_match(&app.process_name, app_name)
+}
+
+/// Whether a running app, seen by its display name and bundle id (or process name),
+/// is the stored application
+pub(crate) fn app_matches_process(app: &Application, app_name: &str, bundle_id: &str) -> bool {
+    names_match(&app.process_name, bundle_id)
+        || names_match(&app.name, app_name)
+        || app_name_likely_matches(&app.name, &app.process_name, app_name, bundle_id)
+}
+
+pub(crate) fn normalize_name(name: &str) -> String {
     // Remove .exe, .app extensions and normalize
-    name.trim()
+    let lower = name.trim().to_lowercase();
+    lower
         .trim_end_matches(".app")
         .trim_end_matches(".exe")
-        .to_lowercase()
+        .to_string()
+}
+
+// Shortest name allowed to match inside a longer one; "go" or "qt" would hit far too much
+const MIN_PARTIAL_MATCH_LEN: usize = 3;
+
+// Whether `needle` appears in `haystack` as whole words, e.g. "chrome" in
+// "com.google.chrome" or "code" in "visual studio code", but not "go" in "google chrome"
+fn contains_words(haystack: &str, needle: &str) -> bool {
+    if needle.chars().count() < MIN_PARTIAL_MATCH_LEN {
+        return false;
+    }
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+// Exact match after normalizing, or one name appearing as whole words inside the other
+fn normalized_names_match(a: &str, b: &str) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    a == b || contains_words(a, b) || contains_words(b, a)
 }
 
 fn names_match(a: &str, b: &str) -> bool {
-    let a_norm = normalize_name(a);
-    let b_norm = normalize_name(b);
-    a_norm == b_norm || a_norm.contains(&b_norm) || b_norm.contains(&a_norm)
+    normalized_names_match(&normalize_name(a), &normalize_name(b))
 }
 
 // More lenient matching for cross-platform scenarios
@@ -442,18 +497,53 @@ fn app_name_likely_matches(app_name: &str, process_name: &str, detected_name: &s
     let bundle_norm = normalize_name(detected_bundle);
     
     // Try exact matches first
-    if proc_norm == bundle_norm || proc_norm == detected_norm {
+    if !proc_norm.is_empty() && (proc_norm == bundle_norm || proc_norm == detected_norm) {
         return true;
     }
     
-    // Try name matches (more lenient)
-    if app_norm == detected_norm {
-        return true;
+    // Try name and partial matches (more lenient)
+    normalized_names_match(&app_norm, &detected_norm) || normalized_names_match(&proc_norm, &detected_norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_names_match_case_insensitively() {
+        assert!(names_match("Safari", "safari"));
+        assert!(names_match("  Notion ", "Notion"));
+    }
+
+    #[test]
+    fn strips_app_and_exe_extensions() {
+        assert!(names_match("Code.exe", "Code"));
+        assert!(names_match("CODE.EXE", "code"));
+        assert!(names_match("Slack.app", "Slack"));
+        assert_eq!(normalize_name("Xcode.app"), "xcode");
+    }
+
+    #[test]
+    fn matches_bundle_ids_against_display_names() {
+        assert!(names_match("com.google.Chrome", "Chrome"));
+        assert!(names_match("Visual Studio Code", "Code"));
+        assert!(app_name_likely_matches("Google Chrome", "chrome.exe", "Google Chrome", "com.google.Chrome"));
+        assert!(app_name_likely_matches("Visual Studio Code", "Code.exe", "Code", "com.microsoft.VSCode"));
+    }
+
+    #[test]
+    fn short_or_partial_words_do_not_match() {
+        assert!(!names_match("Go", "Google Chrome"));
+        assert!(!names_match("Qt", "Qt Creator"));
+        assert!(!names_match("Notes", "Notesnook"));
+        assert!(!names_match("Mail", "Gmail"));
+        assert!(!app_name_likely_matches("Go", "go.exe", "Google Chrome", "com.google.Chrome"));
+    }
+
+    #[test]
+    fn empty_names_never_match() {
+        assert!(!names_match("", "Safari"));
+        assert!(!names_match("Safari", ""));
+        assert!(!app_name_likely_matches("Safari", "", "Finder", ""));
     }
-    
-    // Try partial matches
-    app_norm.contains(&detected_norm) || 
-    detected_norm.contains(&app_norm) ||
-    proc_norm.contains(&detected_norm) ||
-    detected_norm.contains(&proc_norm)
 }
@@ -5,11 +5,17 @@ pub mod windows_tracker;
 pub mod macos_tracker;
 pub mod factory;
 pub mod database_helpers;
+pub mod idle;
+#[cfg(test)]
+pub mod stub_tracker;
+#[cfg(target_os = "linux")]
+pub mod linux_focus;
 
-pub use tracking_trait::{PlatformTracker, BaseTracker};
+pub use tracking_trait::{PlatformTracker, BaseTracker, FocusDiagnosis};
 pub use factory::TrackerFactory;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OperatingSystem {
     Windows,
     MacOS,
@@ -17,6 +23,18 @@ pub enum OperatingSystem {
     Unknown,
 }
 
+impl OperatingSystem {
+    /// Name used for the OS in stored data, matching its serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperatingSystem::Windows => "windows",
+            OperatingSystem::MacOS => "macos",
+            OperatingSystem::Linux => "linux",
+            OperatingSystem::Unknown => "unknown",
+        }
+    }
+}
+
 pub fn detect_os() -> OperatingSystem {
     match env::consts::OS {
         "windows" => OperatingSystem::Windows,
@@ -26,6 +44,37 @@ pub fn detect_os() -> OperatingSystem {
     }
 }
 
+// Keyword heuristics for guessing an app's category, checked in order
+const CATEGORY_KEYWORDS: &[(&str, &[&str])] = &[
+    ("Browser", &["chrome", "firefox", "edge", "safari"]),
+    ("Development", &["code", "xcode", "studio", "vim", "emacs", "terminal"]),
+    ("Productivity", &["word", "excel", "powerpoint", "notion", "pages", "numbers", "keynote"]),
+    ("Gaming", &["game", "steam", "epic"]),
+    ("Communication", &["discord", "slack", "teams", "messages"]),
+];
+
+/// Best-guess category for an app or process name: the user's category rules first,
+/// then the keyword heuristics, and "Other" when nothing matches
+pub fn categorize_app_name(name: &str) -> String {
+    if let Some(category) = crate::category_rules::category_for(name) {
+        return category;
+    }
+
+    let name_lower = name.to_lowercase();
+    CATEGORY_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|keyword| name_lower.contains(keyword)))
+        .map(|(category, _)| *category)
+        .unwrap_or("Other")
+        .to_string()
+}
+
+/// Whether this OS has a native activity tracker
+pub fn tracking_supported(os: &OperatingSystem) -> bool {
+    // Linux only has the best-effort focus fallback for now
+    matches!(os, OperatingSystem::Windows | OperatingSystem::MacOS)
+}
+
 pub fn is_windows() -> bool {
     detect_os() == OperatingSystem::Windows
 }
@@ -0,0 +1,373 @@
+use crate::database::{Application, Database};
+use crate::platform::{BaseTracker, FocusDiagnosis, database_helpers::DatabaseHelpers};
+use crate::tracking::CurrentActivity;
+use std::sync::Mutex;
+
+/// Test-only tracker whose foreground app is set by the test instead of read from
+/// the OS, so the shared tracking state machine can run on any CI host
+pub struct StubTracker {
+    base: BaseTracker,
+    foreground: Mutex<Option<String>>,
+}
+
+impl StubTracker {
+    pub fn new(db: Database) -> Self {
+        Self {
+            base: BaseTracker::new(db),
+            foreground: Mutex::new(None),
+        }
+    }
+
+    /// Process the next tick will see in the foreground, or None for no focused window
+    pub fn set_foreground(&self, process_name: Option<&str>) {
+        *self.foreground.lock().unwrap() = process_name.map(str::to_string);
+    }
+
+    fn foreground(&self) -> Option<String> {
+        self.foreground.lock().unwrap().clone()
+    }
+
+    // No loop is spawned: tests drive each tick through update_activity
+    pub async fn start_tracking(&self) -> Result<(), String> {
+        self.base.state.lock().await.is_tracking = true;
+        Ok(())
+    }
+
+    pub async fn stop_tracking(&self) -> Result<(), String> {
+        self.base.stop_tracking().await;
+        Ok(())
+    }
+
+    pub async fn update_activity(&self) -> Result<(), String> {
+        self.base.apply_foreground(self.foreground(), &[], |app, key| app.process_name == key).await
+    }
+
+    pub async fn get_current_activity(&self) -> Result<Option<CurrentActivity>, String> {
+        Ok(self.base.state.lock().await.cached_current_activity.clone())
+    }
+
+    pub async fn get_active_applications_count(&self) -> Result<usize, String> {
+        Ok(self.base.state.lock().await.active_apps.len())
+    }
+
+    pub async fn stop_tracking_for_app(&self, process_name: &str) -> Result<(), String> {
+        self.base.end_entries_matching(|key| key.eq_ignore_ascii_case(process_name)).await?;
+        Ok(())
+    }
+
+    pub async fn stop_tracking_for_app_by_id(&self, app_id: &str) -> Result<(), String> {
+        match DatabaseHelpers::get_application(&self.base.db, app_id).await? {
+            Some(app) => self.stop_tracking_for_app(&app.process_name).await,
+            None => Ok(()),
+        }
+    }
+
+    pub async fn set_active_task(&self, task_id: Option<String>) {
+        self.base.state.lock().await.active_task_id = task_id;
+    }
+
+    pub async fn set_focus_app(&self, app: Option<Application>) -> Result<(), String> {
+        self.base.set_focus_app(app, |focus_app, key| focus_app.process_name == key).await
+    }
+
+    pub async fn active_task_id(&self) -> Option<String> {
+        self.base.state.lock().await.active_task_id.clone()
+    }
+
+    pub async fn is_tracking(&self) -> bool {
+        self.base.state.lock().await.is_tracking
+    }
+
+    pub async fn diagnose_focus(&self, tracked_apps: &[Application]) -> Result<FocusDiagnosis, String> {
+        let focused_process = self.foreground();
+        let matched_app = focused_process.as_ref().and_then(|process| {
+            tracked_apps.iter().find(|app| app.process_name == *process).map(|app| app.name.clone())
+        });
+        Ok(FocusDiagnosis { focused_process, matched_app })
+    }
+
+    pub async fn forget_running_entries(&self) -> Vec<String> {
+        self.base.forget_running_entries().await
+    }
+
+    pub async fn running_entry_ids(&self) -> Vec<String> {
+        self.base.running_entry_ids().await
+    }
+
+    pub async fn forget_lost_entries(&self) -> Result<usize, String> {
+        self.base.forget_lost_entries().await
+    }
+
+    pub async fn invalidate_activity_cache(&self) {
+        self.base.invalidate_activity_cache().await
+    }
+
+    pub async fn refresh_tracked_apps(&self) {
+        self.base.refresh_tracked_apps().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::PlatformTracker;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    fn app(id: &str, process_name: &str) -> Application {
+        serde_json::from_value(json!({
+            "id": id,
+            "user_id": "user-1",
+            "name": process_name,
+            "process_name": process_name,
+            "is_tracked": true
+        }))
+        .unwrap()
+    }
+
+    // A server that creates entries on request and reports every entry as started an
+    // hour ago, so ending one always closes it rather than discarding it as a blip
+    async fn entries_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(|request: &Request| request.url.query_pairs().any(|(key, _)| key == "id"))
+            .respond_with(|request: &Request| {
+                let id = request
+                    .url
+                    .query_pairs()
+                    .find(|(key, _)| key == "id")
+                    .map(|(_, value)| value.trim_start_matches("eq.").to_string())
+                    .unwrap_or_default();
+                ResponseTemplate::new(200).set_body_json(json!([{
+                    "id": id,
+                    "user_id": "user-1",
+                    "start_time": (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339(),
+                    "is_active": true
+                }]))
+            })
+            .mount(&server)
+            .await;
+        // No entry is already open for the app being started
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("is_active", "eq.true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(|request: &Request| {
+                let row: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                ResponseTemplate::new(201).set_body_json(json!([row]))
+            })
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    // Entry ids closed so far, from the PATCHes the server received
+    async fn ended_entry_ids(server: &MockServer) -> Vec<String> {
+        let mut ids: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| request.method.as_str() == "PATCH" && request.url.path() == "/rest/v1/time_entries")
+            .filter_map(|request| {
+                request
+                    .url
+                    .query_pairs()
+                    .find(|(key, _)| key == "id")
+                    .map(|(_, value)| value.trim_start_matches("eq.").to_string())
+            })
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    async fn stub_tracker(server: &MockServer, tracked_apps: Vec<Application>) -> StubTracker {
+        crate::current_user::set_current_user_id("user-1".to_string());
+        let tracker = StubTracker::new(Database::new(server.uri(), "test-key".to_string()).unwrap());
+        tracker.base.state.lock().await.tracked_apps = Some((Instant::now(), tracked_apps));
+        tracker
+    }
+
+    #[tokio::test]
+    async fn a_focus_change_ends_the_old_entry_and_starts_one_for_the_new_app() {
+        let server = entries_server().await;
+        let tracker = stub_tracker(&server, vec![app("app-code", "Code.exe"), app("app-slack", "slack.exe")]).await;
+        let state = Arc::clone(&tracker.base.state);
+        tracker.set_foreground(Some("Code.exe"));
+        let tracker = PlatformTracker::Stub(tracker);
+        tracker.start_tracking().await.unwrap();
+
+        tracker.update_activity().await.unwrap();
+        let code_entry = state.lock().await.active_apps.get("Code.exe").cloned().unwrap();
+
+        // Staying on the same app keeps its entry running
+        tracker.update_activity().await.unwrap();
+        assert_eq!(tracker.running_entry_ids().await, vec![code_entry.clone()]);
+
+        if let PlatformTracker::Stub(stub) = &tracker {
+            stub.set_foreground(Some("slack.exe"));
+        }
+        tracker.update_activity().await.unwrap();
+
+        let state = state.lock().await;
+        assert_eq!(state.active_apps.keys().collect::<Vec<_>>(), vec!["slack.exe"]);
+        assert_ne!(state.active_apps["slack.exe"], code_entry);
+        drop(state);
+        assert_eq!(ended_entry_ids(&server).await, vec![code_entry]);
+    }
+
+    #[tokio::test]
+    async fn an_untracked_foreground_app_stops_all_tracking() {
+        let server = entries_server().await;
+        let tracker = stub_tracker(&server, vec![app("app-code", "Code.exe")]).await;
+        tracker.set_foreground(Some("Code.exe"));
+        tracker.update_activity().await.unwrap();
+        let code_entry = tracker.running_entry_ids().await;
+
+        tracker.set_foreground(Some("explorer.exe"));
+        let tracker = PlatformTracker::Stub(tracker);
+        tracker.update_activity().await.unwrap();
+
+        assert!(tracker.running_entry_ids().await.is_empty());
+        assert_eq!(ended_entry_ids(&server).await, code_entry);
+        let activity = tracker.get_current_activity().await.unwrap().unwrap();
+        assert_eq!(activity.app_name, "explorer.exe");
+        assert!(!activity.is_active);
+    }
+
+    #[tokio::test]
+    async fn stopping_tracking_closes_every_running_entry() {
+        let server = entries_server().await;
+        let tracker = stub_tracker(&server, Vec::new()).await;
+        {
+            let mut state = tracker.base.state.lock().await;
+            state.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+            state.active_apps.insert("chrome.exe".to_string(), "entry-2".to_string());
+            state.active_task_id = Some("task-1".to_string());
+        }
+        let tracker = PlatformTracker::Stub(tracker);
+        tracker.start_tracking().await.unwrap();
+
+        tracker.stop_tracking().await.unwrap();
+
+        assert!(!tracker.is_tracking().await);
+        assert!(tracker.running_entry_ids().await.is_empty());
+        assert_eq!(tracker.active_task_id().await, None);
+        assert_eq!(ended_entry_ids(&server).await, vec!["entry-1".to_string(), "entry-2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn visible_apps_keep_their_own_entries_until_they_leave_the_screen() {
+        let server = entries_server().await;
+        let tracker = stub_tracker(&server, vec![app("app-code", "Code.exe"), app("app-slack", "slack.exe")]).await;
+        let same_process = |app: &Application, key: &str| app.process_name == key;
+
+        tracker.base.apply_foreground(Some("Code.exe".to_string()), &["slack.exe".to_string()], same_process).await.unwrap();
+        assert_eq!(tracker.running_entry_ids().await.len(), 2);
+        let slack_entry = tracker.base.state.lock().await.active_apps["slack.exe"].clone();
+
+        // Slack is closed: its entry ends and Code keeps running
+        tracker.base.apply_foreground(Some("Code.exe".to_string()), &[], same_process).await.unwrap();
+        let state = tracker.base.state.lock().await;
+        assert_eq!(state.active_apps.keys().collect::<Vec<_>>(), vec!["Code.exe"]);
+        drop(state);
+        assert_eq!(ended_entry_ids(&server).await, vec![slack_entry]);
+    }
+
+    #[tokio::test]
+    async fn an_entry_that_fails_to_end_stays_running() {
+        let server = entries_server().await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(500))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        let tracker = stub_tracker(&server, Vec::new()).await;
+        tracker.base.state.lock().await.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+        let tracker = PlatformTracker::Stub(tracker);
+
+        assert!(tracker.stop_tracking_for_app("Code.exe").await.is_err());
+
+        // Still held, so a later stop can end it
+        assert_eq!(tracker.running_entry_ids().await, vec!["entry-1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn starting_a_focus_session_ends_other_apps_entries() {
+        let server = entries_server().await;
+        let tracker = stub_tracker(&server, Vec::new()).await;
+        {
+            let mut state = tracker.base.state.lock().await;
+            state.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+            state.active_apps.insert("chrome.exe".to_string(), "entry-2".to_string());
+        }
+        let tracker = PlatformTracker::Stub(tracker);
+
+        tracker.set_focus_app(Some(app("app-code", "Code.exe"))).await.unwrap();
+
+        assert_eq!(tracker.running_entry_ids().await, vec!["entry-1".to_string()]);
+        assert_eq!(ended_entry_ids(&server).await, vec!["entry-2".to_string()]);
+
+        // Ending the session leaves the focus app's entry running
+        tracker.set_focus_app(None).await.unwrap();
+        assert_eq!(tracker.running_entry_ids().await, vec!["entry-1".to_string()]);
+    }
+
+    // A tracker holding `entry-1` for Code.exe, started past the default session cap
+    async fn tracker_with_long_session(server: &MockServer) -> StubTracker {
+        let tracker = stub_tracker(server, Vec::new()).await;
+        {
+            let mut state = tracker.base.state.lock().await;
+            state.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+            state.entry_started_at.insert("entry-1".to_string(), chrono::Utc::now() - chrono::Duration::hours(9));
+        }
+        tracker
+    }
+
+    #[tokio::test]
+    async fn long_sessions_are_split_into_a_new_entry() {
+        let server = entries_server().await;
+        let tracker = tracker_with_long_session(&server).await;
+
+        tracker.base.split_long_sessions().await;
+
+        let state = tracker.base.state.lock().await;
+        let next_entry = state.active_apps["Code.exe"].clone();
+        assert_ne!(next_entry, "entry-1");
+        assert!(!state.entry_started_at.contains_key("entry-1"));
+        drop(state);
+        assert_eq!(ended_entry_ids(&server).await, vec!["entry-1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn a_split_that_cannot_continue_forgets_the_closed_entry() {
+        let server = entries_server().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/v1/time_entries"))
+            .respond_with(ResponseTemplate::new(500))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        let tracker = tracker_with_long_session(&server).await;
+
+        tracker.base.split_long_sessions().await;
+
+        // Closed, so the next tick starts a fresh entry rather than holding the old one
+        assert!(tracker.base.running_entry_ids().await.is_empty());
+        assert_eq!(ended_entry_ids(&server).await, vec!["entry-1".to_string()]);
+    }
+}
@@ -1,14 +1,18 @@
-use crate::database::Database;
+use crate::database::{Application, Database, TimeEntry};
 use crate::tracking::CurrentActivity;
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
 
 /// Platform-specific tracker implementations
 pub enum PlatformTracker {
     Windows(crate::platform::windows_tracker::WindowsTracker),
     MacOS(crate::platform::macos_tracker::MacOSTracker),
+    #[cfg(test)]
+    Stub(crate::platform::stub_tracker::StubTracker),
 }
 
 impl PlatformTracker {
@@ -17,6 +21,8 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.start_tracking().await,
             PlatformTracker::MacOS(tracker) => tracker.start_tracking().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.start_tracking().await,
         }
     }
     
@@ -25,6 +31,8 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.stop_tracking().await,
             PlatformTracker::MacOS(tracker) => tracker.stop_tracking().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.stop_tracking().await,
         }
     }
     
@@ -33,6 +41,8 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.update_activity().await,
             PlatformTracker::MacOS(tracker) => tracker.update_activity().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.update_activity().await,
         }
     }
     
@@ -41,6 +51,8 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.get_current_activity().await,
             PlatformTracker::MacOS(tracker) => tracker.get_current_activity().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.get_current_activity().await,
         }
     }
     
@@ -49,6 +61,8 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.get_active_applications_count().await,
             PlatformTracker::MacOS(tracker) => tracker.get_active_applications_count().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.get_active_applications_count().await,
         }
     }
     
@@ -57,6 +71,8 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.stop_tracking_for_app(process_name).await,
             PlatformTracker::MacOS(tracker) => tracker.stop_tracking_for_app(process_name).await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.stop_tracking_for_app(process_name).await,
         }
     }
     
@@ -65,6 +81,38 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.stop_tracking_for_app_by_id(app_id).await,
             PlatformTracker::MacOS(tracker) => tracker.stop_tracking_for_app_by_id(app_id).await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.stop_tracking_for_app_by_id(app_id).await,
+        }
+    }
+    
+    /// Set the task that new time entries should be linked to
+    pub async fn set_active_task(&self, task_id: Option<String>) {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.set_active_task(task_id).await,
+            PlatformTracker::MacOS(tracker) => tracker.set_active_task(task_id).await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.set_active_task(task_id).await,
+        }
+    }
+    
+    /// Restrict tracking to a single app, or return to normal tracking with None
+    pub async fn set_focus_app(&self, app: Option<Application>) -> Result<(), String> {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.set_focus_app(app).await,
+            PlatformTracker::MacOS(tracker) => tracker.set_focus_app(app).await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.set_focus_app(app).await,
+        }
+    }
+    
+    /// Get the task that new time entries are linked to
+    pub async fn active_task_id(&self) -> Option<String> {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.active_task_id().await,
+            PlatformTracker::MacOS(tracker) => tracker.active_task_id().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.active_task_id().await,
         }
     }
     
@@ -73,45 +121,633 @@ impl PlatformTracker {
         match self {
             PlatformTracker::Windows(tracker) => tracker.is_tracking().await,
             PlatformTracker::MacOS(tracker) => tracker.is_tracking().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.is_tracking().await,
+        }
+    }
+
+    /// Short name of the tracker implementation in use
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PlatformTracker::Windows(_) => "windows",
+            PlatformTracker::MacOS(_) => "macos",
+            #[cfg(test)]
+            PlatformTracker::Stub(_) => "stub",
+        }
+    }
+
+    /// What the tracker currently sees in the foreground and which tracked app, if any, it matches
+    pub async fn diagnose_focus(&self, tracked_apps: &[Application]) -> Result<FocusDiagnosis, String> {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.diagnose_focus(tracked_apps).await,
+            PlatformTracker::MacOS(tracker) => tracker.diagnose_focus(tracked_apps).await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.diagnose_focus(tracked_apps).await,
         }
     }
+
+    /// Drop all in-memory tracking state without touching the database
+    pub async fn forget_running_entries(&self) -> Vec<String> {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.forget_running_entries().await,
+            PlatformTracker::MacOS(tracker) => tracker.forget_running_entries().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.forget_running_entries().await,
+        }
+    }
+
+    /// Ids of the entries this tracker is currently running
+    pub async fn running_entry_ids(&self) -> Vec<String> {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.running_entry_ids().await,
+            PlatformTracker::MacOS(tracker) => tracker.running_entry_ids().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.running_entry_ids().await,
+        }
+    }
+
+    /// Forget running entries the database no longer has open, returning how many
+    pub async fn forget_lost_entries(&self) -> Result<usize, String> {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.forget_lost_entries().await,
+            PlatformTracker::MacOS(tracker) => tracker.forget_lost_entries().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.forget_lost_entries().await,
+        }
+    }
+
+    /// Drop the cached current activity so the next read goes to the database
+    pub async fn invalidate_activity_cache(&self) {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.invalidate_activity_cache().await,
+            PlatformTracker::MacOS(tracker) => tracker.invalidate_activity_cache().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.invalidate_activity_cache().await,
+        }
+    }
+
+    /// Drop the cached tracked-app list so the next tick refetches it
+    pub async fn refresh_tracked_apps(&self) {
+        match self {
+            PlatformTracker::Windows(tracker) => tracker.refresh_tracked_apps().await,
+            PlatformTracker::MacOS(tracker) => tracker.refresh_tracked_apps().await,
+            #[cfg(test)]
+            PlatformTracker::Stub(tracker) => tracker.refresh_tracked_apps().await,
+        }
+    }
+}
+
+/// Foreground app as seen by a platform tracker, for troubleshooting
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FocusDiagnosis {
+    pub focused_process: Option<String>,
+    pub matched_app: Option<String>, // Name of the tracked app the focused process matches
 }
 
 /// Shared state for platform trackers
 #[derive(Debug, Clone)]
 pub struct TrackingState {
     pub active_apps: HashMap<String, String>, // process_name -> entry_id
+    pub entry_started_at: HashMap<String, chrono::DateTime<chrono::Utc>>, // entry_id -> start_time
     pub last_activity_time: Instant,
     pub is_tracking: bool,
     pub app_last_seen: HashMap<String, Instant>, // process_name -> last time we saw it running
     pub cached_current_activity: Option<CurrentActivity>, // Cached current activity
     pub cache_last_updated: Instant, // When the cache was last updated
+    pub active_task_id: Option<String>, // Task that new time entries are linked to
+    pub focus_app: Option<Application>, // Only app tracked while a focus session runs
+    pub last_heartbeat: Instant, // When active entries' updated_at was last refreshed
+    pub tracked_apps: Option<(Instant, Vec<Application>)>, // Tracked-app list and when it was fetched
 }
 
 impl Default for TrackingState {
     fn default() -> Self {
         Self {
             active_apps: HashMap::new(),
+            entry_started_at: HashMap::new(),
             last_activity_time: Instant::now(),
             is_tracking: false,
             app_last_seen: HashMap::new(),
             cached_current_activity: None,
             cache_last_updated: Instant::now(),
+            active_task_id: None,
+            focus_app: None,
+            last_heartbeat: Instant::now(),
+            tracked_apps: None,
         }
     }
 }
 
+impl TrackingState {
+    /// Record a newly started (or reused) entry under its active_apps key
+    pub fn track_entry(&mut self, key: String, entry: &TimeEntry) {
+        self.active_apps.insert(key, entry.id.clone());
+        self.entry_started_at.insert(entry.id.clone(), entry.start_time);
+    }
+
+    /// Forget every running entry, returning their ids and start times so they can be ended
+    pub fn take_active_entries(&mut self) -> Vec<(String, Option<chrono::DateTime<chrono::Utc>>)> {
+        let entries = self
+            .active_apps
+            .drain()
+            .map(|(_, entry_id)| {
+                let started_at = self.entry_started_at.get(&entry_id).copied();
+                (entry_id, started_at)
+            })
+            .collect();
+        self.entry_started_at.clear();
+        entries
+    }
+
+    /// Forget every running entry whose key isn't kept, returning the ids and start times to end
+    pub fn take_entries_except<F>(&mut self, keep: F) -> Vec<(String, Option<chrono::DateTime<chrono::Utc>>)>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let keys: Vec<String> = self.active_apps.keys().filter(|key| !keep(key)).cloned().collect();
+        let ids: Vec<String> = keys.iter().filter_map(|key| self.active_apps.remove(key)).collect();
+        ids.into_iter()
+            .map(|id| {
+                let started_at = self.entry_started_at.remove(&id);
+                (id, started_at)
+            })
+            .collect()
+    }
+
+    /// The cached tracked-app list, unless it is older than `max_age`
+    pub fn fresh_tracked_apps(&self, max_age: Duration) -> Option<&Vec<Application>> {
+        self.tracked_apps
+            .as_ref()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < max_age)
+            .map(|(_, apps)| apps)
+    }
+
+    /// Start time of the running entry whose key matches, if any
+    pub fn tracked_since<F>(&self, matches: F) -> Option<chrono::DateTime<chrono::Utc>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.active_apps
+            .iter()
+            .find(|(key, _)| matches(key))
+            .and_then(|(_, entry_id)| self.entry_started_at.get(entry_id).copied())
+    }
+}
+
+/// A running tracking loop and the signal that wakes it to exit
+struct TrackingLoop {
+    handle: JoinHandle<()>,
+    stop: Arc<Notify>,
+}
+
 /// Base struct for platform trackers
+#[derive(Clone)]
 pub struct BaseTracker {
     pub state: Arc<Mutex<TrackingState>>,
     pub db: Database,
+    tracking_loop: Arc<Mutex<Option<TrackingLoop>>>,
 }
 
+// How often running entries get their updated_at refreshed, which bounds
+// the time lost if the app crashes mid-session
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+// How long stopping waits for an in-flight tick before aborting the loop
+const LOOP_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How long the tracked-app list is reused before it is refetched; edits from
+// the UI invalidate it sooner through `refresh_tracked_apps`
+const TRACKED_APPS_MAX_AGE: Duration = Duration::from_secs(30);
+
 impl BaseTracker {
     pub fn new(db: Database) -> Self {
         Self {
             state: Arc::new(Mutex::new(TrackingState::default())),
             db,
+            tracking_loop: Arc::new(Mutex::new(None)),
         }
     }
+
+    /// Run `tick` every `period` while tracking is on, until `stop_loop` is called
+    pub async fn spawn_loop<F, Fut>(&self, period: Duration, tick: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        // A leftover loop would tick alongside the new one
+        self.stop_loop().await;
+
+        let state = Arc::clone(&self.state);
+        let stop = Arc::new(Notify::new());
+        let stop_signal = Arc::clone(&stop);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = stop_signal.notified() => break,
+                }
+
+                if !state.lock().await.is_tracking {
+                    break;
+                }
+
+                tick().await;
+            }
+        });
+
+        *self.tracking_loop.lock().await = Some(TrackingLoop { handle, stop });
+    }
+
+    /// Wake the tracking loop so it exits now rather than on its next tick, and wait for
+    /// any tick in flight to finish so nothing runs against state being torn down
+    pub async fn stop_loop(&self) {
+        let Some(TrackingLoop { mut handle, stop }) = self.tracking_loop.lock().await.take() else {
+            return;
+        };
+
+        stop.notify_one();
+        match tokio::time::timeout(LOOP_STOP_TIMEOUT, &mut handle).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::warn!("Tracking loop ended with an error: {}", e),
+            Err(_) => {
+                log::warn!("Tracking loop did not stop within {:?}, aborting it", LOOP_STOP_TIMEOUT);
+                handle.abort();
+            }
+        }
+    }
+
+    /// Turn tracking off: stop the loop, unlink the active task and end every running entry
+    pub async fn stop_tracking(&self) {
+        self.state.lock().await.is_tracking = false;
+        // Let any tick in flight finish so it can't start an entry after this
+        self.stop_loop().await;
+
+        let entry_ids_to_end = {
+            let mut state = self.state.lock().await;
+            state.active_task_id = None;
+            state.take_active_entries()
+        };
+
+        for (entry_id, started_at) in entry_ids_to_end {
+            let _ = crate::platform::database_helpers::DatabaseHelpers::end_tracked_time_entry(&self.db, entry_id, started_at).await;
+        }
+    }
+
+    /// End the running entries whose active_apps key matches, returning how many were ended
+    pub async fn end_entries_matching<F>(&self, matches: F) -> Result<usize, String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        // Hold the state while ending so a tick can't restart an app halfway through
+        let mut state = self.state.lock().await;
+        let keys: Vec<String> = state.active_apps.keys().filter(|key| matches(key)).cloned().collect();
+        // Don't keep showing an app we are stopping
+        state.cached_current_activity = None;
+
+        let mut ended = 0;
+        for key in keys {
+            let Some(entry_id) = state.active_apps.get(&key).cloned() else {
+                continue;
+            };
+            let started_at = state.entry_started_at.get(&entry_id).copied();
+            // Forget the entry only once it is ended, so a failed end can be retried
+            crate::platform::database_helpers::DatabaseHelpers::end_tracked_time_entry(&self.db, entry_id.clone(), started_at).await?;
+            state.active_apps.remove(&key);
+            state.entry_started_at.remove(&entry_id);
+            ended += 1;
+        }
+        Ok(ended)
+    }
+
+    /// Restrict tracking to `app`, or lift the restriction with None. Starting a session
+    /// ends the running entries of every other app so they stop counting straight away;
+    /// `is_focus_key` says whether an active_apps key belongs to the focus app
+    pub async fn set_focus_app<F>(&self, app: Option<Application>, is_focus_key: F) -> Result<(), String>
+    where
+        F: Fn(&Application, &str) -> bool,
+    {
+        {
+            let mut state = self.state.lock().await;
+            state.focus_app = app.clone();
+            // Show the new focus state on the next read
+            state.cached_current_activity = None;
+        }
+
+        if let Some(focus_app) = app {
+            let ended = self.end_entries_matching(|key| !is_focus_key(&focus_app, key)).await?;
+            if ended > 0 {
+                log::info!("Ended {} running entries outside the focus session on {}", ended, focus_app.name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget every running entry and cached activity, returning the entry ids that were held
+    pub async fn forget_running_entries(&self) -> Vec<String> {
+        let mut state = self.state.lock().await;
+        state.cached_current_activity = None;
+        state.app_last_seen.clear();
+        state.take_active_entries().into_iter().map(|(entry_id, _)| entry_id).collect()
+    }
+
+    /// Close entries left open by an earlier run before tracking starts again
+    pub async fn cleanup_existing_active_entries(&self) -> Result<(), String> {
+        let running = self.running_entry_ids().await;
+        crate::platform::database_helpers::DatabaseHelpers::end_orphaned_entries(&self.db, &running).await?;
+        Ok(())
+    }
+
+    pub async fn running_entry_ids(&self) -> Vec<String> {
+        let state = self.state.lock().await;
+        state.active_apps.values().cloned().collect()
+    }
+
+    /// Forget running entries that are no longer open in the database, e.g. because
+    /// their writes were lost while offline, so the next tick starts them afresh.
+    /// Returns how many were forgotten
+    pub async fn forget_lost_entries(&self) -> Result<usize, String> {
+        let running = self.running_entry_ids().await;
+        let still_active = crate::platform::database_helpers::DatabaseHelpers::still_active_entry_ids(&self.db, &running).await?;
+
+        let mut state = self.state.lock().await;
+        let lost: Vec<String> = state
+            .active_apps
+            .iter()
+            .filter(|(_, entry_id)| running.contains(entry_id) && !still_active.contains(entry_id))
+            // An insert still waiting in the sync queue isn't lost, just not replayed yet
+            .filter(|(_, entry_id)| crate::sync_queue::pending_insert("time_entries", entry_id).is_none())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &lost {
+            if let Some(entry_id) = state.active_apps.remove(key) {
+                state.entry_started_at.remove(&entry_id);
+            }
+        }
+        if !lost.is_empty() {
+            state.cached_current_activity = None;
+        }
+        Ok(lost.len())
+    }
+
+    pub async fn invalidate_activity_cache(&self) {
+        let mut state = self.state.lock().await;
+        state.cached_current_activity = None;
+        state.cache_last_updated = Instant::now();
+    }
+
+    pub async fn refresh_tracked_apps(&self) {
+        self.state.lock().await.tracked_apps = None;
+    }
+
+    /// Tracked applications from the cache, fetching them when it is empty or stale.
+    /// Takes the already-locked state since the trackers hold it across the tick
+    pub async fn tracked_applications(&self, state: &mut TrackingState) -> Result<Vec<Application>, String> {
+        if let Some(apps) = state.fresh_tracked_apps(TRACKED_APPS_MAX_AGE) {
+            return Ok(apps.clone());
+        }
+
+        let apps = crate::platform::database_helpers::DatabaseHelpers::get_tracked_applications(&self.db).await?;
+        state.tracked_apps = Some((Instant::now(), apps.clone()));
+        Ok(apps)
+    }
+
+    /// Run one tracking tick against what is on screen: start an entry when a tracked app
+    /// comes to the front, end the others, and refresh the cached activity. `visible` lists
+    /// other apps with a window on screen, which keep their own entries (visible mode).
+    /// Running entries are filed under the key the tracker sees each app by, and `matches`
+    /// says whether a tracked application is the app seen under a key
+    pub async fn apply_foreground<F>(&self, foreground_process: Option<String>, visible: &[String], matches: F) -> Result<(), String>
+    where
+        F: Fn(&Application, &str) -> bool,
+    {
+        let mut state = self.state.lock().await;
+        state.last_activity_time = Instant::now();
+        
+        // Get tracked applications from database, or only the focus app during a focus session
+        let tracked_apps = match state.focus_app.clone() {
+            Some(focus_app) => vec![focus_app],
+            None => self.tracked_applications(&mut state).await?,
+        };
+        
+        // The foreground app first, then any visible ones, each with the tracked app it is
+        let on_screen: Vec<(String, &Application)> = foreground_process
+            .iter()
+            .chain(visible.iter().filter(|key| foreground_process.as_ref() != Some(*key)))
+            .filter_map(|key| {
+                tracked_apps.iter().find(|app| matches(app, key)).map(|app| (key.clone(), app))
+            })
+            .collect();
+        let foreground_is_tracked = foreground_process
+            .as_ref()
+            .is_some_and(|fg| on_screen.iter().any(|(key, _)| key == fg));
+        
+        // Initialize counters
+        let mut apps_started_count = 0;
+        let mut apps_stopped_count = 0;
+        
+        // Only apps still on screen may have a running entry, so close the rest before
+        // starting any; otherwise fast focus swaps double-count time
+        let entry_ids_to_end = state.take_entries_except(|key| on_screen.iter().any(|(on_screen_key, _)| on_screen_key == key));
+        if !foreground_is_tracked && !entry_ids_to_end.is_empty() {
+            log::info!("Foreground app '{}' is not in tracked list, stopping its tracking", 
+                     foreground_process.as_deref().unwrap_or("None"));
+        }
+        for (entry_id, started_at) in &entry_ids_to_end {
+            let _ = crate::platform::database_helpers::DatabaseHelpers::end_tracked_time_entry(&self.db, entry_id.clone(), *started_at).await;
+            log::info!("Ended time entry: {}", entry_id);
+        }
+        apps_stopped_count += entry_ids_to_end.len();
+        
+        // Ensure every tracked app on screen is being tracked
+        for (key, tracked_app) in &on_screen {
+            if state.active_apps.contains_key(key) {
+                // Already tracking, the entry continues
+                continue;
+            }
+            
+            let active_task_id = state.active_task_id.clone();
+            match crate::platform::database_helpers::DatabaseHelpers::start_time_entry(&self.db, tracked_app, active_task_id.as_deref()).await {
+                Ok(entry) => {
+                    state.track_entry(key.clone(), &entry);
+                    state.app_last_seen.insert(key.clone(), Instant::now());
+                    apps_started_count += 1;
+                    log::info!("Started tracking for {} (entry_id: {})", tracked_app.name, entry.id);
+                }
+                Err(e) => {
+                    log::error!("Failed to start time entry for {}: {}", tracked_app.name, e);
+                }
+            }
+        }
+        
+        debug_assert!(
+            state.active_apps.len() <= 1 + visible.len(),
+            "at most one time entry per app on screen may be active, found {}",
+            state.active_apps.len()
+        );
+
+        // Invalidate cache if any apps started or stopped tracking
+        if apps_started_count > 0 || apps_stopped_count > 0 {
+            state.cached_current_activity = None;
+            state.cache_last_updated = Instant::now();
+            log::debug!("Cache invalidated due to activity changes: {} started, {} stopped", apps_started_count, apps_stopped_count);
+        }
+        
+        // Update cache - show current foreground app regardless of database tracking
+        if let Some(foreground) = foreground_process {
+            let app_category = crate::platform::categorize_app_name(&foreground);
+            let now = chrono::Utc::now();
+            
+            // Check if this app is being tracked in the database
+            let is_being_tracked = state.active_apps.contains_key(&foreground);
+            let start_time = state.tracked_since(|key| key == foreground).unwrap_or(now);
+            let elapsed = now - start_time;
+            let focus_app_name = state.focus_app.as_ref().map(|app| app.name.clone());
+            let on_focus_app = state.focus_app.as_ref().map(|app| matches(app, &foreground));
+            
+            state.cached_current_activity = Some(CurrentActivity {
+                app_name: foreground,
+                app_category,
+                start_time,
+                duration_minutes: elapsed.num_minutes(),
+                duration_hours: elapsed.num_hours(),
+                is_active: is_being_tracked, // Only active if being tracked in database
+                active_apps_count: state.active_apps.len(), // Count of tracked apps
+                focus_app_name,
+                on_focus_app,
+            });
+            state.cache_last_updated = Instant::now();
+        } else {
+            // No foreground process, clear cache
+            state.cached_current_activity = None;
+            state.cache_last_updated = Instant::now();
+        }
+        
+        Ok(())
+    }
+
+    /// Split any running entry that has passed the configured maximum session length,
+    /// continuing it as a fresh entry for the same app from the same instant
+    pub async fn split_long_sessions(&self) {
+        let Some(max_seconds) = crate::tracking_settings::max_session_seconds() else {
+            return;
+        };
+
+        let now = chrono::Utc::now();
+        let expired: Vec<(String, String)> = {
+            let state = self.state.lock().await;
+            state
+                .active_apps
+                .iter()
+                .filter(|(_, entry_id)| {
+                    state
+                        .entry_started_at
+                        .get(*entry_id)
+                        .is_some_and(|started| (now - *started).num_seconds() >= max_seconds as i64)
+                })
+                .map(|(key, entry_id)| (key.clone(), entry_id.clone()))
+                .collect()
+        };
+
+        for (key, entry_id) in expired {
+            match crate::platform::database_helpers::DatabaseHelpers::roll_over_time_entry(&self.db, &entry_id, now).await {
+                Ok(Some(next_entry)) => {
+                    let mut state = self.state.lock().await;
+                    // Only swap if the app wasn't stopped while the split was in flight
+                    if state.active_apps.get(&key) == Some(&entry_id) {
+                        state.entry_started_at.remove(&entry_id);
+                        state.track_entry(key, &next_entry);
+                        state.cached_current_activity = None;
+                    } else {
+                        drop(state);
+                        let _ = crate::platform::database_helpers::DatabaseHelpers::end_tracked_time_entry(&self.db, next_entry.id, Some(next_entry.start_time)).await;
+                    }
+                }
+                Ok(None) => {
+                    // The old entry is closed, so stop holding it; the next tick starts a fresh one
+                    let mut state = self.state.lock().await;
+                    if state.active_apps.get(&key) == Some(&entry_id) {
+                        state.active_apps.remove(&key);
+                        state.entry_started_at.remove(&entry_id);
+                        state.cached_current_activity = None;
+                    }
+                }
+                Err(e) => log::warn!("Failed to split long session {}: {}", entry_id, e),
+            }
+        }
+    }
+
+    /// Refresh updated_at on the running entries once per heartbeat interval
+    pub async fn heartbeat_if_due(&self) {
+        let entry_ids: Vec<String> = {
+            let mut state = self.state.lock().await;
+            if state.last_heartbeat.elapsed() < HEARTBEAT_INTERVAL {
+                return;
+            }
+            state.last_heartbeat = Instant::now();
+            state.active_apps.values().cloned().collect()
+        };
+
+        if let Err(e) = crate::platform::database_helpers::DatabaseHelpers::touch_time_entries(&self.db, &entry_ids).await {
+            log::warn!("Heartbeat failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> TimeEntry {
+        let now = chrono::Utc::now();
+        TimeEntry {
+            id: id.to_string(),
+            user_id: "user-1".to_string(),
+            app_id: Some(format!("app-{}", id)),
+            task_id: None,
+            workspace_id: None,
+            start_time: now,
+            end_time: None,
+            duration_seconds: None,
+            is_active: true,
+            created_at: Some(now),
+            updated_at: Some(now),
+        }
+    }
+
+    #[test]
+    fn switching_apps_leaves_a_single_active_entry() {
+        let mut state = TrackingState::default();
+        state.track_entry("Code.exe".to_string(), &entry("e1"));
+        state.track_entry("chrome.exe".to_string(), &entry("e2"));
+
+        // Focus moves to Slack: everything else is handed back to be ended
+        let mut ended: Vec<String> = state.take_entries_except(|key| key == "slack.exe").into_iter().map(|(id, _)| id).collect();
+        ended.sort();
+        state.track_entry("slack.exe".to_string(), &entry("e3"));
+
+        assert_eq!(ended, vec!["e1".to_string(), "e2".to_string()]);
+        assert_eq!(state.active_apps.len(), 1);
+        assert_eq!(state.active_apps.get("slack.exe").map(String::as_str), Some("e3"));
+        assert_eq!(state.entry_started_at.len(), 1);
+    }
+
+    #[test]
+    fn keeps_the_entry_for_the_current_app() {
+        let mut state = TrackingState::default();
+        state.track_entry("Code.exe".to_string(), &entry("e1"));
+
+        assert!(state.take_entries_except(|key| key == "Code.exe").is_empty());
+        assert!(state.tracked_since(|key| key == "Code.exe").is_some());
+    }
+
+    #[test]
+    fn tracked_apps_cache_expires() {
+        let mut state = TrackingState::default();
+        assert!(state.fresh_tracked_apps(Duration::from_secs(30)).is_none());
+
+        state.tracked_apps = Some((Instant::now(), Vec::new()));
+        assert!(state.fresh_tracked_apps(Duration::from_secs(30)).is_some());
+        assert!(state.fresh_tracked_apps(Duration::ZERO).is_none());
+    }
 }
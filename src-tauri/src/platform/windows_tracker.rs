@@ -1,10 +1,9 @@
-use crate::database::Database;
-use crate::platform::{BaseTracker, database_helpers::DatabaseHelpers};
+use crate::database::{Application, Database};
+use crate::platform::{BaseTracker, FocusDiagnosis, database_helpers::DatabaseHelpers};
 use crate::tracking::CurrentActivity;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sysinfo::System;
-use tokio::time::interval;
 
 #[cfg(target_os = "windows")]
 use winapi::um::{
@@ -24,18 +23,6 @@ impl WindowsTracker {
         }
     }
 
-    async fn cleanup_existing_active_entries(&self) -> Result<(), String> {
-        // Get all active time entries and end them
-        let active_entries = DatabaseHelpers::get_active_time_entries(&self.base.db).await?;
-        
-        for entry in active_entries {
-            let _ = DatabaseHelpers::end_time_entry(&self.base.db, entry.id).await;
-            println!("Cleaned up existing active entry for app_id: {:?}", entry.app_id);
-        }
-        
-        Ok(())
-    }
-
     async fn get_active_processes(&self) -> Result<Vec<String>, String> {
         // Get all running processes, not just those with CPU usage
         let mut system = System::new_all();
@@ -56,9 +43,17 @@ impl WindowsTracker {
             Ok(self.get_focused_window_process_name())
         }
         
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(target_os = "linux")]
+        {
+            // Ask the compositor (Wayland) or X server, falling back to /proc sampling
+            tokio::task::spawn_blocking(crate::platform::linux_focus::focused_app)
+                .await
+                .map_err(|e| format!("Focused window detection failed: {}", e))
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
         {
-            // Fallback for non-Windows platforms - use CPU usage method
+            // Fallback for other platforms - use CPU usage method
             let mut system = System::new_all();
             system.refresh_processes();
             
@@ -138,22 +133,7 @@ impl WindowsTracker {
     }
 
     async fn categorize_app(&self, app_name: &str) -> String {
-        // Simple categorization based on app name
-        let name_lower = app_name.to_lowercase();
-        
-        if name_lower.contains("chrome") || name_lower.contains("firefox") || name_lower.contains("edge") || name_lower.contains("safari") {
-            "Browser".to_string()
-        } else if name_lower.contains("code") || name_lower.contains("studio") || name_lower.contains("vim") || name_lower.contains("emacs") {
-            "Development".to_string()
-        } else if name_lower.contains("word") || name_lower.contains("excel") || name_lower.contains("powerpoint") || name_lower.contains("notion") {
-            "Productivity".to_string()
-        } else if name_lower.contains("game") || name_lower.contains("steam") || name_lower.contains("epic") {
-            "Gaming".to_string()
-        } else if name_lower.contains("discord") || name_lower.contains("slack") || name_lower.contains("teams") {
-            "Communication".to_string()
-        } else {
-            "Other".to_string()
-        }
+        crate::platform::categorize_app_name(app_name)
     }
 }
 
@@ -165,66 +145,38 @@ impl WindowsTracker {
         };
         
         if already_tracking {
-            println!("Windows tracking is already running, skipping start");
+            log::info!("Windows tracking is already running, skipping start");
             return Ok(());
         }
         
         // Clean up existing active entries
-        self.cleanup_existing_active_entries().await?;
+        self.base.cleanup_existing_active_entries().await?;
         
         let mut state = self.base.state.lock().await;
         state.is_tracking = true;
         state.last_activity_time = Instant::now();
         drop(state);
 
-        // Start the tracking loop
-        let state_clone = Arc::clone(&self.base.state);
-        let db_clone = self.base.db.clone();
-        
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(5)); // Check every 5 seconds
-            loop {
-                interval.tick().await;
-                
-                let should_continue = {
-                    let state = state_clone.lock().await;
-                    state.is_tracking
-                };
-                
-                if !should_continue {
-                    break;
-                }
-                
-                // Update activity tracking
-                let tracker = WindowsTracker {
-                    base: BaseTracker {
-                        state: Arc::clone(&state_clone),
-                        db: db_clone.clone(),
-                    },
-                };
-                
+        // Start the tracking loop, checking every 5 seconds; the ticks share one tracker
+        let tracker = Arc::new(WindowsTracker { base: self.base.clone() });
+        self.base.spawn_loop(Duration::from_secs(5), move || {
+            let tracker = Arc::clone(&tracker);
+            async move {
                 if let Err(e) = tracker.update_activity().await {
-                    eprintln!("Error updating Windows activity: {}", e);
+                    log::error!("Error updating Windows activity: {}", e);
                 }
+
+                tracker.base.heartbeat_if_due().await;
+                tracker.base.split_long_sessions().await;
             }
-        });
+        }).await;
 
         Ok(())
     }
 
     pub async fn stop_tracking(&self) -> Result<(), String> {
-        let mut state = self.base.state.lock().await;
-        state.is_tracking = false;
-        
-        // End all active time entries
-        let entry_ids_to_end: Vec<String> = state.active_apps.values().cloned().collect();
-        drop(state);
-        
-        for entry_id in entry_ids_to_end {
-            let _ = DatabaseHelpers::end_time_entry(&self.base.db, entry_id).await;
-        }
-        
-        println!("Stopping Windows tracking");
+        self.base.stop_tracking().await;
+        log::info!("Stopping Windows tracking");
 
         Ok(())
     }
@@ -237,101 +189,7 @@ impl WindowsTracker {
         
         let foreground_process = self.get_foreground_process().await?;
         
-        let mut state = self.base.state.lock().await;
-        state.last_activity_time = Instant::now();
-        
-        // Get tracked applications from database
-        let tracked_apps = DatabaseHelpers::get_tracked_applications(&self.base.db).await?;
-        
-        // Initialize counters
-        let mut apps_started_count = 0;
-        let mut apps_stopped_count = 0;
-        let mut should_invalidate_cache = false;
-        
-        // Check if the current foreground app is in the tracked list
-        let foreground_is_tracked = if let Some(ref fg_process) = foreground_process {
-            tracked_apps.iter().any(|app| app.process_name == *fg_process)
-        } else {
-            false
-        };
-        
-        // If foreground app is not tracked, stop all active tracking
-        if !foreground_is_tracked && !state.active_apps.is_empty() {
-            println!("Foreground app '{}' is not in tracked list, stopping all active tracking", 
-                     foreground_process.as_deref().unwrap_or("None"));
-            
-            // End all active time entries
-            let entry_ids_to_end: Vec<String> = state.active_apps.values().cloned().collect();
-            let stopped_count = entry_ids_to_end.len();
-            state.active_apps.clear();
-            
-            for entry_id in &entry_ids_to_end {
-                let _ = DatabaseHelpers::end_time_entry(&self.base.db, entry_id.clone()).await;
-                println!("Ended time entry: {}", entry_id);
-            }
-            
-            should_invalidate_cache = true;
-            apps_stopped_count += stopped_count;
-        }
-        
-        // If foreground app is tracked, ensure it's being tracked
-        if foreground_is_tracked {
-            if let Some(ref fg_process) = foreground_process {
-                if let Some(tracked_app) = tracked_apps.iter().find(|app| app.process_name == *fg_process) {
-                    let was_tracked = state.active_apps.contains_key(&tracked_app.process_name);
-                    
-                    if !was_tracked {
-                        // Foreground app is tracked but not currently being tracked - start tracking
-                        match DatabaseHelpers::start_time_entry(&self.base.db, tracked_app).await {
-                            Ok(entry_id) => {
-                                state.active_apps.insert(tracked_app.process_name.clone(), entry_id.clone());
-                                state.app_last_seen.insert(tracked_app.process_name.clone(), Instant::now());
-                                apps_started_count += 1;
-                                should_invalidate_cache = true;
-                                println!("Started tracking for {} (entry_id: {})", tracked_app.name, entry_id);
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to start time entry for {}: {}", tracked_app.name, e);
-                            }
-                        }
-                    }
-                    // If already tracking, continue tracking (no action needed)
-                }
-            }
-        }
-        
-        // Invalidate cache if any apps started or stopped tracking
-        if should_invalidate_cache {
-            state.cached_current_activity = None;
-            state.cache_last_updated = Instant::now();
-            println!("Cache invalidated due to activity changes: {} started, {} stopped", apps_started_count, apps_stopped_count);
-        }
-        
-        // Update cache - show current foreground app regardless of database tracking
-        if let Some(foreground) = foreground_process {
-            let app_category = self.categorize_app(&foreground).await;
-            let start_time = chrono::Utc::now();
-            
-            // Check if this app is being tracked in the database
-            let is_being_tracked = state.active_apps.contains_key(&foreground);
-            
-            state.cached_current_activity = Some(CurrentActivity {
-                app_name: foreground,
-                app_category,
-                start_time,
-                duration_minutes: 0,
-                duration_hours: 0,
-                is_active: is_being_tracked, // Only active if being tracked in database
-                active_apps_count: state.active_apps.len(), // Count of tracked apps
-            });
-            state.cache_last_updated = Instant::now();
-        } else {
-            // No foreground process, clear cache
-            state.cached_current_activity = None;
-            state.cache_last_updated = Instant::now();
-        }
-        
-        Ok(())
+        self.base.apply_foreground(foreground_process, &[], is_process).await
     }
 
     pub async fn get_current_activity(&self) -> Result<Option<CurrentActivity>, String> {
@@ -340,22 +198,29 @@ impl WindowsTracker {
         
         if let Some(foreground) = foreground_process {
             let app_category = self.categorize_app(&foreground).await;
-            let start_time = chrono::Utc::now();
+            let now = chrono::Utc::now();
             
             // Check if this app is being tracked in the database
             let state = self.base.state.lock().await;
             let is_being_tracked = state.active_apps.contains_key(&foreground);
+            // Report how long the running entry has been open, not just this instant
+            let start_time = state.tracked_since(|key| key == foreground).unwrap_or(now);
             let active_apps_count = state.active_apps.len();
+            let focus_app_name = state.focus_app.as_ref().map(|app| app.name.clone());
+            let on_focus_app = state.focus_app.as_ref().map(|app| is_process(app, &foreground));
             drop(state);
+            let elapsed = now - start_time;
             
             Ok(Some(CurrentActivity {
                 app_name: foreground,
                 app_category,
                 start_time,
-                duration_minutes: 0,
-                duration_hours: 0,
+                duration_minutes: elapsed.num_minutes(),
+                duration_hours: elapsed.num_hours(),
                 is_active: is_being_tracked,
                 active_apps_count,
+                focus_app_name,
+                on_focus_app,
             }))
         } else {
             Ok(None)
@@ -368,24 +233,189 @@ impl WindowsTracker {
     }
 
     pub async fn stop_tracking_for_app(&self, process_name: &str) -> Result<(), String> {
-        let mut state = self.base.state.lock().await;
-        
-        if let Some(_entry_id) = state.active_apps.remove(process_name) {
-            // For now, we'll just remove from tracking without database operations
-            println!("Stopped tracking for app: {}", process_name);
+        // active_apps is keyed by process_name on Windows
+        let ended = self.base.end_entries_matching(|key| key.eq_ignore_ascii_case(process_name)).await?;
+        if ended > 0 {
+            log::info!("Stopped tracking for app: {}", process_name);
         }
-        
         Ok(())
     }
 
     pub async fn stop_tracking_for_app_by_id(&self, app_id: &str) -> Result<(), String> {
-        // For now, we'll skip database operations
-        println!("Stopped tracking for app ID: {}", app_id);
-        Ok(())
+        match DatabaseHelpers::get_application(&self.base.db, app_id).await? {
+            Some(app) => self.stop_tracking_for_app(&app.process_name).await,
+            None => {
+                log::warn!("Could not find application with ID: {}", app_id);
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn set_active_task(&self, task_id: Option<String>) {
+        let mut state = self.base.state.lock().await;
+        state.active_task_id = task_id;
+    }
+
+    pub async fn set_focus_app(&self, app: Option<Application>) -> Result<(), String> {
+        // active_apps is keyed by process name on Windows
+        self.base.set_focus_app(app, is_process).await
+    }
+
+    pub async fn active_task_id(&self) -> Option<String> {
+        let state = self.base.state.lock().await;
+        state.active_task_id.clone()
     }
 
     pub async fn is_tracking(&self) -> bool {
         let state = self.base.state.lock().await;
         state.is_tracking
     }
+
+    pub async fn forget_running_entries(&self) -> Vec<String> {
+        self.base.forget_running_entries().await
+    }
+
+    pub async fn running_entry_ids(&self) -> Vec<String> {
+        self.base.running_entry_ids().await
+    }
+
+    pub async fn forget_lost_entries(&self) -> Result<usize, String> {
+        self.base.forget_lost_entries().await
+    }
+
+    pub async fn invalidate_activity_cache(&self) {
+        self.base.invalidate_activity_cache().await
+    }
+
+    pub async fn refresh_tracked_apps(&self) {
+        self.base.refresh_tracked_apps().await
+    }
+
+    pub async fn diagnose_focus(&self, tracked_apps: &[Application]) -> Result<FocusDiagnosis, String> {
+        let focused_process = self.get_foreground_process().await?;
+        // Same comparison update_activity uses
+        let matched_app = focused_process.as_ref().and_then(|process| {
+            tracked_apps.iter().find(|app| is_process(app, process)).map(|app| app.name.clone())
+        });
+        Ok(FocusDiagnosis { focused_process, matched_app })
+    }
+}
+
+/// Whether a stored application is the running process. Windows file names ignore case,
+/// so neither does this
+fn is_process(app: &Application, process_name: &str) -> bool {
+    app.process_name.eq_ignore_ascii_case(process_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::PlatformTracker;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    // A time_entries row still running since `started`
+    fn running_entry_row(id: &str, started: chrono::DateTime<chrono::Utc>) -> serde_json::Value {
+        json!({
+            "id": id,
+            "user_id": "user-1",
+            "app_id": "app-1",
+            "task_id": null,
+            "start_time": started.to_rfc3339(),
+            "end_time": null,
+            "duration_seconds": null,
+            "is_active": true,
+            "created_at": null,
+            "updated_at": null
+        })
+    }
+
+    #[tokio::test]
+    async fn stopping_an_app_ends_its_entry_in_the_database() {
+        let server = MockServer::start().await;
+        let started = chrono::Utc::now() - chrono::Duration::hours(1);
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([running_entry_row("entry-1", started)])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .and(|request: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+                body["is_active"] == false && body["duration_seconds"].as_i64().is_some_and(|seconds| seconds >= 3600)
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tracker = WindowsTracker::new(Database::new(server.uri(), "test-key".to_string()).unwrap());
+        tracker.base.state.lock().await.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+        let state = Arc::clone(&tracker.base.state);
+        let tracker = PlatformTracker::Windows(tracker);
+
+        tracker.stop_tracking_for_app("code.exe").await.unwrap();
+
+        assert!(state.lock().await.active_apps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_blip_shorter_than_the_minimum_is_deleted() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([running_entry_row(
+                "entry-1",
+                chrono::Utc::now() - chrono::Duration::seconds(2),
+            )])))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let tracker = WindowsTracker::new(Database::new(server.uri(), "test-key".to_string()).unwrap());
+        tracker.base.state.lock().await.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+
+        tracker.stop_tracking_for_app("Code.exe").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn entries_lost_while_offline_are_forgotten() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/v1/time_entries"))
+            .and(query_param("is_active", "eq.true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "id": "entry-1" }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tracker = WindowsTracker::new(Database::new(server.uri(), "test-key".to_string()).unwrap());
+        {
+            let mut state = tracker.base.state.lock().await;
+            state.active_apps.insert("Code.exe".to_string(), "entry-1".to_string());
+            state.active_apps.insert("chrome.exe".to_string(), "entry-2".to_string());
+        }
+
+        let lost = tracker.forget_lost_entries().await.unwrap();
+
+        assert_eq!(lost, 1);
+        let state = tracker.base.state.lock().await;
+        assert_eq!(state.active_apps.keys().collect::<Vec<_>>(), vec!["Code.exe"]);
+    }
 }
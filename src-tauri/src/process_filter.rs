@@ -0,0 +1,132 @@
+use crate::settings_file::SettingsFile;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+static OVERRIDE_FILE: SettingsFile = SettingsFile::new("ignored_processes.json", "ignored processes");
+
+// Built-in list of background/system processes that are never user apps
+static BUILT_IN: Lazy<Vec<String>> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../resources/background_processes.json"))
+        .expect("background_processes.json must be a JSON array of process names")
+});
+
+// Effective ignore list (lowercased), rebuilt whenever the override changes
+static IGNORED: Lazy<Arc<Mutex<HashSet<String>>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(build_ignored(&ProcessFilterOverride::default())))
+});
+
+/// User edits on top of the built-in list, stored in the app data dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessFilterOverride {
+    #[serde(default)]
+    pub ignore: Vec<String>, // Extra processes to hide
+    #[serde(default)]
+    pub unignore: Vec<String>, // Built-in entries the user wants to see again
+}
+
+fn build_ignored(overrides: &ProcessFilterOverride) -> HashSet<String> {
+    let unignored: HashSet<String> = overrides.unignore.iter().map(|name| name.to_lowercase()).collect();
+    BUILT_IN
+        .iter()
+        .chain(overrides.ignore.iter())
+        .map(|name| name.to_lowercase())
+        .filter(|name| !unignored.contains(name))
+        .collect()
+}
+
+fn reload(overrides: &ProcessFilterOverride) {
+    if let Ok(mut ignored) = IGNORED.lock() {
+        *ignored = build_ignored(overrides);
+    }
+}
+
+/// Set the directory the override file lives in (the app data dir) and load it
+pub fn set_override_dir(dir: PathBuf) {
+    OVERRIDE_FILE.set_dir(dir);
+    reload(&OVERRIDE_FILE.read());
+}
+
+/// Whether a process is a background/system process that should be hidden (case-insensitive)
+pub fn is_ignored(process_name: &str) -> bool {
+    IGNORED
+        .lock()
+        .map(|ignored| ignored.contains(&process_name.to_lowercase()))
+        .unwrap_or(false)
+}
+
+/// Current effective ignore list, sorted
+pub fn ignored_processes() -> Vec<String> {
+    let mut names: Vec<String> = IGNORED
+        .lock()
+        .map(|ignored| ignored.iter().cloned().collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Hide a process from detection and tracking
+pub fn add_ignored(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Process name is required".to_string());
+    }
+
+    OVERRIDE_FILE.update(
+        |overrides: &mut ProcessFilterOverride| {
+            overrides.unignore.retain(|n| !n.eq_ignore_ascii_case(name));
+            if !BUILT_IN.iter().any(|n| n.eq_ignore_ascii_case(name))
+                && !overrides.ignore.iter().any(|n| n.eq_ignore_ascii_case(name))
+            {
+                overrides.ignore.push(name.to_string());
+            }
+            Ok(())
+        },
+        reload,
+    )
+}
+
+/// Stop hiding a process, including one from the built-in list
+pub fn remove_ignored(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    OVERRIDE_FILE.update(
+        |overrides: &mut ProcessFilterOverride| {
+            overrides.ignore.retain(|n| !n.eq_ignore_ascii_case(name));
+            if BUILT_IN.iter().any(|n| n.eq_ignore_ascii_case(name))
+                && !overrides.unignore.iter().any(|n| n.eq_ignore_ascii_case(name))
+            {
+                overrides.unignore.push(name.to_string());
+            }
+            Ok(())
+        },
+        reload,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_processes_are_ignored_whatever_their_case() {
+        let ignored = build_ignored(&ProcessFilterOverride::default());
+
+        assert!(ignored.contains("explorer.exe"));
+        assert!(ignored.contains("wmiprvse.exe"));
+        assert!(!ignored.contains("code.exe"));
+    }
+
+    #[test]
+    fn overrides_add_and_remove_entries() {
+        let ignored = build_ignored(&ProcessFilterOverride {
+            ignore: vec!["Updater.exe".to_string()],
+            unignore: vec!["Explorer.EXE".to_string()],
+        });
+
+        assert!(ignored.contains("updater.exe"));
+        assert!(!ignored.contains("explorer.exe"));
+        assert!(ignored.contains("svchost.exe"));
+    }
+}
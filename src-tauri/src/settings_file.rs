@@ -0,0 +1,134 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A JSON file of user settings in the app data dir. Reads fall back to the default
+/// until the directory is known or when the file is missing or unreadable
+pub struct SettingsFile {
+    file_name: &'static str,
+    description: &'static str, // What the file holds, for error messages
+    dir: Mutex<Option<PathBuf>>,
+    edit: Mutex<()>, // Held across an update's read-modify-write
+}
+
+impl SettingsFile {
+    pub const fn new(file_name: &'static str, description: &'static str) -> Self {
+        Self {
+            file_name,
+            description,
+            dir: Mutex::new(None),
+            edit: Mutex::new(()),
+        }
+    }
+
+    /// Set the directory the file lives in, once the app data dir is known
+    pub fn set_dir(&self, dir: PathBuf) {
+        if let Ok(mut settings_dir) = self.dir.lock() {
+            *settings_dir = Some(dir);
+        }
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        self.dir
+            .lock()
+            .ok()
+            .and_then(|dir| dir.as_ref().map(|d| d.join(self.file_name)))
+    }
+
+    pub fn read<T: DeserializeOwned + Default>(&self) -> T {
+        let contents = match self.path().and_then(|path| fs::read_to_string(path).ok()) {
+            Some(contents) => contents,
+            None => return T::default(),
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable {}: {}", self.file_name, e);
+            T::default()
+        })
+    }
+
+    pub fn write<T: Serialize + ?Sized>(&self, value: &T) -> Result<(), String> {
+        let path = self
+            .path()
+            .ok_or_else(|| format!("Directory for {} not configured", self.description))?;
+        // The app data dir doesn't exist yet on a fresh install
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory for {}: {}", self.description, e))?;
+        }
+        let contents = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.description, e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", self.description, e))
+    }
+
+    /// Read, change and write the file as one step, then hand the saved value to `apply`
+    /// (e.g. to rebuild a cache) before another update can start, so concurrent edits
+    /// don't overwrite each other
+    pub fn update<T, F, A>(&self, change: F, apply: A) -> Result<(), String>
+    where
+        T: Serialize + DeserializeOwned + Default,
+        F: FnOnce(&mut T) -> Result<(), String>,
+        A: FnOnce(&T),
+    {
+        let _edit = self.edit.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut value: T = self.read();
+        change(&mut value)?;
+        self.write(&value)?;
+        apply(&value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn reads_the_default_until_a_file_is_written() {
+        let file = SettingsFile::new("settings.json", "test settings");
+        let unset: BTreeMap<String, String> = file.read();
+        assert!(unset.is_empty());
+        assert!(file.write(&unset).unwrap_err().contains("not configured"));
+
+        let dir = std::env::temp_dir().join(format!("macro-settings-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        file.set_dir(dir.clone());
+        let saved = BTreeMap::from([("code.exe".to_string(), "VS Code".to_string())]);
+        file.write(&saved).unwrap();
+        assert_eq!(file.read::<BTreeMap<String, String>>(), saved);
+
+        // A corrupt file is ignored rather than failing every read
+        fs::write(dir.join("settings.json"), "{not json").unwrap();
+        assert!(file.read::<BTreeMap<String, String>>().is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn concurrent_updates_keep_every_change() {
+        static FILE: SettingsFile = SettingsFile::new("counts.json", "test counts");
+        let dir = std::env::temp_dir().join(format!("macro-settings-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        FILE.set_dir(dir.clone());
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    FILE.update(
+                        |names: &mut Vec<String>| {
+                            names.push(format!("writer-{}", i));
+                            Ok(())
+                        },
+                        |_| {},
+                    )
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        assert_eq!(FILE.read::<Vec<String>>().len(), 8);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
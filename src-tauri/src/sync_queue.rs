@@ -0,0 +1,407 @@
+use crate::database::{Database, DbError};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const QUEUE_FILE_NAME: &str = "pending_writes.ndjson";
+const FLUSH_INTERVAL_SECS: u64 = 30;
+
+// Directory that holds the queue file, set once the app data dir is known
+static QUEUE_DIR: Lazy<Arc<Mutex<Option<PathBuf>>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(None))
+});
+
+static FLUSHER_STARTED: AtomicBool = AtomicBool::new(false);
+
+// Held for a whole flush so the background flusher and a reconnect never replay at once
+static FLUSHING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// A database write that could not be sent because the network was unavailable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingWrite {
+    pub method: String,
+    pub table: String, // Table path as passed to execute_query, may include a filter
+    pub payload: Option<serde_json::Value>,
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl PendingWrite {
+    /// Id of the row this write targets, taken from the payload or an `id=eq.` filter
+    fn entry_id(&self) -> Option<String> {
+        if let Some(id) = self.payload.as_ref().and_then(|p| p.get("id")).and_then(|id| id.as_str()) {
+            return Some(id.to_string());
+        }
+
+        let (_, query) = self.table.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("id=eq."))
+            .map(|id| id.to_string())
+    }
+
+    fn table_name(&self) -> &str {
+        self.table.split('?').next().unwrap_or(&self.table)
+    }
+}
+
+/// Set the directory the queue file lives in (the app data dir)
+pub fn set_queue_dir(dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::error!("Failed to create sync queue directory {:?}: {}", dir, e);
+        return;
+    }
+    if let Ok(mut queue_dir) = QUEUE_DIR.lock() {
+        *queue_dir = Some(dir);
+    }
+}
+
+fn queue_path() -> Option<PathBuf> {
+    QUEUE_DIR
+        .lock()
+        .ok()
+        .and_then(|dir| dir.as_ref().map(|d| d.join(QUEUE_FILE_NAME)))
+}
+
+fn read_queue(path: &Path) -> Vec<PendingWrite> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return Vec::new(),
+    };
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<PendingWrite>(line) {
+            Ok(write) => Some(write),
+            Err(e) => {
+                log::warn!("Skipping unreadable sync queue line: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Replace the queue file in one step, so a crash mid-write leaves either the old or the new queue
+fn write_queue(path: &Path, writes: &[PendingWrite]) -> Result<(), String> {
+    let mut contents = String::new();
+    for write in writes {
+        let line = serde_json::to_string(write)
+            .map_err(|e| format!("Failed to serialize pending write: {}", e))?;
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    let temp_path = path.with_extension("ndjson.tmp");
+    fs::write(&temp_path, contents).map_err(|e| format!("Failed to write sync queue: {}", e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace sync queue: {}", e))
+}
+
+/// Append a failed write to the queue file
+pub fn enqueue(method: &str, table: &str, payload: Option<serde_json::Value>) -> Result<(), String> {
+    let path = queue_path().ok_or_else(|| "Sync queue directory not configured".to_string())?;
+    let write = PendingWrite {
+        method: method.to_string(),
+        table: table.to_string(),
+        payload,
+        queued_at: chrono::Utc::now(),
+    };
+    let line = serde_json::to_string(&write)
+        .map_err(|e| format!("Failed to serialize pending write: {}", e))?;
+
+    let _guard = QUEUE_DIR.lock().map_err(|_| "Sync queue lock poisoned".to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open sync queue: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to append to sync queue: {}", e))?;
+
+    log::info!("Queued offline {} to {}", method, write.table_name());
+    Ok(())
+}
+
+/// Collapse writes that target the same row so replaying them is idempotent.
+/// A PATCH for a row that is still waiting to be inserted is merged into the
+/// insert, and a repeated insert replaces the earlier one.
+fn coalesce(writes: Vec<PendingWrite>) -> Vec<PendingWrite> {
+    let mut result: Vec<PendingWrite> = Vec::new();
+
+    for write in writes {
+        let key = write.entry_id().map(|id| (write.table_name().to_string(), id));
+        let existing = key.as_ref().and_then(|(table, id)| {
+            result.iter().position(|w| {
+                w.method == "POST" && w.table_name() == table && w.entry_id().as_deref() == Some(id.as_str())
+            })
+        });
+
+        match (write.method.as_str(), existing) {
+            ("POST", Some(index)) => result[index] = write,
+            ("PATCH", Some(index)) => {
+                if let (Some(serde_json::Value::Object(base)), Some(serde_json::Value::Object(patch))) =
+                    (result[index].payload.as_mut(), write.payload.as_ref())
+                {
+                    for (field, value) in patch {
+                        base.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            ("DELETE", Some(index)) => {
+                // The row never reached the server, so neither write needs sending
+                result.remove(index);
+            }
+            _ => result.push(write),
+        }
+    }
+
+    result
+}
+
+/// Payload of a queued insert for the given row, if it has not been synced yet
+pub fn pending_insert(table: &str, entry_id: &str) -> Option<serde_json::Value> {
+    let path = queue_path()?;
+    let _guard = QUEUE_DIR.lock().ok()?;
+    coalesce(read_queue(&path))
+        .into_iter()
+        .find(|w| w.method == "POST" && w.table_name() == table && w.entry_id().as_deref() == Some(entry_id))
+        .and_then(|w| w.payload)
+}
+
+/// Number of writes still waiting to be synced
+pub fn pending_count() -> usize {
+    match queue_path() {
+        Some(path) => {
+            let _guard = QUEUE_DIR.lock();
+            coalesce(read_queue(&path)).len()
+        }
+        None => 0,
+    }
+}
+
+async fn replay(db: &Database, write: &PendingWrite) -> Result<(), DbError> {
+    let url = format!("{}/rest/v1/{}", db.base_url, write.table);
+    let mut request = match write.method.as_str() {
+        "POST" => db.client.post(&url),
+        "PATCH" => db.client.patch(&url),
+        "DELETE" => db.client.delete(&url),
+        other => return Err(DbError::Other(format!("Unsupported queued method: {}", other))),
+    };
+
+    // Inserts are replayed as upserts in case the original request reached the server
+    let prefer = if write.method == "POST" {
+        "return=minimal, resolution=merge-duplicates"
+    } else {
+        "return=minimal"
+    };
+
+    request = request
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .header("Content-Type", "application/json")
+        .header("Prefer", prefer);
+
+    if let Some(payload) = &write.payload {
+        request = request.json(payload);
+    }
+
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(DbError::Http { status, body });
+    }
+
+    Ok(())
+}
+
+/// Replay queued writes in order, stopping at the first one that may succeed later.
+/// Writes the server rejects outright are dropped. Returns the number of writes that were synced.
+pub async fn flush(db: &Database) -> Result<usize, String> {
+    let _flushing = FLUSHING.lock().await;
+    match queue_path() {
+        Some(path) => flush_queue(db, &path).await,
+        None => Ok(0),
+    }
+}
+
+async fn flush_queue(db: &Database, path: &Path) -> Result<usize, String> {
+    // The file is left as is until the replay is over, so a crash part way through
+    // loses nothing; at worst some writes are replayed twice, which is harmless since inserts replay as upserts
+    let (taken, pending) = {
+        let _guard = QUEUE_DIR.lock().map_err(|_| "Sync queue lock poisoned".to_string())?;
+        let writes = read_queue(path);
+        (writes.len(), coalesce(writes))
+    };
+    if taken == 0 {
+        return Ok(0);
+    }
+
+    let mut synced = 0;
+    let mut done = 0;
+    let mut failure = None;
+    for write in &pending {
+        match replay(db, write).await {
+            Ok(()) => synced += 1,
+            // Replaying a write the server refuses would only block everything queued after it
+            Err(DbError::Http { status, body }) if status.is_client_error() => {
+                log::error!("Dropping queued {} to {}, rejected with {}: {}", write.method, write.table, status, body);
+            }
+            Err(e) => {
+                failure = Some(format!("Failed to replay write: {}", e));
+                break;
+            }
+        }
+        done += 1;
+    }
+
+    // Keep the writes that weren't replayed plus anything appended while replaying
+    {
+        let _guard = QUEUE_DIR.lock().map_err(|_| "Sync queue lock poisoned".to_string())?;
+        let mut remaining = pending[done..].to_vec();
+        remaining.extend(read_queue(path).into_iter().skip(taken));
+        write_queue(path, &remaining)?;
+    }
+
+    if synced > 0 {
+        log::info!("Synced {} queued writes", synced);
+    }
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(synced),
+    }
+}
+
+/// Start the background task that flushes the queue once the database is reachable again
+pub fn start_background_flush(db: Database) {
+    if FLUSHER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(FLUSH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            if pending_count() == 0 {
+                continue;
+            }
+
+            if let Ok(true) = db.test_connection().await {
+                if let Err(e) = flush(&db).await {
+                    log::warn!("Sync queue flush stopped early: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn write(method: &str, table: &str, payload: serde_json::Value) -> PendingWrite {
+        PendingWrite {
+            method: method.to_string(),
+            table: table.to_string(),
+            payload: Some(payload),
+            queued_at: chrono::Utc::now(),
+        }
+    }
+
+    fn queue_file(writes: &[PendingWrite]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("macro-sync-queue-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(QUEUE_FILE_NAME);
+        write_queue(&path, writes).unwrap();
+        path
+    }
+
+    #[test]
+    fn updates_to_an_unsynced_insert_are_folded_into_it() {
+        let writes = coalesce(vec![
+            write("POST", "time_entries", json!({ "id": "entry-1", "is_active": true })),
+            write("PATCH", "time_entries?id=eq.entry-1", json!({ "is_active": false, "duration_seconds": 60 })),
+            write("POST", "time_entries", json!({ "id": "entry-2", "is_active": true })),
+            write("DELETE", "time_entries?id=eq.entry-2", json!({})),
+            write("PATCH", "time_entries?id=eq.entry-3", json!({ "is_active": false })),
+        ]);
+
+        assert_eq!(writes.len(), 2);
+        assert_eq!(writes[0].method, "POST");
+        assert_eq!(writes[0].payload, Some(json!({ "id": "entry-1", "is_active": false, "duration_seconds": 60 })));
+        assert_eq!(writes[1].table, "time_entries?id=eq.entry-3");
+    }
+
+    #[tokio::test]
+    async fn a_successful_flush_empties_the_queue() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let path = queue_file(&[
+            write("PATCH", "time_entries?id=eq.entry-1", json!({ "is_active": false })),
+            write("PATCH", "time_entries?id=eq.entry-2", json!({ "is_active": false })),
+        ]);
+
+        assert_eq!(flush_queue(&db, &path).await, Ok(2));
+        assert!(read_queue(&path).is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_failed_flush_keeps_only_the_writes_that_were_not_synced() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(query_param("id", "eq.entry-2"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let path = queue_file(&[
+            write("PATCH", "time_entries?id=eq.entry-1", json!({ "is_active": false })),
+            write("PATCH", "time_entries?id=eq.entry-2", json!({ "is_active": false })),
+            write("PATCH", "time_entries?id=eq.entry-3", json!({ "is_active": false })),
+        ]);
+
+        assert!(flush_queue(&db, &path).await.is_err());
+
+        let remaining: Vec<String> = read_queue(&path).into_iter().map(|w| w.table).collect();
+        assert_eq!(remaining, vec!["time_entries?id=eq.entry-2", "time_entries?id=eq.entry-3"]);
+    }
+
+    #[tokio::test]
+    async fn a_rejected_write_is_dropped_without_blocking_the_rest() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(query_param("id", "eq.entry-1"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let db = Database::new(server.uri(), "test-key".to_string()).unwrap();
+        let path = queue_file(&[
+            write("PATCH", "time_entries?id=eq.entry-1", json!({ "is_active": false })),
+            write("PATCH", "time_entries?id=eq.entry-2", json!({ "is_active": false })),
+        ]);
+
+        assert_eq!(flush_queue(&db, &path).await, Ok(1));
+        assert!(read_queue(&path).is_empty());
+    }
+}
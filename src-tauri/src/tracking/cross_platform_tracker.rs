@@ -1,4 +1,4 @@
-use crate::database::Database;
+use crate::database::{Application, Database};
 use crate::platform::{PlatformTracker, TrackerFactory};
 use crate::tracking::CurrentActivity;
 use std::sync::Arc;
@@ -53,8 +53,58 @@ impl CrossPlatformTracker {
         tracker.stop_tracking_for_app_by_id(app_id).await
     }
 
+    pub async fn set_active_task(&self, task_id: Option<String>) {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.set_active_task(task_id).await
+    }
+
+    pub async fn set_focus_app(&self, app: Option<Application>) -> Result<(), String> {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.set_focus_app(app).await
+    }
+
+    pub async fn active_task_id(&self) -> Option<String> {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.active_task_id().await
+    }
+
     pub async fn is_tracking(&self) -> bool {
         let tracker = self.platform_tracker.lock().await;
         tracker.is_tracking().await
     }
+
+    pub async fn kind(&self) -> &'static str {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.kind()
+    }
+
+    pub async fn diagnose_focus(&self, tracked_apps: &[Application]) -> Result<crate::platform::FocusDiagnosis, String> {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.diagnose_focus(tracked_apps).await
+    }
+
+    pub async fn forget_running_entries(&self) -> Vec<String> {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.forget_running_entries().await
+    }
+
+    pub async fn running_entry_ids(&self) -> Vec<String> {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.running_entry_ids().await
+    }
+
+    pub async fn forget_lost_entries(&self) -> Result<usize, String> {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.forget_lost_entries().await
+    }
+
+    pub async fn invalidate_activity_cache(&self) {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.invalidate_activity_cache().await
+    }
+
+    pub async fn refresh_tracked_apps(&self) {
+        let tracker = self.platform_tracker.lock().await;
+        tracker.refresh_tracked_apps().await
+    }
 }
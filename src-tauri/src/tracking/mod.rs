@@ -1,4 +1,4 @@
-use crate::database::{Database, TimeEntry, Application};
+use crate::database::{encode_filter_value, Database, DbError, TimeEntry, Application};
 use crate::tracking::cross_platform_tracker::CrossPlatformTracker;
 use serde_json::json;
 use std::sync::{Arc, Mutex};
@@ -102,17 +102,12 @@ fn get_most_active_process() -> Option<String> {
 }
 
 fn is_system_process(name: &str) -> bool {
-    let system_processes = [
-        "dwm.exe", "winlogon.exe", "csrss.exe", "wininit.exe", 
-        "services.exe", "lsass.exe", "svchost.exe", "explorer.exe",
-        "System", "Registry", "smss.exe", "audiodg.exe", "conhost.exe"
-    ];
-    
-    system_processes.iter().any(|&sys_proc| name.eq_ignore_ascii_case(sys_proc))
+    crate::process_filter::is_ignored(name)
 }
 
 // Cross-platform tracker module
 pub mod cross_platform_tracker;
+pub mod pomodoro;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CurrentActivity {
@@ -123,6 +118,10 @@ pub struct CurrentActivity {
     pub duration_hours: i64,
     pub is_active: bool,
     pub active_apps_count: usize,
+    #[serde(default)]
+    pub focus_app_name: Option<String>, // Set while a focus session is running
+    #[serde(default)]
+    pub on_focus_app: Option<bool>, // Whether the user is on the focus session's app
 }
 
 #[derive(Debug, Clone)]
@@ -134,6 +133,7 @@ pub struct TrackingState {
     pub last_focused_change: Instant,        // When focus last changed
     pub cached_current_activity: Option<CurrentActivity>, // Cached current activity
     pub cache_last_updated: Instant, // When the cache was last updated
+    pub active_task_id: Option<String>, // Task that new time entries are linked to
 }
 
 impl Default for TrackingState {
@@ -146,6 +146,7 @@ impl Default for TrackingState {
             last_focused_change: Instant::now(),
             cached_current_activity: None,
             cache_last_updated: Instant::now(),
+            active_task_id: None,
         }
     }
 }
@@ -171,7 +172,7 @@ impl ActivityTracker {
         };
         
         if already_tracking {
-            println!("Tracking is already running, skipping start");
+            log::info!("Tracking is already running, skipping start");
             return Ok(());
         }
         
@@ -183,9 +184,11 @@ impl ActivityTracker {
         state.last_activity_time = Instant::now();
         drop(state);
 
-        // Start the tracking loop
-        let state_clone = Arc::clone(&self.state);
-        let db_clone = self.db.clone();
+        // Start the tracking loop; one tracker serves every tick so the client's pool is reused
+        let tracker = ActivityTracker {
+            state: Arc::clone(&self.state),
+            db: self.db.clone(),
+        };
         
         tokio::spawn(async move {
             let mut interval = interval(Duration::from_secs(5)); // Check every 5 seconds
@@ -193,7 +196,7 @@ impl ActivityTracker {
                 interval.tick().await;
                 
                 let should_continue = {
-                    let state = state_clone.lock().unwrap();
+                    let state = tracker.state.lock().unwrap();
                     state.is_tracking
                 };
                 
@@ -202,13 +205,8 @@ impl ActivityTracker {
                 }
                 
                 // Update activity tracking
-                let tracker = ActivityTracker {
-                    state: Arc::clone(&state_clone),
-                    db: db_clone.clone(),
-                };
-                
                 if let Err(e) = tracker.update_activity().await {
-                    eprintln!("Error updating activity: {}", e);
+                    log::error!("Error updating activity: {}", e);
                 }
             }
         });
@@ -221,6 +219,7 @@ impl ActivityTracker {
             let mut state = self.state.lock().unwrap();
             state.is_tracking = false;
             state.current_focused_app = None;
+            state.active_task_id = None;
             state.cached_current_activity = None; // Clear cache when stopping tracking
             state.cache_last_updated = Instant::now();
             state.current_entry_id.take()
@@ -238,7 +237,7 @@ impl ActivityTracker {
         // Get the currently focused window's process name
         let focused_process_name = get_focused_window_process_name();
         
-        println!("🔍 Currently focused window process: {:?}", focused_process_name);
+        log::debug!("Currently focused window process: {:?}", focused_process_name);
         
         // Get tracked applications from database
         let tracked_apps = self.get_tracked_applications().await?;
@@ -258,7 +257,7 @@ impl ActivityTracker {
             let has_focus_changed = current_focused != &focused_process_name;
             
             if has_focus_changed {
-                println!("🔄 Focus changed from {:?} to {:?}", current_focused, focused_process_name);
+                log::debug!("Focus changed from {:?} to {:?}", current_focused, focused_process_name);
                 state.last_focused_change = Instant::now();
                 state.current_focused_app = focused_process_name.clone();
                 
@@ -282,7 +281,7 @@ impl ActivityTracker {
                 let mut state = self.state.lock().unwrap();
                 state.current_entry_id.take()
             } {
-                println!("⏹️ Ending tracking for entry_id: {}", entry_id);
+                log::info!("Ending tracking for entry_id: {}", entry_id);
                 let _ = Self::end_time_entry(&self.db, entry_id).await;
             }
         }
@@ -290,15 +289,15 @@ impl ActivityTracker {
         // Start tracking new focused app if it's a tracked application
         if should_start_new {
             if let Some(app) = new_app {
-                println!("▶️ Starting tracking for focused app: {}", app.name);
+                log::info!("Starting tracking for focused app: {}", app.name);
                 match self.start_time_entry(&app).await {
                     Ok(entry_id) => {
                         let mut state = self.state.lock().unwrap();
                         state.current_entry_id = Some(entry_id.clone());
-                        println!("✅ Started tracking for {} (entry_id: {})", app.name, entry_id);
+                        log::info!("Started tracking for {} (entry_id: {})", app.name, entry_id);
                     }
                     Err(e) => {
-                        eprintln!("❌ Failed to start time entry for {}: {}", app.name, e);
+                        log::error!("Failed to start time entry for {}: {}", app.name, e);
                     }
                 }
             }
@@ -345,7 +344,7 @@ impl ActivityTracker {
         };
         
         let url = format!("{}/rest/v1/applications?user_id=eq.{}&is_tracked=eq.true", 
-                         self.db.base_url, user_id);
+                         self.db.base_url, encode_filter_value(&user_id));
         let response = self.db.client
             .get(&url)
             .header("apikey", &self.db.api_key)
@@ -357,7 +356,7 @@ impl ActivityTracker {
         if response.status().is_success() {
             let apps: Vec<Application> = response.json().await
                 .map_err(|e| format!("Failed to parse applications: {}", e))?;
-            Ok(apps)
+            Ok(crate::platform::database_helpers::DatabaseHelpers::apply_process_aliases(&self.db, &user_id, apps).await)
         } else {
             Ok(Vec::new())
         }
@@ -372,7 +371,7 @@ impl ActivityTracker {
         
         // Find any existing active time entries for this user
     let url = format!("{}/rest/v1/time_entries?user_id=eq.{}&is_active=eq.true", 
-             self.db.base_url, user_id);
+             self.db.base_url, encode_filter_value(&user_id));
         let response = self.db.client
             .get(&url)
             .header("apikey", &self.db.api_key)
@@ -388,7 +387,7 @@ impl ActivityTracker {
             // End all existing active entries
             for entry in entries {
                 let _ = Self::end_time_entry(&self.db, entry.id).await;
-                println!("Cleaned up existing active entry for app_id: {:?}", entry.app_id);
+                log::info!("Cleaned up existing active entry for app_id: {:?}", entry.app_id);
             }
         }
         
@@ -401,34 +400,41 @@ impl ActivityTracker {
             Some(id) => id,
             None => return Err("No user logged in".to_string())
         };
+        let active_task_id = self.state.lock().unwrap().active_task_id.clone();
         
         // First check if there's already an active time entry for this app
     let existing_entry_url = format!("{}/rest/v1/time_entries?user_id=eq.{}&app_id=eq.{}&is_active=eq.true", 
-                       self.db.base_url, user_id, app.id);
+                       self.db.base_url, encode_filter_value(&user_id), encode_filter_value(&app.id));
         let existing_response = self.db.client
             .get(&existing_entry_url)
             .header("apikey", &self.db.api_key)
             .header("Authorization", format!("Bearer {}", self.db.api_key))
             .send()
-            .await
-            .map_err(|e| format!("Failed to check existing time entries: {}", e))?;
+            .await;
+
+        // If the check can't reach the server, fall through and let the insert be queued
+        if let Err(e) = &existing_response {
+            log::warn!("Failed to check existing time entries: {}", e);
+        }
 
-        if existing_response.status().is_success() {
+        if let Ok(existing_response) = existing_response.and_then(|r| r.error_for_status()) {
             let existing_entries: Vec<TimeEntry> = existing_response.json().await
                 .map_err(|e| format!("Failed to parse existing time entries: {}", e))?;
             
             if let Some(existing_entry) = existing_entries.first() {
-                println!("Found existing active time entry for {} (id: {}), reusing it", app.name, existing_entry.id);
+                log::debug!("Found existing active time entry for {} (id: {}), reusing it", app.name, existing_entry.id);
                 return Ok(existing_entry.id.clone());
             }
         }
 
         // No existing active entry found, create a new one
+        let workspace_id = crate::platform::database_helpers::DatabaseHelpers::current_workspace_id(&self.db, &user_id).await;
         let time_entry_data = json!({
             "id": uuid::Uuid::new_v4().to_string(),
             "user_id": user_id,
             "app_id": app.id,
-            "task_id": null,
+            "task_id": active_task_id,
+            "workspace_id": workspace_id,
             "start_time": chrono::Utc::now().to_rfc3339(),
             "end_time": null,
             "duration_seconds": null,
@@ -437,17 +443,19 @@ impl ActivityTracker {
             "updated_at": chrono::Utc::now().to_rfc3339()
         });
 
-        let response = self.db
-            .execute_query("time_entries", "POST", Some(time_entry_data))
-            .await
-            .map_err(|e| format!("Failed to create time entry: {}", e))?;
+        let response = match self.db.upsert("time_entries", "id", time_entry_data.clone()).await {
+            // Offline: the insert waits in the sync queue, so carry on with the row as sent
+            Err(DbError::Queued) => json!([time_entry_data]),
+            response => response.map_err(|e| format!("Failed to create time entry: {}", e))?,
+        };
 
         // The response should be an array with the created record
         let created_entries: Vec<TimeEntry> = serde_json::from_value(response)
             .map_err(|e| format!("Failed to parse created time entry: {}", e))?;
 
         if let Some(created_entry) = created_entries.first() {
-            println!("Created new time entry for {} (id: {})", app.name, created_entry.id);
+            log::info!("Created new time entry for {} (id: {})", app.name, created_entry.id);
+            crate::platform::database_helpers::DatabaseHelpers::mark_application_used(&self.db, &app.id, created_entry.start_time);
             Ok(created_entry.id.clone())
         } else {
             Err("No time entry was created".to_string())
@@ -455,61 +463,9 @@ impl ActivityTracker {
     }
 
     async fn end_time_entry(db: &Database, entry_id: String) -> Result<(), String> {
-        // First, get the current time entry to access the start_time
-        let get_url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, entry_id);
-        let get_response = db.client
-            .get(&get_url)
-            .header("apikey", &db.api_key)
-            .header("Authorization", format!("Bearer {}", db.api_key))
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch time entry: {}", e))?;
-
-        if !get_response.status().is_success() {
-            let status = get_response.status();
-            let error_text = get_response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("HTTP error {}: {}", status, error_text));
-        }
-
-        let time_entries: Vec<TimeEntry> = get_response.json().await
-            .map_err(|e| format!("Failed to parse time entry: {}", e))?;
-        
-        let time_entry = time_entries.first()
-            .ok_or("Time entry not found")?;
-
-        let end_time = chrono::Utc::now();
-        let start_time = time_entry.start_time;
-        
-        // Calculate duration in seconds
-        let duration_seconds = (end_time - start_time).num_seconds();
-        
-        let update_data = json!({
-            "end_time": end_time.to_rfc3339(),
-            "duration_seconds": duration_seconds,
-            "is_active": false,
-            "updated_at": chrono::Utc::now().to_rfc3339()
-        });
-
-        let url = format!("{}/rest/v1/time_entries?id=eq.{}", db.base_url, entry_id);
-        let response = db.client
-            .patch(&url)
-            .header("apikey", &db.api_key)
-            .header("Authorization", format!("Bearer {}", db.api_key))
-            .header("Content-Type", "application/json")
-            .header("Prefer", "return=representation")
-            .json(&update_data)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to update time entry: {}", e))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("HTTP error {}: {}", status, error_text));
-        }
-
-        println!("Ended time entry {} with duration: {} seconds", entry_id, duration_seconds);
-        Ok(())
+        // Shares the platform helper so ended entries are queued while offline; this tracker
+        // doesn't keep start times, so an entry ended offline relies on the queued insert
+        crate::platform::database_helpers::DatabaseHelpers::end_time_entry(db, entry_id, None).await
     }
 
     pub async fn get_current_activity(&self) -> Result<Option<CurrentActivity>, String> {
@@ -544,6 +500,8 @@ impl ActivityTracker {
                     duration_hours: duration.num_hours(),
                     is_active: cached.is_active,
                     active_apps_count: 1, // Always 1 since we track only focused app
+                    focus_app_name: None,
+                    on_focus_app: None,
                 }));
             }
         }
@@ -560,7 +518,7 @@ impl ActivityTracker {
         
         if let Some(entry_id) = current_entry_id {
             // Get the time entry details
-            let url = format!("{}/rest/v1/time_entries?id=eq.{}", self.db.base_url, entry_id);
+            let url = format!("{}/rest/v1/time_entries?id=eq.{}", self.db.base_url, encode_filter_value(&entry_id));
             let response = self.db.client
                 .get(&url)
                 .header("apikey", &self.db.api_key)
@@ -576,43 +534,33 @@ impl ActivityTracker {
                 if let Some(entry) = entries.first() {
                     // Get app details if app_id exists
                     if let Some(app_id) = &entry.app_id {
-                        let app_url = format!("{}/rest/v1/applications?id=eq.{}", self.db.base_url, app_id);
-                        let app_response = self.db.client
-                            .get(&app_url)
-                            .header("apikey", &self.db.api_key)
-                            .header("Authorization", format!("Bearer {}", self.db.api_key))
-                            .send()
-                            .await
-                            .map_err(|e| format!("Failed to fetch application: {}", e))?;
-
-                        if app_response.status().is_success() {
-                            let apps: Vec<Application> = app_response.json().await
-                                .map_err(|e| format!("Failed to parse application: {}", e))?;
+                        let app = crate::platform::database_helpers::DatabaseHelpers::get_application(&self.db, app_id).await?;
+                        
+                        if let Some(app) = app {
+                            let duration = chrono::Utc::now().signed_duration_since(entry.start_time);
+                            let duration_minutes = duration.num_minutes();
+                            let duration_hours = duration.num_hours();
+                            
+                            let activity = CurrentActivity {
+                                app_name: app.name.clone(),
+                                app_category: app.category.clone().unwrap_or_else(|| "Other".to_string()),
+                                start_time: entry.start_time,
+                                duration_minutes,
+                                duration_hours,
+                                is_active: entry.is_active,
+                                active_apps_count: 1, // Always 1 for focused tracking
+                                focus_app_name: None,
+                                on_focus_app: None,
+                            };
                             
-                            if let Some(app) = apps.first() {
-                                let duration = chrono::Utc::now().signed_duration_since(entry.start_time);
-                                let duration_minutes = duration.num_minutes();
-                                let duration_hours = duration.num_hours();
-                                
-                                let activity = CurrentActivity {
-                                    app_name: app.name.clone(),
-                                    app_category: app.category.clone().unwrap_or_else(|| "Other".to_string()),
-                                    start_time: entry.start_time,
-                                    duration_minutes,
-                                    duration_hours,
-                                    is_active: entry.is_active,
-                                    active_apps_count: 1, // Always 1 for focused tracking
-                                };
-                                
-                                // Cache the result
-                                {
-                                    let mut state = self.state.lock().unwrap();
-                                    state.cached_current_activity = Some(activity.clone());
-                                    state.cache_last_updated = Instant::now();
-                                }
-                                
-                                return Ok(Some(activity));
+                            // Cache the result
+                            {
+                                let mut state = self.state.lock().unwrap();
+                                state.cached_current_activity = Some(activity.clone());
+                                state.cache_last_updated = Instant::now();
                             }
+                            
+                            return Ok(Some(activity));
                         }
                     }
                 }
@@ -622,6 +570,11 @@ impl ActivityTracker {
         Ok(None)
     }
 
+    pub fn set_active_task(&self, task_id: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.active_task_id = task_id;
+    }
+
     pub async fn get_active_applications_count(&self) -> Result<usize, String> {
         let state = self.state.lock().unwrap();
         Ok(if state.current_entry_id.is_some() { 1 } else { 0 })
@@ -642,7 +595,7 @@ impl ActivityTracker {
             };
             
             if let Some(entry_id) = entry_id_to_end {
-                println!("Stopping tracking for app: {} (entry_id: {})", process_name, entry_id);
+                log::info!("Stopping tracking for app: {} (entry_id: {})", process_name, entry_id);
                 let _ = Self::end_time_entry(&self.db, entry_id).await;
             }
         }
@@ -654,7 +607,7 @@ impl ActivityTracker {
     pub async fn stop_tracking_for_app_by_id(&self, app_id: &str) -> Result<(), String> {
         // Find the process name for this app ID
         let process_name = {
-            let url = format!("{}/rest/v1/applications?id=eq.{}", self.db.base_url, app_id);
+            let url = format!("{}/rest/v1/applications?id=eq.{}", self.db.base_url, encode_filter_value(app_id));
             let response = self.db.client
                 .get(&url)
                 .header("apikey", &self.db.api_key)
@@ -680,7 +633,7 @@ impl ActivityTracker {
         if let Some(process_name) = process_name {
             self.stop_tracking_for_app(&process_name).await
         } else {
-            println!("Could not find application with ID: {}", app_id);
+            log::warn!("Could not find application with ID: {}", app_id);
             Ok(())
         }
     }
@@ -722,6 +675,8 @@ pub async fn start_activity_tracking() -> Result<(), String> {
 
 #[tauri::command]
 pub async fn stop_activity_tracking() -> Result<(), String> {
+    // A manual stop also ends any Pomodoro schedule driving the tracker
+    pomodoro::cancel();
     if let Some(tracker) = get_tracker() {
         tracker.stop_tracking().await
     } else {
@@ -775,7 +730,207 @@ pub async fn stop_tracking_for_app_by_id(app_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn get_detected_os() -> Result<String, String> {
+pub async fn set_active_task(task_id: Option<String>) -> Result<(), String> {
+    if let Some(tracker) = get_tracker() {
+        tracker.set_active_task(task_id).await;
+        Ok(())
+    } else {
+        Err("Activity tracker not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn start_focus_session(db: tauri::State<'_, Database>, app_id: String) -> Result<(), String> {
+    let tracker = get_tracker().ok_or_else(|| "Activity tracker not initialized".to_string())?;
+
+    let app = crate::platform::database_helpers::DatabaseHelpers::get_application(&db, &app_id)
+        .await?
+        .ok_or_else(|| format!("Application not found: {}", app_id))?;
+
+    log::info!("Starting focus session on {}", app.name);
+    tracker.set_focus_app(Some(app)).await?;
+
+    if !tracker.is_tracking().await {
+        tracker.start_tracking().await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn end_focus_session() -> Result<(), String> {
+    if let Some(tracker) = get_tracker() {
+        tracker.set_focus_app(None).await
+    } else {
+        Err("Activity tracker not initialized".to_string())
+    }
+}
+
+/// Stop tracking and end every running entry the current user has in the database,
+/// including ones left behind by a crash. Returns how many entries were closed.
+#[tauri::command]
+pub async fn clear_all_tracking(db: tauri::State<'_, Database>) -> Result<usize, String> {
+    use crate::platform::database_helpers::DatabaseHelpers;
+
+    let user_id = crate::current_user::get_current_user_id_or_error()?;
+
+    pomodoro::cancel();
+    if let Some(tracker) = get_tracker() {
+        // Forget the running entries first so stopping doesn't end them twice;
+        // the sweep below closes them along with any stale ones
+        let forgotten = tracker.forget_running_entries().await;
+        log::info!("clear_all_tracking: Dropped {} in-memory entries", forgotten.len());
+        tracker.stop_tracking().await?;
+    }
+
+    let url = format!("{}/rest/v1/time_entries?user_id=eq.{}&is_active=eq.true&select=id", db.base_url, encode_filter_value(&user_id));
+    let response = db.client
+        .get(&url)
+        .header("apikey", &db.api_key)
+        .header("Authorization", format!("Bearer {}", db.api_key))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch active time entries: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch active time entries: {}", response.status()));
+    }
+
+    let rows: Vec<serde_json::Value> = response.json().await
+        .map_err(|e| format!("Failed to parse active time entries: {}", e))?;
+
+    let mut closed = 0;
+    for entry_id in rows.iter().filter_map(|row| row["id"].as_str()) {
+        match DatabaseHelpers::end_time_entry(&db, entry_id.to_string(), None).await {
+            Ok(()) => closed += 1,
+            Err(e) => log::warn!("clear_all_tracking: Failed to end entry {}: {}", entry_id, e),
+        }
+    }
+
+    log::info!("clear_all_tracking: Closed {} active entries for user {}", closed, user_id);
+    Ok(closed)
+}
+
+#[tauri::command]
+pub async fn get_tracking_settings() -> Result<crate::tracking_settings::TrackingSettings, String> {
+    Ok(crate::tracking_settings::current())
+}
+
+/// Cap how long a single entry may run before it is split; None disables splitting
+#[tauri::command]
+pub async fn set_max_session_length(seconds: Option<u64>) -> Result<crate::tracking_settings::TrackingSettings, String> {
+    crate::tracking_settings::set_max_session_seconds(seconds)
+}
+
+/// Discard tracker entries shorter than this many seconds; None (or 0) keeps them all.
+/// Manually created entries are never discarded
+#[tauri::command]
+pub async fn set_min_entry_duration(seconds: Option<u64>) -> Result<crate::tracking_settings::TrackingSettings, String> {
+    crate::tracking_settings::set_min_entry_seconds(seconds)
+}
+
+/// How many seconds apart the `usage-snapshot` event is emitted while tracking; None disables it
+#[tauri::command]
+pub async fn set_usage_snapshot_interval(seconds: Option<u64>) -> Result<crate::tracking_settings::TrackingSettings, String> {
+    crate::tracking_settings::set_usage_snapshot_seconds(seconds)
+}
+
+/// Switch between "frontmost" (default) and "visible" tracking; only the macOS tracker
+/// uses visible mode, where apps left on screen keep being counted
+#[tauri::command]
+pub async fn set_tracking_mode(mode: crate::tracking_settings::TrackingMode) -> Result<crate::tracking_settings::TrackingSettings, String> {
+    log::info!("Tracking mode set to {:?}", mode);
+    crate::tracking_settings::set_tracking_mode(mode)
+}
+
+/// Overall tracker state reported to the frontend
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrackingStatus {
+    pub is_tracking: bool,
+    pub active_task_id: Option<String>,
+    pub pomodoro: Option<pomodoro::PomodoroStatus>,
+}
+
+#[tauri::command]
+pub async fn get_tracking_state() -> Result<TrackingStatus, String> {
+    if let Some(tracker) = get_tracker() {
+        Ok(TrackingStatus {
+            is_tracking: tracker.is_tracking().await,
+            active_task_id: tracker.active_task_id().await,
+            pomodoro: pomodoro::current_status(),
+        })
+    } else {
+        Err("Activity tracker not initialized".to_string())
+    }
+}
+
+/// Everything support needs to see why tracking isn't picking an app up
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrackingDiagnostics {
+    pub os: crate::platform::OperatingSystem,
+    pub tracker: Option<String>, // Tracker implementation in use, None if it failed to initialize
+    pub native_tracker: bool, // false when another platform's tracker is standing in
+    pub is_tracking: bool,
+    pub focused_process: Option<String>,
+    pub tracked_app_count: usize,
+    pub tracked_process_names: Vec<String>,
+    pub focused_app_match: Option<String>, // Tracked app the focused process matches
+    pub focused_is_tracked: bool,
+    pub active_entry_count: usize,
+}
+
+#[tauri::command]
+pub async fn get_tracking_diagnostics(db: tauri::State<'_, Database>) -> Result<TrackingDiagnostics, String> {
+    use crate::platform::database_helpers::DatabaseHelpers;
+
+    let os = crate::platform::detect_os();
+    let tracked_apps = DatabaseHelpers::get_tracked_applications(&db).await?;
+    let active_entry_count = DatabaseHelpers::get_active_time_entries(&db).await?.len();
+
+    let (tracker, is_tracking, focus) = match get_tracker() {
+        Some(tracker) => {
+            let focus = tracker.diagnose_focus(&tracked_apps).await.unwrap_or_else(|e| {
+                log::warn!("Focused app detection failed: {}", e);
+                crate::platform::FocusDiagnosis { focused_process: None, matched_app: None }
+            });
+            (Some(tracker.kind().await), tracker.is_tracking().await, focus)
+        }
+        None => (None, false, crate::platform::FocusDiagnosis { focused_process: None, matched_app: None }),
+    };
+
+    Ok(TrackingDiagnostics {
+        native_tracker: tracker == Some(os.as_str()),
+        tracker: tracker.map(str::to_string),
+        is_tracking,
+        focused_process: focus.focused_process,
+        tracked_app_count: tracked_apps.len(),
+        tracked_process_names: tracked_apps.iter().map(|app| app.process_name.clone()).collect(),
+        focused_is_tracked: focus.matched_app.is_some(),
+        focused_app_match: focus.matched_app,
+        active_entry_count,
+        os,
+    })
+}
+
+/// Host OS details reported to the frontend
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DetectedOs {
+    pub os: crate::platform::OperatingSystem,
+    pub version: Option<String>, // e.g. "Windows 11 Pro" or "macOS 14.2 Sonoma"
+    pub tracking_supported: bool,
+}
+
+/// Seconds since the last keyboard or mouse input; 0 where the OS doesn't report it
+#[tauri::command]
+pub async fn get_idle_time_seconds() -> Result<u64, String> {
+    Ok(crate::platform::idle::idle_time_seconds())
+}
+
+#[tauri::command]
+pub async fn get_detected_os() -> Result<DetectedOs, String> {
     let os = crate::platform::detect_os();
-    Ok(format!("{:?}", os))
+    Ok(DetectedOs {
+        tracking_supported: crate::platform::tracking_supported(&os),
+        version: System::long_os_version(),
+        os,
+    })
 }
@@ -0,0 +1,154 @@
+use crate::tracking::get_tracker;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PomodoroPhase {
+    Work,
+    Break,
+}
+
+/// Snapshot of the running Pomodoro schedule, reported to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    pub current_cycle: u32,
+    pub total_cycles: u32,
+    pub work_minutes: u32,
+    pub break_minutes: u32,
+    pub phase_ends_at: chrono::DateTime<chrono::Utc>,
+    pub remaining_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PomodoroEvent {
+    cycle: u32,
+    total_cycles: u32,
+}
+
+// Current schedule, None when no Pomodoro is running
+static POMODORO: Lazy<Arc<Mutex<Option<PomodoroStatus>>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(None))
+});
+
+// Bumped on every start/cancel so an outdated driver loop knows to exit
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn set_phase(phase: PomodoroPhase, cycle: u32, total_cycles: u32, work_minutes: u32, break_minutes: u32) {
+    let minutes = match phase {
+        PomodoroPhase::Work => work_minutes,
+        PomodoroPhase::Break => break_minutes,
+    };
+    if let Ok(mut schedule) = POMODORO.lock() {
+        *schedule = Some(PomodoroStatus {
+            phase,
+            current_cycle: cycle,
+            total_cycles,
+            work_minutes,
+            break_minutes,
+            phase_ends_at: chrono::Utc::now() + chrono::Duration::minutes(minutes as i64),
+            remaining_seconds: minutes as i64 * 60,
+        });
+    }
+}
+
+// Sleep for the given number of minutes, returning false if the schedule was cancelled meanwhile
+async fn wait_phase(minutes: u32, generation: u64) -> bool {
+    let mut remaining = minutes as u64 * 60;
+    while remaining > 0 {
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        remaining -= 1;
+    }
+    GENERATION.load(Ordering::SeqCst) == generation
+}
+
+/// Current Pomodoro status with the remaining time recalculated
+pub fn current_status() -> Option<PomodoroStatus> {
+    let schedule = POMODORO.lock().ok()?;
+    schedule.clone().map(|mut status| {
+        status.remaining_seconds = (status.phase_ends_at - chrono::Utc::now()).num_seconds().max(0);
+        status
+    })
+}
+
+/// Cancel any running Pomodoro schedule
+pub fn cancel() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut schedule) = POMODORO.lock() {
+        if schedule.take().is_some() {
+            log::info!("Pomodoro cancelled");
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn start_pomodoro(
+    app_handle: AppHandle,
+    work_minutes: u32,
+    break_minutes: u32,
+    cycles: u32,
+) -> Result<(), String> {
+    if work_minutes == 0 || cycles == 0 {
+        return Err("Work minutes and cycles must be greater than zero".to_string());
+    }
+    if get_tracker().is_none() {
+        return Err("Activity tracker not initialized".to_string());
+    }
+
+    // Replace any schedule that is already running
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    tokio::spawn(async move {
+        let tracker = match get_tracker() {
+            Some(tracker) => tracker,
+            None => return,
+        };
+
+        for cycle in 1..=cycles {
+            set_phase(PomodoroPhase::Work, cycle, cycles, work_minutes, break_minutes);
+            if let Err(e) = tracker.start_tracking().await {
+                log::error!("Pomodoro failed to start tracking: {}", e);
+            }
+
+            if !wait_phase(work_minutes, generation).await {
+                return;
+            }
+
+            // Stopping clears the active task, so carry it over to the next work interval
+            let active_task_id = tracker.active_task_id().await;
+            if let Err(e) = tracker.stop_tracking().await {
+                log::error!("Pomodoro failed to pause tracking: {}", e);
+            }
+            tracker.set_active_task(active_task_id).await;
+
+            if cycle == cycles {
+                break;
+            }
+
+            set_phase(PomodoroPhase::Break, cycle, cycles, work_minutes, break_minutes);
+            let _ = app_handle.emit("pomodoro-break", PomodoroEvent { cycle, total_cycles: cycles });
+
+            if !wait_phase(break_minutes, generation).await {
+                return;
+            }
+        }
+
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            if let Ok(mut schedule) = POMODORO.lock() {
+                *schedule = None;
+            }
+            let _ = app_handle.emit("pomodoro-complete", PomodoroEvent { cycle: cycles, total_cycles: cycles });
+            log::info!("Pomodoro complete after {} cycles", cycles);
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use crate::settings_file::SettingsFile;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+static SETTINGS_FILE: SettingsFile = SettingsFile::new("tracking_settings.json", "tracking settings");
+
+const DEFAULT_MAX_SESSION_SECONDS: u64 = 8 * 60 * 60;
+
+// Shortest session cap accepted, so entries aren't chopped into slivers
+const MIN_MAX_SESSION_SECONDS: u64 = 60;
+
+const DEFAULT_USAGE_SNAPSHOT_SECONDS: u64 = 5 * 60;
+
+// Each snapshot queries the day's entries, so don't allow them more often than this
+const MIN_USAGE_SNAPSHOT_SECONDS: u64 = 60;
+
+const DEFAULT_MIN_ENTRY_SECONDS: u64 = 5;
+
+// Highest threshold accepted, so a typo can't start deleting real work
+const MAX_MIN_ENTRY_SECONDS: u64 = 5 * 60;
+
+static SETTINGS: Lazy<Arc<Mutex<TrackingSettings>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(TrackingSettings::default()))
+});
+
+/// Which apps count as in use on macOS
+///
+/// `Frontmost` only tracks the app with keyboard focus. `Visible` also keeps entries
+/// open for tracked apps with a visible window on the active Space, which covers
+/// split-screen work but will count an app that is merely left on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackingMode {
+    #[default]
+    Frontmost,
+    Visible,
+}
+
+/// User-adjustable tracker behaviour, stored in the app data dir
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingSettings {
+    #[serde(default = "default_max_session_seconds")]
+    pub max_session_seconds: Option<u64>, // Longest single entry before it is split; None disables splitting
+    #[serde(default = "default_usage_snapshot_seconds")]
+    pub usage_snapshot_seconds: Option<u64>, // How often `usage-snapshot` is emitted; None disables it
+    #[serde(default)]
+    pub tracking_mode: TrackingMode,
+    #[serde(default = "default_min_entry_seconds")]
+    pub min_entry_seconds: Option<u64>, // Tracker entries shorter than this are discarded; None keeps everything
+}
+
+fn default_max_session_seconds() -> Option<u64> {
+    Some(DEFAULT_MAX_SESSION_SECONDS)
+}
+
+fn default_usage_snapshot_seconds() -> Option<u64> {
+    Some(DEFAULT_USAGE_SNAPSHOT_SECONDS)
+}
+
+fn default_min_entry_seconds() -> Option<u64> {
+    Some(DEFAULT_MIN_ENTRY_SECONDS)
+}
+
+impl Default for TrackingSettings {
+    fn default() -> Self {
+        Self {
+            max_session_seconds: default_max_session_seconds(),
+            usage_snapshot_seconds: default_usage_snapshot_seconds(),
+            tracking_mode: TrackingMode::default(),
+            min_entry_seconds: default_min_entry_seconds(),
+        }
+    }
+}
+
+// Apply a change, persist it and make it the active settings
+fn update<F>(change: F) -> Result<TrackingSettings, String>
+where
+    F: FnOnce(&mut TrackingSettings),
+{
+    let mut saved = None;
+    SETTINGS_FILE.update(
+        |settings: &mut TrackingSettings| {
+            change(settings);
+            Ok(())
+        },
+        |settings| {
+            if let Ok(mut active) = SETTINGS.lock() {
+                *active = settings.clone();
+            }
+            saved = Some(settings.clone());
+        },
+    )?;
+    Ok(saved.unwrap_or_default())
+}
+
+/// Set the directory the settings file lives in (the app data dir) and load it
+pub fn set_settings_dir(dir: PathBuf) {
+    SETTINGS_FILE.set_dir(dir);
+    let settings: TrackingSettings = SETTINGS_FILE.read();
+    if let Ok(mut active) = SETTINGS.lock() {
+        *active = settings;
+    }
+}
+
+/// Settings currently in effect
+pub fn current() -> TrackingSettings {
+    SETTINGS.lock().map(|settings| settings.clone()).unwrap_or_default()
+}
+
+/// Longest a single entry may run before it is split, if splitting is enabled
+pub fn max_session_seconds() -> Option<u64> {
+    current().max_session_seconds
+}
+
+/// Change the session cap; None turns splitting off
+pub fn set_max_session_seconds(seconds: Option<u64>) -> Result<TrackingSettings, String> {
+    if let Some(seconds) = seconds {
+        if seconds < MIN_MAX_SESSION_SECONDS {
+            return Err(format!("Maximum session length must be at least {} seconds", MIN_MAX_SESSION_SECONDS));
+        }
+    }
+    update(|settings| settings.max_session_seconds = seconds)
+}
+
+/// Seconds between usage snapshots, if they are enabled
+pub fn usage_snapshot_seconds() -> Option<u64> {
+    current().usage_snapshot_seconds
+}
+
+/// Change how often usage snapshots are emitted; None turns them off
+pub fn set_usage_snapshot_seconds(seconds: Option<u64>) -> Result<TrackingSettings, String> {
+    if let Some(seconds) = seconds {
+        if seconds < MIN_USAGE_SNAPSHOT_SECONDS {
+            return Err(format!("Usage snapshot interval must be at least {} seconds", MIN_USAGE_SNAPSHOT_SECONDS));
+        }
+    }
+    update(|settings| settings.usage_snapshot_seconds = seconds)
+}
+
+pub fn tracking_mode() -> TrackingMode {
+    current().tracking_mode
+}
+
+pub fn set_tracking_mode(mode: TrackingMode) -> Result<TrackingSettings, String> {
+    update(|settings| settings.tracking_mode = mode)
+}
+
+/// Shortest tracker entry worth keeping, if short entries are discarded
+pub fn min_entry_seconds() -> Option<u64> {
+    current().min_entry_seconds.filter(|seconds| *seconds > 0)
+}
+
+/// Change the shortest tracker entry that is kept; None (or 0) keeps every entry
+pub fn set_min_entry_seconds(seconds: Option<u64>) -> Result<TrackingSettings, String> {
+    if let Some(seconds) = seconds {
+        if seconds > MAX_MIN_ENTRY_SECONDS {
+            return Err(format!("Minimum entry duration can be at most {} seconds", MAX_MIN_ENTRY_SECONDS));
+        }
+    }
+    update(|settings| settings.min_entry_seconds = seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_cap_uses_default_but_null_disables_it() {
+        let missing: TrackingSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(missing.max_session_seconds, Some(DEFAULT_MAX_SESSION_SECONDS));
+        assert_eq!(missing.usage_snapshot_seconds, Some(DEFAULT_USAGE_SNAPSHOT_SECONDS));
+        assert_eq!(missing.tracking_mode, TrackingMode::Frontmost);
+        assert_eq!(missing.min_entry_seconds, Some(DEFAULT_MIN_ENTRY_SECONDS));
+
+        let disabled: TrackingSettings = serde_json::from_str(r#"{"max_session_seconds": null}"#).unwrap();
+        assert_eq!(disabled.max_session_seconds, None);
+    }
+
+    #[test]
+    fn rejects_a_minimum_entry_duration_above_the_cap() {
+        let err = set_min_entry_seconds(Some(MAX_MIN_ENTRY_SECONDS + 1)).unwrap_err();
+        assert!(err.contains("at most"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_a_usage_snapshot_interval_below_the_minimum() {
+        let err = set_usage_snapshot_seconds(Some(MIN_USAGE_SNAPSHOT_SECONDS - 1)).unwrap_err();
+        assert!(err.contains("at least 60 seconds"), "{}", err);
+    }
+
+    #[test]
+    fn tracking_mode_uses_lowercase_names() {
+        let visible: TrackingSettings = serde_json::from_str(r#"{"tracking_mode": "visible"}"#).unwrap();
+        assert_eq!(visible.tracking_mode, TrackingMode::Visible);
+        assert!(serde_json::from_str::<TrackingSettings>(r#"{"tracking_mode": "background"}"#).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").expect("email pattern is valid")
+});
+
+/// Check an address looks like `local@domain.tld` before it is stored or sent to the Auth API
+pub fn validate_email(email: &str) -> Result<(), String> {
+    if EMAIL_REGEX.is_match(email) {
+        Ok(())
+    } else {
+        Err("Invalid email format".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_common_addresses() {
+        assert!(validate_email("jane@example.com").is_ok());
+        assert!(validate_email("jane.doe@example.co.uk").is_ok());
+        assert!(validate_email("jane+macro@example.com").is_ok());
+        assert!(validate_email("ops@mail.eu.example.io").is_ok());
+        assert!(validate_email("J_Doe-99@Example.COM").is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        for email in ["", "jane", "jane@", "@example.com", "jane@example", "jane@example.c", "jane doe@example.com", "jane@@example.com", " jane@example.com"] {
+            assert_eq!(validate_email(email), Err("Invalid email format".to_string()), "{:?} should be rejected", email);
+        }
+    }
+}
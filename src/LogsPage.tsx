@@ -237,8 +237,8 @@ function LogsPage({ onLogout, onPageChange }: LogsPageProps) {
 
   const fetchDetectedOS = async () => {
     try {
-      const os = await invoke<string>('get_detected_os');
-      setDetectedOS(os);
+      const info = await invoke<{ os: string; version: string | null; tracking_supported: boolean }>('get_detected_os');
+      setDetectedOS(info.version ?? info.os);
     } catch (error) {
       console.error('Failed to fetch detected OS:', error);
     }
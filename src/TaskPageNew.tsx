@@ -399,7 +399,7 @@ function TaskPage({ onLogout, onPageChange }: TaskPageProps) {
       // Update in backend
       if (isTauri()) {
         await invoke('update_task', {
-          task_id: draggedTask,
+          taskId: draggedTask,
           status: newStatus
         });
         console.log('Task status updated successfully');
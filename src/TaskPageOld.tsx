@@ -310,7 +310,7 @@ function TaskPage({ onLogout, onPageChange }: TaskPageProps) {
       try {
         if (isTauri()) {
           await invoke('update_task', {
-            task_id: taskId,
+            taskId,
             status: backendStatus
           });
           console.log('Task status updated successfully');
@@ -399,12 +399,11 @@ function TaskPage({ onLogout, onPageChange }: TaskPageProps) {
         // Create task in backend
         const newTask = await invoke('create_task', {
           title: newTaskTitle.trim(),
-          project_id: 'default-project', // For now, use a default project
-          assignee_id: currentUserId,
+          assignedUserIds: [currentUserId],
           description: newTaskDescription.trim() || null,
           status: status === 'backlog' ? 'todo' : status === 'in-progress' ? 'in_progress' : status,
           priority: newTaskPriority.toLowerCase(),
-          due_date: newTaskDueDate || null
+          dueDate: newTaskDueDate || null
         }) as BackendTask;
 
         // Transform to frontend format and add to list
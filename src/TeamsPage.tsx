@@ -256,8 +256,8 @@ function TeamsPage({ onLogout, onPageChange }: TeamsPageProps) {
       try {
         if (isTauri()) {
           await invoke('update_user', {
-            user_id: draggedMember,
-            team_id: newTeamId
+            userId: draggedMember,
+            teamId: newTeamId
           });
           console.log('User team updated successfully');
         } else {